@@ -0,0 +1,140 @@
+//! Logs into a sim and saves the object updates it sends, to be used as test
+//! data in `tests/data`.
+//!
+//! The login data is read from `establish-circuit.toml`, like for the
+//! establish-circuit example. Each `ObjectUpdate` is written as a packet to
+//! `output/objects/object_update_<local id>.bin`, which can be read with
+//! `Packet::read`. The data of each object in `ObjectUpdateCompressed` is
+//! written unchanged to `output/objects/object_update_compressed_<n>.bin`,
+//! as expected by `Primitive::from_compressed`.
+
+extern crate futures;
+extern crate opensim_networking;
+#[macro_use]
+extern crate serde_derive;
+extern crate tokio_core;
+extern crate toml;
+
+use opensim_networking::circuit::message_handlers;
+use opensim_networking::logging::{Log, LogLevel};
+use opensim_networking::login::{hash_password, login, LoginRequest, StartLocation};
+use opensim_networking::messages::{MessageInstance, MessageType};
+use opensim_networking::packet::Packet;
+use opensim_networking::simulator::{ConnectInfo, Simulator};
+use opensim_networking::types::Duration;
+
+use futures::{Future, Stream};
+use std::fs::{self, File};
+use std::io::prelude::*;
+use tokio_core::reactor::{Core, Timeout};
+
+/// How long to wait for the sim to send its objects.
+const CAPTURE_SECS: u64 = 20;
+
+const OUTPUT_DIR: &str = "output/objects";
+
+#[derive(Deserialize)]
+struct Config {
+    user: ConfigUser,
+    sim: ConfigSim,
+}
+
+#[derive(Deserialize)]
+struct ConfigUser {
+    first_name: String,
+    last_name: String,
+    password_plain: String,
+}
+
+#[derive(Deserialize)]
+struct ConfigSim {
+    loginuri: String,
+}
+
+fn main() {
+    let log = Log::new_dir("output/logdir", LogLevel::Debug).expect("Setting up log failed.");
+    let config = get_config();
+    fs::create_dir_all(OUTPUT_DIR).expect("Creating output directory failed.");
+
+    let request = LoginRequest {
+        first_name: config.user.first_name,
+        last_name: config.user.last_name,
+        password_hash: hash_password(config.user.password_plain.as_str()),
+        start: StartLocation::Last,
+        options: LoginRequest::default_options(),
+    };
+
+    let mut core = Core::new().unwrap();
+    let login_uri = config.sim.loginuri.parse().expect("Invalid login URI.");
+    let resp = core.run(login(request, login_uri)).expect("Login failed.");
+    println!("Login success.");
+
+    // Subscribe before connecting, the sim sends the objects right away.
+    let message_handlers = message_handlers::Handlers::default();
+    let updates = message_handlers.subscribe_filter(Box::new(|msg: &MessageInstance| {
+        let m_type = msg.message_type();
+        m_type == MessageType::ObjectUpdate || m_type == MessageType::ObjectUpdateCompressed
+    }));
+
+    let sim = Simulator::connect(
+        ConnectInfo::from(resp),
+        message_handlers,
+        core.handle(),
+        log,
+    )
+    .wait()
+    .unwrap();
+
+    let mut sequence_number = 1;
+    let mut compressed_count = 0;
+    let capture = updates.for_each(|msg| {
+        match msg {
+            MessageInstance::ObjectUpdate(ref update) => {
+                for object in &update.object_data {
+                    println!("ObjectUpdate: {}", object.id);
+                }
+                let path = format!(
+                    "{}/object_update_{}.bin",
+                    OUTPUT_DIR, update.object_data[0].id
+                );
+                save_packet(&path, msg.clone(), sequence_number);
+                sequence_number += 1;
+            }
+            MessageInstance::ObjectUpdateCompressed(ref update) => {
+                for object in &update.object_data {
+                    let path = format!(
+                        "{}/object_update_compressed_{}.bin",
+                        OUTPUT_DIR, compressed_count
+                    );
+                    println!("ObjectUpdateCompressed: {}", path);
+                    File::create(path).unwrap().write_all(&object.data).unwrap();
+                    compressed_count += 1;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    });
+    let timeout = Timeout::new(Duration::from_secs(CAPTURE_SECS), &core.handle()).unwrap();
+    let _ = core.run(capture.select2(timeout));
+
+    sim.logout(Duration::from_secs(10)).wait().unwrap();
+    println!("Logged out.");
+}
+
+/// The packet header is not the one sent by the sim, only the message is.
+fn save_packet(path: &str, msg: MessageInstance, sequence_number: u32) {
+    let mut data = Vec::new();
+    Packet::new(msg, sequence_number)
+        .write_to(&mut data)
+        .unwrap();
+    File::create(path).unwrap().write_all(&data).unwrap();
+}
+
+fn get_config() -> Config {
+    let mut file = File::open("establish-circuit.toml")
+        .expect("Copy establish-circuit.toml.tpl to establish-circuit.toml and populate it.");
+    let mut raw_data = String::new();
+    file.read_to_string(&mut raw_data).unwrap();
+    toml::from_str(raw_data.as_str()).expect("invalid TOML")
+}
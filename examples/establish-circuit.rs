@@ -7,15 +7,16 @@ extern crate toml;
 
 use opensim_networking::circuit::message_handlers;
 use opensim_networking::logging::{Log, LogLevel};
-use opensim_networking::login::{hash_password, LoginRequest};
+use opensim_networking::login::{hash_password, login, LoginRequest, StartLocation};
 use opensim_networking::simulator::{ConnectInfo, Simulator};
-use opensim_networking::systems::agent_update::{AgentState, Modality, MoveDirection};
-use opensim_networking::types::{Duration, UnitQuaternion, Vector3};
+use opensim_networking::systems::agent_update::{
+    AgentState, AgentUpdater, AgentUpdaterConfig, Modality, MoveDirection,
+};
+use opensim_networking::types::{Duration, Instant, Vector3};
 
 use futures::future::Future;
 use std::fs::File;
 use std::io::prelude::*;
-use std::thread;
 use tokio_core::reactor::Core;
 
 #[derive(Deserialize)]
@@ -48,19 +49,17 @@ fn main() {
         first_name: config.user.first_name,
         last_name: config.user.last_name,
         password_hash: hash_password(config.user.password_plain.as_str()),
-        start: "last".to_string(),
+        start: StartLocation::Last,
+        options: LoginRequest::default_options(),
     };
 
+    let mut core = Core::new().unwrap();
+
     println!("Performing login request: {:?}", request);
-    let resp = request
-        .perform(config.sim.loginuri.as_str())
-        .expect("Login failed.");
+    let login_uri = config.sim.loginuri.parse().expect("Invalid login URI.");
+    let resp = core.run(login(request, login_uri)).expect("Login failed.");
     // println!("Login success, response = {:?}", resp);
     println!("Login success.");
-    let agent_id = resp.agent_id.clone();
-    let session_id = resp.session_id.clone();
-
-    let mut core = Core::new().unwrap();
 
     let message_handlers = message_handlers::Handlers::default();
     let sim_connect_info = ConnectInfo::from(resp);
@@ -74,29 +73,31 @@ fn main() {
     let texture = core.run(sim.get_texture(&texture_id, &handle)).unwrap();
     println!("texture: {:?}", texture);
 
-    // Let the avatar walk back and forth.
+    // Let the avatar walk and run back and forth.
     // TODO: extract position
-    let z_axis = Vector3::z_axis();
-    let mut state = AgentState {
-        position: Vector3::new(0., 0., 0.),
-        move_direction: Some(MoveDirection::Forward),
-        modality: Modality::Walking,
-        // TODO: This initialization is redundant as it was already done in simulator.rs
-        body_rotation: UnitQuaternion::from_axis_angle(&z_axis, 0.),
-        head_rotation: UnitQuaternion::from_axis_angle(&z_axis, 0.),
-    };
-
-    loop {
-        for _ in 1..40 {
-            let msg = state.to_update_message(agent_id, session_id);
-            // TODO: change this to unreliable (false) after debugging
-            sim.send_message(msg, true).wait().unwrap();
-
-            thread::sleep(Duration::from_millis(50));
+    let mut state = AgentState::new(Vector3::new(0., 0., 0.));
+    state.move_direction = Some(MoveDirection::Forward);
+    let updater = AgentUpdater::new(&sim, state, AgentUpdaterConfig::default(), &handle).unwrap();
+    let state = updater.state();
+    handle.spawn(updater.map_err(|e| println!("Sending agent updates failed: {}", e)));
+
+    for round in 0..5 {
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while Instant::now() < deadline {
+            core.turn(Some(Duration::from_millis(50)));
         }
-        thread::sleep(Duration::from_millis(200));
-        state.move_direction = Some(state.move_direction.unwrap().inverse());
+
+        let mut state = state.lock().unwrap();
+        state.move_direction = state.move_direction.map(|d| d.inverse());
+        state.modality = if round % 2 == 0 {
+            Modality::Running
+        } else {
+            Modality::Walking
+        };
     }
+
+    sim.logout(Duration::from_secs(10)).wait().unwrap();
+    println!("Logged out.");
 }
 
 fn get_config() -> Config {
@@ -1,6 +1,7 @@
 extern crate opensim_networking;
 
-use std::io::BufReader;
+use opensim_networking::messages::all::ImprovedTerseObjectUpdate_ObjectData;
+use opensim_networking::object_update::TerseUpdate;
 
 fn main() {
     let object_data = vec![
@@ -9,8 +10,11 @@ fn main() {
         255, 127, 255, 127, 255, 127, 255, 127, 255, 127, 255, 255, 255, 127, 255, 127, 255, 127,
     ];
 
-    let mut reader = BufReader::new(&object_data[..]);
-    let data = opensim_networking::object_update::read_object_data(&mut reader).unwrap();
+    let data = TerseUpdate::read(&ImprovedTerseObjectUpdate_ObjectData {
+        data: object_data,
+        texture_entry: Vec::new(),
+    })
+    .unwrap();
     println!("object data: {:?}", data);
 
     // TODO: Find actual data for the texture_entry and test our decoder.
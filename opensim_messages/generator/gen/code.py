@@ -222,6 +222,41 @@ def generate_message_type_enum(all_msgnames):
     code += "}\n\n"
     return code
 
+def generate_message_type_impl(messages):
+    def rust_bool(value):
+        return "true" if value else "false"
+
+    def generate_getter(doc, name, r_type, value):
+        code = "\t%s\n" % doc
+        code += "\tpub fn %s(&self) -> %s {\n" % (name, r_type)
+        code += "\t\tmatch *self {\n"
+        for message in messages:
+            code += "\t\t\tMessageType::%s => %s,\n" % (message.name, value(message))
+        code += "\t\t}\n"
+        code += "\t}\n"
+        return code
+
+    code = ""
+    code += "impl MessageType {\n"
+    code += generate_getter(
+        "/// The frequency class of the message, determining how its message number is encoded.",
+        "frequency", "MessageFrequency",
+        lambda m: "MessageFrequency::%s" % m.frequency_class.capitalize())
+    code += "\n"
+    code += generate_getter(
+        "/// Whether the message body is zerocoded when sent.",
+        "is_zerocoded", "bool", lambda m: rust_bool(m.is_zerocoded))
+    code += "\n"
+    code += generate_getter(
+        "/// Whether the message is only accepted over trusted (simulator to simulator) circuits.",
+        "is_trusted", "bool", lambda m: rust_bool(m.is_trusted))
+    code += "\n"
+    code += generate_getter(
+        "/// Whether the message is deprecated, i.e. should no longer be sent over UDP.",
+        "is_deprecated", "bool", lambda m: rust_bool(m.is_deprecated))
+    code += "}\n\n"
+    return code
+
 def generate_message_instance_enum(all_msgnames, messages):
     code = ""
     code += "#[derive(Clone, Debug)]\n"
@@ -136,9 +136,24 @@ class Message:
         # TODO ? self.id = 
         self.trusted = xml_obj.trusted
         self.compression = xml_obj.compression
+        self.deprecation = getattr(xml_obj, "deprecation", None)
         self.blocks = [Block(block, self) for block in xml_obj.block]
         self.doc = to_rust_doc(xml_obj.doc)
 
+    @property
+    def is_trusted(self):
+        return str(self.trusted).lower() in ("true", "trusted")
+
+    @property
+    def is_zerocoded(self):
+        return str(self.compression).lower() in ("true", "zerocoded")
+
+    @property
+    def is_deprecated(self):
+        if self.deprecation is None:
+            return False
+        return str(self.deprecation).lower() not in ("", "false", "notdeprecated")
+
     @property
     def message_num(self):
         bs = [self.id_byte(n)[2:4] for n in reversed(range(4))]
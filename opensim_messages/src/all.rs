@@ -4,7 +4,10 @@
 ///
 /// THIS FILE WAS AUTOMATICALLY GENERATED.
 /// Don't edit manually, instead edit the generator.
-use {Ip4Addr, IpPort, Message, Quaternion, ReadError, Uuid, Vector3, Vector4, WriteMessageResult};
+use {
+    Ip4Addr, IpPort, Message, MessageFrequency, Quaternion, ReadError, Uuid, Vector3, Vector4,
+    WriteMessageResult,
+};
 
 use arrayvec::ArrayVec;
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
@@ -15641,6 +15644,1932 @@ pub enum MessageType {
     ViewerStats,
 }
 
+impl MessageType {
+    /// The frequency class of the message, determining how its message number is encoded.
+    pub fn frequency(&self) -> MessageFrequency {
+        match *self {
+            MessageType::AbortXfer => MessageFrequency::Low,
+            MessageType::AcceptCallingCard => MessageFrequency::Low,
+            MessageType::AcceptFriendship => MessageFrequency::Low,
+            MessageType::ActivateGestures => MessageFrequency::Low,
+            MessageType::ActivateGroup => MessageFrequency::Low,
+            MessageType::AddCircuitCode => MessageFrequency::Low,
+            MessageType::AgentAlertMessage => MessageFrequency::Low,
+            MessageType::AgentAnimation => MessageFrequency::High,
+            MessageType::AgentCachedTexture => MessageFrequency::Low,
+            MessageType::AgentCachedTextureResponse => MessageFrequency::Low,
+            MessageType::AgentDataUpdate => MessageFrequency::Low,
+            MessageType::AgentDataUpdateRequest => MessageFrequency::Low,
+            MessageType::AgentDropGroup => MessageFrequency::Low,
+            MessageType::AgentFOV => MessageFrequency::Low,
+            MessageType::AgentGroupDataUpdate => MessageFrequency::Low,
+            MessageType::AgentHeightWidth => MessageFrequency::Low,
+            MessageType::AgentIsNowWearing => MessageFrequency::Low,
+            MessageType::AgentMovementComplete => MessageFrequency::Low,
+            MessageType::AgentPause => MessageFrequency::Low,
+            MessageType::AgentQuitCopy => MessageFrequency::Low,
+            MessageType::AgentRequestSit => MessageFrequency::High,
+            MessageType::AgentResume => MessageFrequency::Low,
+            MessageType::AgentSetAppearance => MessageFrequency::Low,
+            MessageType::AgentSit => MessageFrequency::High,
+            MessageType::AgentThrottle => MessageFrequency::Low,
+            MessageType::AgentUpdate => MessageFrequency::High,
+            MessageType::AgentWearablesRequest => MessageFrequency::Low,
+            MessageType::AgentWearablesUpdate => MessageFrequency::Low,
+            MessageType::AlertMessage => MessageFrequency::Low,
+            MessageType::AssetUploadComplete => MessageFrequency::Low,
+            MessageType::AssetUploadRequest => MessageFrequency::Low,
+            MessageType::AtomicPassObject => MessageFrequency::High,
+            MessageType::AttachedSound => MessageFrequency::Medium,
+            MessageType::AttachedSoundGainChange => MessageFrequency::Medium,
+            MessageType::AvatarAnimation => MessageFrequency::High,
+            MessageType::AvatarAppearance => MessageFrequency::Low,
+            MessageType::AvatarClassifiedReply => MessageFrequency::Low,
+            MessageType::AvatarGroupsReply => MessageFrequency::Low,
+            MessageType::AvatarInterestsReply => MessageFrequency::Low,
+            MessageType::AvatarInterestsUpdate => MessageFrequency::Low,
+            MessageType::AvatarNotesReply => MessageFrequency::Low,
+            MessageType::AvatarNotesUpdate => MessageFrequency::Low,
+            MessageType::AvatarPickerReply => MessageFrequency::Low,
+            MessageType::AvatarPickerRequest => MessageFrequency::Low,
+            MessageType::AvatarPickerRequestBackend => MessageFrequency::Low,
+            MessageType::AvatarPicksReply => MessageFrequency::Low,
+            MessageType::AvatarPropertiesReply => MessageFrequency::Low,
+            MessageType::AvatarPropertiesRequest => MessageFrequency::Low,
+            MessageType::AvatarPropertiesRequestBackend => MessageFrequency::Low,
+            MessageType::AvatarPropertiesUpdate => MessageFrequency::Low,
+            MessageType::AvatarSitResponse => MessageFrequency::High,
+            MessageType::AvatarTextureUpdate => MessageFrequency::Low,
+            MessageType::BulkUpdateInventory => MessageFrequency::Low,
+            MessageType::BuyObjectInventory => MessageFrequency::Low,
+            MessageType::CameraConstraint => MessageFrequency::High,
+            MessageType::CancelAuction => MessageFrequency::Low,
+            MessageType::ChangeInventoryItemFlags => MessageFrequency::Low,
+            MessageType::ChangeUserRights => MessageFrequency::Low,
+            MessageType::ChatFromSimulator => MessageFrequency::Low,
+            MessageType::ChatFromViewer => MessageFrequency::Low,
+            MessageType::ChatPass => MessageFrequency::Low,
+            MessageType::CheckParcelAuctions => MessageFrequency::Low,
+            MessageType::CheckParcelSales => MessageFrequency::Low,
+            MessageType::ChildAgentAlive => MessageFrequency::High,
+            MessageType::ChildAgentDying => MessageFrequency::Low,
+            MessageType::ChildAgentPositionUpdate => MessageFrequency::High,
+            MessageType::ChildAgentUnknown => MessageFrequency::Low,
+            MessageType::ChildAgentUpdate => MessageFrequency::High,
+            MessageType::ClassifiedDelete => MessageFrequency::Low,
+            MessageType::ClassifiedGodDelete => MessageFrequency::Low,
+            MessageType::ClassifiedInfoReply => MessageFrequency::Low,
+            MessageType::ClassifiedInfoRequest => MessageFrequency::Low,
+            MessageType::ClassifiedInfoUpdate => MessageFrequency::Low,
+            MessageType::ClearFollowCamProperties => MessageFrequency::Low,
+            MessageType::CloseCircuit => MessageFrequency::Fixed,
+            MessageType::CoarseLocationUpdate => MessageFrequency::Medium,
+            MessageType::CompleteAgentMovement => MessageFrequency::Low,
+            MessageType::CompleteAuction => MessageFrequency::Low,
+            MessageType::CompletePingCheck => MessageFrequency::High,
+            MessageType::ConfirmAuctionStart => MessageFrequency::Low,
+            MessageType::ConfirmEnableSimulator => MessageFrequency::Medium,
+            MessageType::ConfirmXferPacket => MessageFrequency::High,
+            MessageType::CopyInventoryFromNotecard => MessageFrequency::Low,
+            MessageType::CopyInventoryItem => MessageFrequency::Low,
+            MessageType::CreateGroupReply => MessageFrequency::Low,
+            MessageType::CreateGroupRequest => MessageFrequency::Low,
+            MessageType::CreateInventoryFolder => MessageFrequency::Low,
+            MessageType::CreateInventoryItem => MessageFrequency::Low,
+            MessageType::CreateLandmarkForEvent => MessageFrequency::Low,
+            MessageType::CreateNewOutfitAttachments => MessageFrequency::Low,
+            MessageType::CreateTrustedCircuit => MessageFrequency::Low,
+            MessageType::CrossedRegion => MessageFrequency::Medium,
+            MessageType::DataHomeLocationReply => MessageFrequency::Low,
+            MessageType::DataHomeLocationRequest => MessageFrequency::Low,
+            MessageType::DataServerLogout => MessageFrequency::Low,
+            MessageType::DeRezAck => MessageFrequency::Low,
+            MessageType::DeRezObject => MessageFrequency::Low,
+            MessageType::DeactivateGestures => MessageFrequency::Low,
+            MessageType::DeclineCallingCard => MessageFrequency::Low,
+            MessageType::DeclineFriendship => MessageFrequency::Low,
+            MessageType::DenyTrustedCircuit => MessageFrequency::Low,
+            MessageType::DerezContainer => MessageFrequency::Low,
+            MessageType::DetachAttachmentIntoInv => MessageFrequency::Low,
+            MessageType::DirClassifiedQuery => MessageFrequency::Low,
+            MessageType::DirClassifiedQueryBackend => MessageFrequency::Low,
+            MessageType::DirClassifiedReply => MessageFrequency::Low,
+            MessageType::DirEventsReply => MessageFrequency::Low,
+            MessageType::DirFindQuery => MessageFrequency::Low,
+            MessageType::DirFindQueryBackend => MessageFrequency::Low,
+            MessageType::DirGroupsReply => MessageFrequency::Low,
+            MessageType::DirLandQuery => MessageFrequency::Low,
+            MessageType::DirLandQueryBackend => MessageFrequency::Low,
+            MessageType::DirLandReply => MessageFrequency::Low,
+            MessageType::DirPeopleReply => MessageFrequency::Low,
+            MessageType::DirPlacesQuery => MessageFrequency::Low,
+            MessageType::DirPlacesQueryBackend => MessageFrequency::Low,
+            MessageType::DirPlacesReply => MessageFrequency::Low,
+            MessageType::DirPopularQuery => MessageFrequency::Low,
+            MessageType::DirPopularQueryBackend => MessageFrequency::Low,
+            MessageType::DirPopularReply => MessageFrequency::Low,
+            MessageType::DisableSimulator => MessageFrequency::Low,
+            MessageType::EconomyData => MessageFrequency::Low,
+            MessageType::EconomyDataRequest => MessageFrequency::Low,
+            MessageType::EdgeDataPacket => MessageFrequency::High,
+            MessageType::EjectGroupMemberReply => MessageFrequency::Low,
+            MessageType::EjectGroupMemberRequest => MessageFrequency::Low,
+            MessageType::EjectUser => MessageFrequency::Low,
+            MessageType::EmailMessageReply => MessageFrequency::Low,
+            MessageType::EmailMessageRequest => MessageFrequency::Low,
+            MessageType::EnableSimulator => MessageFrequency::Low,
+            MessageType::Error => MessageFrequency::Low,
+            MessageType::EstateCovenantReply => MessageFrequency::Low,
+            MessageType::EstateCovenantRequest => MessageFrequency::Low,
+            MessageType::EstateOwnerMessage => MessageFrequency::Low,
+            MessageType::EventGodDelete => MessageFrequency::Low,
+            MessageType::EventInfoReply => MessageFrequency::Low,
+            MessageType::EventInfoRequest => MessageFrequency::Low,
+            MessageType::EventLocationReply => MessageFrequency::Low,
+            MessageType::EventLocationRequest => MessageFrequency::Low,
+            MessageType::EventNotificationAddRequest => MessageFrequency::Low,
+            MessageType::EventNotificationRemoveRequest => MessageFrequency::Low,
+            MessageType::FeatureDisabled => MessageFrequency::Low,
+            MessageType::FetchInventory => MessageFrequency::Low,
+            MessageType::FetchInventoryDescendents => MessageFrequency::Low,
+            MessageType::FetchInventoryReply => MessageFrequency::Low,
+            MessageType::FindAgent => MessageFrequency::Low,
+            MessageType::ForceObjectSelect => MessageFrequency::Low,
+            MessageType::ForceScriptControlRelease => MessageFrequency::Low,
+            MessageType::FormFriendship => MessageFrequency::Low,
+            MessageType::FreezeUser => MessageFrequency::Low,
+            MessageType::GenericMessage => MessageFrequency::Low,
+            MessageType::GetScriptRunning => MessageFrequency::Low,
+            MessageType::GodKickUser => MessageFrequency::Low,
+            MessageType::GodUpdateRegionInfo => MessageFrequency::Low,
+            MessageType::GodlikeMessage => MessageFrequency::Low,
+            MessageType::GrantGodlikePowers => MessageFrequency::Low,
+            MessageType::GrantUserRights => MessageFrequency::Low,
+            MessageType::GroupAccountDetailsReply => MessageFrequency::Low,
+            MessageType::GroupAccountDetailsRequest => MessageFrequency::Low,
+            MessageType::GroupAccountSummaryReply => MessageFrequency::Low,
+            MessageType::GroupAccountSummaryRequest => MessageFrequency::Low,
+            MessageType::GroupAccountTransactionsReply => MessageFrequency::Low,
+            MessageType::GroupAccountTransactionsRequest => MessageFrequency::Low,
+            MessageType::GroupActiveProposalItemReply => MessageFrequency::Low,
+            MessageType::GroupActiveProposalsRequest => MessageFrequency::Low,
+            MessageType::GroupDataUpdate => MessageFrequency::Low,
+            MessageType::GroupMembersReply => MessageFrequency::Low,
+            MessageType::GroupMembersRequest => MessageFrequency::Low,
+            MessageType::GroupNoticeAdd => MessageFrequency::Low,
+            MessageType::GroupNoticeRequest => MessageFrequency::Low,
+            MessageType::GroupNoticesListReply => MessageFrequency::Low,
+            MessageType::GroupNoticesListRequest => MessageFrequency::Low,
+            MessageType::GroupProfileReply => MessageFrequency::Low,
+            MessageType::GroupProfileRequest => MessageFrequency::Low,
+            MessageType::GroupProposalBallot => MessageFrequency::Low,
+            MessageType::GroupRoleChanges => MessageFrequency::Low,
+            MessageType::GroupRoleDataReply => MessageFrequency::Low,
+            MessageType::GroupRoleDataRequest => MessageFrequency::Low,
+            MessageType::GroupRoleMembersReply => MessageFrequency::Low,
+            MessageType::GroupRoleMembersRequest => MessageFrequency::Low,
+            MessageType::GroupRoleUpdate => MessageFrequency::Low,
+            MessageType::GroupTitleUpdate => MessageFrequency::Low,
+            MessageType::GroupTitlesReply => MessageFrequency::Low,
+            MessageType::GroupTitlesRequest => MessageFrequency::Low,
+            MessageType::GroupVoteHistoryItemReply => MessageFrequency::Low,
+            MessageType::GroupVoteHistoryRequest => MessageFrequency::Low,
+            MessageType::HealthMessage => MessageFrequency::Low,
+            MessageType::ImageData => MessageFrequency::High,
+            MessageType::ImageNotInDatabase => MessageFrequency::Low,
+            MessageType::ImagePacket => MessageFrequency::High,
+            MessageType::ImprovedInstantMessage => MessageFrequency::Low,
+            MessageType::ImprovedTerseObjectUpdate => MessageFrequency::High,
+            MessageType::InitiateDownload => MessageFrequency::Low,
+            MessageType::InternalScriptMail => MessageFrequency::Medium,
+            MessageType::InventoryAssetResponse => MessageFrequency::Low,
+            MessageType::InventoryDescendents => MessageFrequency::Low,
+            MessageType::InviteGroupRequest => MessageFrequency::Low,
+            MessageType::InviteGroupResponse => MessageFrequency::Low,
+            MessageType::JoinGroupReply => MessageFrequency::Low,
+            MessageType::JoinGroupRequest => MessageFrequency::Low,
+            MessageType::KickUser => MessageFrequency::Low,
+            MessageType::KickUserAck => MessageFrequency::Low,
+            MessageType::KillChildAgents => MessageFrequency::Low,
+            MessageType::KillObject => MessageFrequency::High,
+            MessageType::LandStatReply => MessageFrequency::Low,
+            MessageType::LandStatRequest => MessageFrequency::Low,
+            MessageType::LayerData => MessageFrequency::High,
+            MessageType::LeaveGroupReply => MessageFrequency::Low,
+            MessageType::LeaveGroupRequest => MessageFrequency::Low,
+            MessageType::LinkInventoryItem => MessageFrequency::Low,
+            MessageType::LiveHelpGroupReply => MessageFrequency::Low,
+            MessageType::LiveHelpGroupRequest => MessageFrequency::Low,
+            MessageType::LoadURL => MessageFrequency::Low,
+            MessageType::LogDwellTime => MessageFrequency::Low,
+            MessageType::LogFailedMoneyTransaction => MessageFrequency::Low,
+            MessageType::LogParcelChanges => MessageFrequency::Low,
+            MessageType::LogTextMessage => MessageFrequency::Low,
+            MessageType::LogoutReply => MessageFrequency::Low,
+            MessageType::LogoutRequest => MessageFrequency::Low,
+            MessageType::MapBlockReply => MessageFrequency::Low,
+            MessageType::MapBlockRequest => MessageFrequency::Low,
+            MessageType::MapItemReply => MessageFrequency::Low,
+            MessageType::MapItemRequest => MessageFrequency::Low,
+            MessageType::MapLayerReply => MessageFrequency::Low,
+            MessageType::MapLayerRequest => MessageFrequency::Low,
+            MessageType::MapNameRequest => MessageFrequency::Low,
+            MessageType::MeanCollisionAlert => MessageFrequency::Low,
+            MessageType::MergeParcel => MessageFrequency::Low,
+            MessageType::ModifyLand => MessageFrequency::Low,
+            MessageType::MoneyBalanceReply => MessageFrequency::Low,
+            MessageType::MoneyBalanceRequest => MessageFrequency::Low,
+            MessageType::MoneyTransferBackend => MessageFrequency::Low,
+            MessageType::MoneyTransferRequest => MessageFrequency::Low,
+            MessageType::MoveInventoryFolder => MessageFrequency::Low,
+            MessageType::MoveInventoryItem => MessageFrequency::Low,
+            MessageType::MoveTaskInventory => MessageFrequency::Low,
+            MessageType::MultipleObjectUpdate => MessageFrequency::Medium,
+            MessageType::MuteListRequest => MessageFrequency::Low,
+            MessageType::MuteListUpdate => MessageFrequency::Low,
+            MessageType::NameValuePair => MessageFrequency::Low,
+            MessageType::NearestLandingRegionReply => MessageFrequency::Low,
+            MessageType::NearestLandingRegionRequest => MessageFrequency::Low,
+            MessageType::NearestLandingRegionUpdated => MessageFrequency::Low,
+            MessageType::NeighborList => MessageFrequency::High,
+            MessageType::NetTest => MessageFrequency::Low,
+            MessageType::ObjectAdd => MessageFrequency::Medium,
+            MessageType::ObjectAttach => MessageFrequency::Low,
+            MessageType::ObjectBuy => MessageFrequency::Low,
+            MessageType::ObjectCategory => MessageFrequency::Low,
+            MessageType::ObjectClickAction => MessageFrequency::Low,
+            MessageType::ObjectDeGrab => MessageFrequency::Low,
+            MessageType::ObjectDelete => MessageFrequency::Low,
+            MessageType::ObjectDelink => MessageFrequency::Low,
+            MessageType::ObjectDescription => MessageFrequency::Low,
+            MessageType::ObjectDeselect => MessageFrequency::Low,
+            MessageType::ObjectDetach => MessageFrequency::Low,
+            MessageType::ObjectDrop => MessageFrequency::Low,
+            MessageType::ObjectDuplicate => MessageFrequency::Low,
+            MessageType::ObjectDuplicateOnRay => MessageFrequency::Low,
+            MessageType::ObjectExportSelected => MessageFrequency::Low,
+            MessageType::ObjectExtraParams => MessageFrequency::Low,
+            MessageType::ObjectFlagUpdate => MessageFrequency::Low,
+            MessageType::ObjectGrab => MessageFrequency::Low,
+            MessageType::ObjectGrabUpdate => MessageFrequency::Low,
+            MessageType::ObjectGroup => MessageFrequency::Low,
+            MessageType::ObjectImage => MessageFrequency::Low,
+            MessageType::ObjectIncludeInSearch => MessageFrequency::Low,
+            MessageType::ObjectLink => MessageFrequency::Low,
+            MessageType::ObjectMaterial => MessageFrequency::Low,
+            MessageType::ObjectName => MessageFrequency::Low,
+            MessageType::ObjectOwner => MessageFrequency::Low,
+            MessageType::ObjectPermissions => MessageFrequency::Low,
+            MessageType::ObjectPosition => MessageFrequency::Medium,
+            MessageType::ObjectProperties => MessageFrequency::Medium,
+            MessageType::ObjectPropertiesFamily => MessageFrequency::Medium,
+            MessageType::ObjectRotation => MessageFrequency::Low,
+            MessageType::ObjectSaleInfo => MessageFrequency::Low,
+            MessageType::ObjectScale => MessageFrequency::Low,
+            MessageType::ObjectSelect => MessageFrequency::Low,
+            MessageType::ObjectShape => MessageFrequency::Low,
+            MessageType::ObjectSpinStart => MessageFrequency::Low,
+            MessageType::ObjectSpinStop => MessageFrequency::Low,
+            MessageType::ObjectSpinUpdate => MessageFrequency::Low,
+            MessageType::ObjectUpdate => MessageFrequency::High,
+            MessageType::ObjectUpdateCached => MessageFrequency::High,
+            MessageType::ObjectUpdateCompressed => MessageFrequency::High,
+            MessageType::OfferCallingCard => MessageFrequency::Low,
+            MessageType::OfflineNotification => MessageFrequency::Low,
+            MessageType::OnlineNotification => MessageFrequency::Low,
+            MessageType::OpenCircuit => MessageFrequency::Fixed,
+            MessageType::PacketAck => MessageFrequency::Fixed,
+            MessageType::ParcelAccessListReply => MessageFrequency::Low,
+            MessageType::ParcelAccessListRequest => MessageFrequency::Low,
+            MessageType::ParcelAccessListUpdate => MessageFrequency::Low,
+            MessageType::ParcelAuctions => MessageFrequency::Low,
+            MessageType::ParcelBuy => MessageFrequency::Low,
+            MessageType::ParcelBuyPass => MessageFrequency::Low,
+            MessageType::ParcelClaim => MessageFrequency::Low,
+            MessageType::ParcelDeedToGroup => MessageFrequency::Low,
+            MessageType::ParcelDisableObjects => MessageFrequency::Low,
+            MessageType::ParcelDivide => MessageFrequency::Low,
+            MessageType::ParcelDwellReply => MessageFrequency::Low,
+            MessageType::ParcelDwellRequest => MessageFrequency::Low,
+            MessageType::ParcelGodForceOwner => MessageFrequency::Low,
+            MessageType::ParcelGodMarkAsContent => MessageFrequency::Low,
+            MessageType::ParcelInfoReply => MessageFrequency::Low,
+            MessageType::ParcelInfoRequest => MessageFrequency::Low,
+            MessageType::ParcelJoin => MessageFrequency::Low,
+            MessageType::ParcelMediaCommandMessage => MessageFrequency::Low,
+            MessageType::ParcelMediaUpdate => MessageFrequency::Low,
+            MessageType::ParcelObjectOwnersReply => MessageFrequency::Low,
+            MessageType::ParcelObjectOwnersRequest => MessageFrequency::Low,
+            MessageType::ParcelOverlay => MessageFrequency::Low,
+            MessageType::ParcelProperties => MessageFrequency::High,
+            MessageType::ParcelPropertiesRequest => MessageFrequency::Medium,
+            MessageType::ParcelPropertiesRequestByID => MessageFrequency::Low,
+            MessageType::ParcelPropertiesUpdate => MessageFrequency::Low,
+            MessageType::ParcelReclaim => MessageFrequency::Low,
+            MessageType::ParcelRelease => MessageFrequency::Low,
+            MessageType::ParcelRename => MessageFrequency::Low,
+            MessageType::ParcelReturnObjects => MessageFrequency::Low,
+            MessageType::ParcelSales => MessageFrequency::Low,
+            MessageType::ParcelSelectObjects => MessageFrequency::Low,
+            MessageType::ParcelSetOtherCleanTime => MessageFrequency::Low,
+            MessageType::PayPriceReply => MessageFrequency::Low,
+            MessageType::PickDelete => MessageFrequency::Low,
+            MessageType::PickGodDelete => MessageFrequency::Low,
+            MessageType::PickInfoReply => MessageFrequency::Low,
+            MessageType::PickInfoUpdate => MessageFrequency::Low,
+            MessageType::PlacesQuery => MessageFrequency::Low,
+            MessageType::PlacesReply => MessageFrequency::Low,
+            MessageType::PreloadSound => MessageFrequency::Medium,
+            MessageType::PurgeInventoryDescendents => MessageFrequency::Low,
+            MessageType::RebakeAvatarTextures => MessageFrequency::Low,
+            MessageType::Redo => MessageFrequency::Low,
+            MessageType::RegionHandleRequest => MessageFrequency::Low,
+            MessageType::RegionHandshake => MessageFrequency::Low,
+            MessageType::RegionHandshakeReply => MessageFrequency::Low,
+            MessageType::RegionIDAndHandleReply => MessageFrequency::Low,
+            MessageType::RegionInfo => MessageFrequency::Low,
+            MessageType::RegionPresenceRequestByHandle => MessageFrequency::Low,
+            MessageType::RegionPresenceRequestByRegionID => MessageFrequency::Low,
+            MessageType::RegionPresenceResponse => MessageFrequency::Low,
+            MessageType::RemoveAttachment => MessageFrequency::Low,
+            MessageType::RemoveInventoryFolder => MessageFrequency::Low,
+            MessageType::RemoveInventoryItem => MessageFrequency::Low,
+            MessageType::RemoveInventoryObjects => MessageFrequency::Low,
+            MessageType::RemoveMuteListEntry => MessageFrequency::Low,
+            MessageType::RemoveNameValuePair => MessageFrequency::Low,
+            MessageType::RemoveParcel => MessageFrequency::Low,
+            MessageType::RemoveTaskInventory => MessageFrequency::Low,
+            MessageType::ReplyTaskInventory => MessageFrequency::Low,
+            MessageType::ReportAutosaveCrash => MessageFrequency::Low,
+            MessageType::RequestGodlikePowers => MessageFrequency::Low,
+            MessageType::RequestImage => MessageFrequency::High,
+            MessageType::RequestInventoryAsset => MessageFrequency::Low,
+            MessageType::RequestMultipleObjects => MessageFrequency::Medium,
+            MessageType::RequestObjectPropertiesFamily => MessageFrequency::Medium,
+            MessageType::RequestParcelTransfer => MessageFrequency::Low,
+            MessageType::RequestPayPrice => MessageFrequency::Low,
+            MessageType::RequestRegionInfo => MessageFrequency::Low,
+            MessageType::RequestTaskInventory => MessageFrequency::Low,
+            MessageType::RequestTrustedCircuit => MessageFrequency::Low,
+            MessageType::RequestXfer => MessageFrequency::Low,
+            MessageType::RetrieveInstantMessages => MessageFrequency::Low,
+            MessageType::RevokePermissions => MessageFrequency::Low,
+            MessageType::RezMultipleAttachmentsFromInv => MessageFrequency::Low,
+            MessageType::RezObject => MessageFrequency::Low,
+            MessageType::RezObjectFromNotecard => MessageFrequency::Low,
+            MessageType::RezRestoreToWorld => MessageFrequency::Low,
+            MessageType::RezScript => MessageFrequency::Low,
+            MessageType::RezSingleAttachmentFromInv => MessageFrequency::Low,
+            MessageType::RoutedMoneyBalanceReply => MessageFrequency::Low,
+            MessageType::RpcChannelReply => MessageFrequency::Low,
+            MessageType::RpcChannelRequest => MessageFrequency::Low,
+            MessageType::RpcScriptReplyInbound => MessageFrequency::Low,
+            MessageType::RpcScriptRequestInbound => MessageFrequency::Low,
+            MessageType::RpcScriptRequestInboundForward => MessageFrequency::Low,
+            MessageType::SaveAssetIntoInventory => MessageFrequency::Low,
+            MessageType::ScriptAnswerYes => MessageFrequency::Low,
+            MessageType::ScriptControlChange => MessageFrequency::Low,
+            MessageType::ScriptDataReply => MessageFrequency::Low,
+            MessageType::ScriptDataRequest => MessageFrequency::Low,
+            MessageType::ScriptDialog => MessageFrequency::Low,
+            MessageType::ScriptDialogReply => MessageFrequency::Low,
+            MessageType::ScriptMailRegistration => MessageFrequency::Low,
+            MessageType::ScriptQuestion => MessageFrequency::Low,
+            MessageType::ScriptReset => MessageFrequency::Low,
+            MessageType::ScriptRunningReply => MessageFrequency::Low,
+            MessageType::ScriptSensorReply => MessageFrequency::Low,
+            MessageType::ScriptSensorRequest => MessageFrequency::Low,
+            MessageType::ScriptTeleportRequest => MessageFrequency::Low,
+            MessageType::SendPostcard => MessageFrequency::Low,
+            MessageType::SendXferPacket => MessageFrequency::High,
+            MessageType::SetAlwaysRun => MessageFrequency::Low,
+            MessageType::SetCPURatio => MessageFrequency::Low,
+            MessageType::SetFollowCamProperties => MessageFrequency::Low,
+            MessageType::SetGroupAcceptNotices => MessageFrequency::Low,
+            MessageType::SetGroupContribution => MessageFrequency::Low,
+            MessageType::SetScriptRunning => MessageFrequency::Low,
+            MessageType::SetSimPresenceInDatabase => MessageFrequency::Low,
+            MessageType::SetSimStatusInDatabase => MessageFrequency::Low,
+            MessageType::SetStartLocation => MessageFrequency::Low,
+            MessageType::SetStartLocationRequest => MessageFrequency::Low,
+            MessageType::SimCrashed => MessageFrequency::Low,
+            MessageType::SimStats => MessageFrequency::Low,
+            MessageType::SimStatus => MessageFrequency::Medium,
+            MessageType::SimWideDeletes => MessageFrequency::Low,
+            MessageType::SimulatorLoad => MessageFrequency::Low,
+            MessageType::SimulatorMapUpdate => MessageFrequency::Low,
+            MessageType::SimulatorPresentAtLocation => MessageFrequency::Low,
+            MessageType::SimulatorReady => MessageFrequency::Low,
+            MessageType::SimulatorSetMap => MessageFrequency::Low,
+            MessageType::SimulatorShutdownRequest => MessageFrequency::Low,
+            MessageType::SimulatorViewerTimeMessage => MessageFrequency::Low,
+            MessageType::SoundTrigger => MessageFrequency::High,
+            MessageType::StartAuction => MessageFrequency::Low,
+            MessageType::StartGroupProposal => MessageFrequency::Low,
+            MessageType::StartLure => MessageFrequency::Low,
+            MessageType::StartPingCheck => MessageFrequency::High,
+            MessageType::StateSave => MessageFrequency::Low,
+            MessageType::SubscribeLoad => MessageFrequency::Low,
+            MessageType::SystemKickUser => MessageFrequency::Low,
+            MessageType::SystemMessage => MessageFrequency::Low,
+            MessageType::TallyVotes => MessageFrequency::Low,
+            MessageType::TelehubInfo => MessageFrequency::Low,
+            MessageType::TeleportCancel => MessageFrequency::Low,
+            MessageType::TeleportFailed => MessageFrequency::Low,
+            MessageType::TeleportFinish => MessageFrequency::Low,
+            MessageType::TeleportLandingStatusChanged => MessageFrequency::Low,
+            MessageType::TeleportLandmarkRequest => MessageFrequency::Low,
+            MessageType::TeleportLocal => MessageFrequency::Low,
+            MessageType::TeleportLocationRequest => MessageFrequency::Low,
+            MessageType::TeleportLureRequest => MessageFrequency::Low,
+            MessageType::TeleportProgress => MessageFrequency::Low,
+            MessageType::TeleportRequest => MessageFrequency::Low,
+            MessageType::TeleportStart => MessageFrequency::Low,
+            MessageType::TerminateFriendship => MessageFrequency::Low,
+            MessageType::TestMessage => MessageFrequency::Low,
+            MessageType::TrackAgent => MessageFrequency::Low,
+            MessageType::TransferAbort => MessageFrequency::Low,
+            MessageType::TransferInfo => MessageFrequency::Low,
+            MessageType::TransferInventory => MessageFrequency::Low,
+            MessageType::TransferInventoryAck => MessageFrequency::Low,
+            MessageType::TransferPacket => MessageFrequency::High,
+            MessageType::TransferRequest => MessageFrequency::Low,
+            MessageType::UUIDGroupNameReply => MessageFrequency::Low,
+            MessageType::UUIDGroupNameRequest => MessageFrequency::Low,
+            MessageType::UUIDNameReply => MessageFrequency::Low,
+            MessageType::UUIDNameRequest => MessageFrequency::Low,
+            MessageType::Undo => MessageFrequency::Low,
+            MessageType::UndoLand => MessageFrequency::Low,
+            MessageType::UnsubscribeLoad => MessageFrequency::Low,
+            MessageType::UpdateAttachment => MessageFrequency::Low,
+            MessageType::UpdateCreateInventoryItem => MessageFrequency::Low,
+            MessageType::UpdateGroupInfo => MessageFrequency::Low,
+            MessageType::UpdateInventoryFolder => MessageFrequency::Low,
+            MessageType::UpdateInventoryItem => MessageFrequency::Low,
+            MessageType::UpdateMuteListEntry => MessageFrequency::Low,
+            MessageType::UpdateParcel => MessageFrequency::Low,
+            MessageType::UpdateSimulator => MessageFrequency::Low,
+            MessageType::UpdateTaskInventory => MessageFrequency::Low,
+            MessageType::UpdateUserInfo => MessageFrequency::Low,
+            MessageType::UseCachedMuteList => MessageFrequency::Low,
+            MessageType::UseCircuitCode => MessageFrequency::Low,
+            MessageType::UserInfoReply => MessageFrequency::Low,
+            MessageType::UserInfoRequest => MessageFrequency::Low,
+            MessageType::UserReport => MessageFrequency::Low,
+            MessageType::UserReportInternal => MessageFrequency::Low,
+            MessageType::VelocityInterpolateOff => MessageFrequency::Low,
+            MessageType::VelocityInterpolateOn => MessageFrequency::Low,
+            MessageType::ViewerEffect => MessageFrequency::Medium,
+            MessageType::ViewerFrozenMessage => MessageFrequency::Low,
+            MessageType::ViewerStartAuction => MessageFrequency::Low,
+            MessageType::ViewerStats => MessageFrequency::Low,
+        }
+    }
+
+    /// Whether the message body is zerocoded when sent.
+    pub fn is_zerocoded(&self) -> bool {
+        match *self {
+            MessageType::AbortXfer => false,
+            MessageType::AcceptCallingCard => false,
+            MessageType::AcceptFriendship => false,
+            MessageType::ActivateGestures => false,
+            MessageType::ActivateGroup => true,
+            MessageType::AddCircuitCode => false,
+            MessageType::AgentAlertMessage => false,
+            MessageType::AgentAnimation => false,
+            MessageType::AgentCachedTexture => false,
+            MessageType::AgentCachedTextureResponse => false,
+            MessageType::AgentDataUpdate => true,
+            MessageType::AgentDataUpdateRequest => false,
+            MessageType::AgentDropGroup => true,
+            MessageType::AgentFOV => false,
+            MessageType::AgentGroupDataUpdate => true,
+            MessageType::AgentHeightWidth => false,
+            MessageType::AgentIsNowWearing => true,
+            MessageType::AgentMovementComplete => false,
+            MessageType::AgentPause => false,
+            MessageType::AgentQuitCopy => false,
+            MessageType::AgentRequestSit => true,
+            MessageType::AgentResume => false,
+            MessageType::AgentSetAppearance => true,
+            MessageType::AgentSit => false,
+            MessageType::AgentThrottle => true,
+            MessageType::AgentUpdate => true,
+            MessageType::AgentWearablesRequest => false,
+            MessageType::AgentWearablesUpdate => true,
+            MessageType::AlertMessage => false,
+            MessageType::AssetUploadComplete => false,
+            MessageType::AssetUploadRequest => false,
+            MessageType::AtomicPassObject => false,
+            MessageType::AttachedSound => false,
+            MessageType::AttachedSoundGainChange => false,
+            MessageType::AvatarAnimation => false,
+            MessageType::AvatarAppearance => true,
+            MessageType::AvatarClassifiedReply => false,
+            MessageType::AvatarGroupsReply => true,
+            MessageType::AvatarInterestsReply => true,
+            MessageType::AvatarInterestsUpdate => true,
+            MessageType::AvatarNotesReply => false,
+            MessageType::AvatarNotesUpdate => false,
+            MessageType::AvatarPickerReply => false,
+            MessageType::AvatarPickerRequest => false,
+            MessageType::AvatarPickerRequestBackend => false,
+            MessageType::AvatarPicksReply => false,
+            MessageType::AvatarPropertiesReply => true,
+            MessageType::AvatarPropertiesRequest => false,
+            MessageType::AvatarPropertiesRequestBackend => false,
+            MessageType::AvatarPropertiesUpdate => true,
+            MessageType::AvatarSitResponse => true,
+            MessageType::AvatarTextureUpdate => true,
+            MessageType::BulkUpdateInventory => true,
+            MessageType::BuyObjectInventory => true,
+            MessageType::CameraConstraint => true,
+            MessageType::CancelAuction => false,
+            MessageType::ChangeInventoryItemFlags => false,
+            MessageType::ChangeUserRights => false,
+            MessageType::ChatFromSimulator => false,
+            MessageType::ChatFromViewer => true,
+            MessageType::ChatPass => true,
+            MessageType::CheckParcelAuctions => false,
+            MessageType::CheckParcelSales => false,
+            MessageType::ChildAgentAlive => false,
+            MessageType::ChildAgentDying => true,
+            MessageType::ChildAgentPositionUpdate => false,
+            MessageType::ChildAgentUnknown => false,
+            MessageType::ChildAgentUpdate => true,
+            MessageType::ClassifiedDelete => false,
+            MessageType::ClassifiedGodDelete => false,
+            MessageType::ClassifiedInfoReply => false,
+            MessageType::ClassifiedInfoRequest => true,
+            MessageType::ClassifiedInfoUpdate => false,
+            MessageType::ClearFollowCamProperties => false,
+            MessageType::CloseCircuit => false,
+            MessageType::CoarseLocationUpdate => false,
+            MessageType::CompleteAgentMovement => false,
+            MessageType::CompleteAuction => false,
+            MessageType::CompletePingCheck => false,
+            MessageType::ConfirmAuctionStart => false,
+            MessageType::ConfirmEnableSimulator => false,
+            MessageType::ConfirmXferPacket => false,
+            MessageType::CopyInventoryFromNotecard => true,
+            MessageType::CopyInventoryItem => true,
+            MessageType::CreateGroupReply => false,
+            MessageType::CreateGroupRequest => true,
+            MessageType::CreateInventoryFolder => false,
+            MessageType::CreateInventoryItem => true,
+            MessageType::CreateLandmarkForEvent => true,
+            MessageType::CreateNewOutfitAttachments => false,
+            MessageType::CreateTrustedCircuit => false,
+            MessageType::CrossedRegion => false,
+            MessageType::DataHomeLocationReply => false,
+            MessageType::DataHomeLocationRequest => true,
+            MessageType::DataServerLogout => false,
+            MessageType::DeRezAck => false,
+            MessageType::DeRezObject => true,
+            MessageType::DeactivateGestures => false,
+            MessageType::DeclineCallingCard => false,
+            MessageType::DeclineFriendship => false,
+            MessageType::DenyTrustedCircuit => false,
+            MessageType::DerezContainer => true,
+            MessageType::DetachAttachmentIntoInv => false,
+            MessageType::DirClassifiedQuery => true,
+            MessageType::DirClassifiedQueryBackend => true,
+            MessageType::DirClassifiedReply => true,
+            MessageType::DirEventsReply => true,
+            MessageType::DirFindQuery => true,
+            MessageType::DirFindQueryBackend => true,
+            MessageType::DirGroupsReply => true,
+            MessageType::DirLandQuery => true,
+            MessageType::DirLandQueryBackend => true,
+            MessageType::DirLandReply => true,
+            MessageType::DirPeopleReply => true,
+            MessageType::DirPlacesQuery => true,
+            MessageType::DirPlacesQueryBackend => true,
+            MessageType::DirPlacesReply => true,
+            MessageType::DirPopularQuery => true,
+            MessageType::DirPopularQueryBackend => true,
+            MessageType::DirPopularReply => true,
+            MessageType::DisableSimulator => false,
+            MessageType::EconomyData => true,
+            MessageType::EconomyDataRequest => false,
+            MessageType::EdgeDataPacket => true,
+            MessageType::EjectGroupMemberReply => false,
+            MessageType::EjectGroupMemberRequest => false,
+            MessageType::EjectUser => false,
+            MessageType::EmailMessageReply => false,
+            MessageType::EmailMessageRequest => false,
+            MessageType::EnableSimulator => false,
+            MessageType::Error => true,
+            MessageType::EstateCovenantReply => false,
+            MessageType::EstateCovenantRequest => false,
+            MessageType::EstateOwnerMessage => true,
+            MessageType::EventGodDelete => false,
+            MessageType::EventInfoReply => false,
+            MessageType::EventInfoRequest => false,
+            MessageType::EventLocationReply => true,
+            MessageType::EventLocationRequest => true,
+            MessageType::EventNotificationAddRequest => false,
+            MessageType::EventNotificationRemoveRequest => false,
+            MessageType::FeatureDisabled => false,
+            MessageType::FetchInventory => true,
+            MessageType::FetchInventoryDescendents => true,
+            MessageType::FetchInventoryReply => true,
+            MessageType::FindAgent => false,
+            MessageType::ForceObjectSelect => false,
+            MessageType::ForceScriptControlRelease => false,
+            MessageType::FormFriendship => false,
+            MessageType::FreezeUser => false,
+            MessageType::GenericMessage => true,
+            MessageType::GetScriptRunning => false,
+            MessageType::GodKickUser => false,
+            MessageType::GodUpdateRegionInfo => true,
+            MessageType::GodlikeMessage => true,
+            MessageType::GrantGodlikePowers => false,
+            MessageType::GrantUserRights => false,
+            MessageType::GroupAccountDetailsReply => true,
+            MessageType::GroupAccountDetailsRequest => true,
+            MessageType::GroupAccountSummaryReply => true,
+            MessageType::GroupAccountSummaryRequest => true,
+            MessageType::GroupAccountTransactionsReply => true,
+            MessageType::GroupAccountTransactionsRequest => true,
+            MessageType::GroupActiveProposalItemReply => true,
+            MessageType::GroupActiveProposalsRequest => false,
+            MessageType::GroupDataUpdate => true,
+            MessageType::GroupMembersReply => true,
+            MessageType::GroupMembersRequest => false,
+            MessageType::GroupNoticeAdd => false,
+            MessageType::GroupNoticeRequest => false,
+            MessageType::GroupNoticesListReply => false,
+            MessageType::GroupNoticesListRequest => false,
+            MessageType::GroupProfileReply => true,
+            MessageType::GroupProfileRequest => false,
+            MessageType::GroupProposalBallot => false,
+            MessageType::GroupRoleChanges => false,
+            MessageType::GroupRoleDataReply => false,
+            MessageType::GroupRoleDataRequest => false,
+            MessageType::GroupRoleMembersReply => false,
+            MessageType::GroupRoleMembersRequest => false,
+            MessageType::GroupRoleUpdate => false,
+            MessageType::GroupTitleUpdate => false,
+            MessageType::GroupTitlesReply => true,
+            MessageType::GroupTitlesRequest => false,
+            MessageType::GroupVoteHistoryItemReply => true,
+            MessageType::GroupVoteHistoryRequest => false,
+            MessageType::HealthMessage => true,
+            MessageType::ImageData => false,
+            MessageType::ImageNotInDatabase => false,
+            MessageType::ImagePacket => false,
+            MessageType::ImprovedInstantMessage => true,
+            MessageType::ImprovedTerseObjectUpdate => false,
+            MessageType::InitiateDownload => false,
+            MessageType::InternalScriptMail => false,
+            MessageType::InventoryAssetResponse => false,
+            MessageType::InventoryDescendents => true,
+            MessageType::InviteGroupRequest => false,
+            MessageType::InviteGroupResponse => false,
+            MessageType::JoinGroupReply => false,
+            MessageType::JoinGroupRequest => true,
+            MessageType::KickUser => false,
+            MessageType::KickUserAck => false,
+            MessageType::KillChildAgents => false,
+            MessageType::KillObject => false,
+            MessageType::LandStatReply => false,
+            MessageType::LandStatRequest => false,
+            MessageType::LayerData => false,
+            MessageType::LeaveGroupReply => false,
+            MessageType::LeaveGroupRequest => false,
+            MessageType::LinkInventoryItem => true,
+            MessageType::LiveHelpGroupReply => false,
+            MessageType::LiveHelpGroupRequest => false,
+            MessageType::LoadURL => false,
+            MessageType::LogDwellTime => true,
+            MessageType::LogFailedMoneyTransaction => true,
+            MessageType::LogParcelChanges => true,
+            MessageType::LogTextMessage => true,
+            MessageType::LogoutReply => true,
+            MessageType::LogoutRequest => false,
+            MessageType::MapBlockReply => false,
+            MessageType::MapBlockRequest => false,
+            MessageType::MapItemReply => false,
+            MessageType::MapItemRequest => false,
+            MessageType::MapLayerReply => false,
+            MessageType::MapLayerRequest => false,
+            MessageType::MapNameRequest => false,
+            MessageType::MeanCollisionAlert => true,
+            MessageType::MergeParcel => false,
+            MessageType::ModifyLand => true,
+            MessageType::MoneyBalanceReply => true,
+            MessageType::MoneyBalanceRequest => true,
+            MessageType::MoneyTransferBackend => true,
+            MessageType::MoneyTransferRequest => true,
+            MessageType::MoveInventoryFolder => true,
+            MessageType::MoveInventoryItem => true,
+            MessageType::MoveTaskInventory => false,
+            MessageType::MultipleObjectUpdate => true,
+            MessageType::MuteListRequest => false,
+            MessageType::MuteListUpdate => false,
+            MessageType::NameValuePair => false,
+            MessageType::NearestLandingRegionReply => false,
+            MessageType::NearestLandingRegionRequest => false,
+            MessageType::NearestLandingRegionUpdated => false,
+            MessageType::NeighborList => false,
+            MessageType::NetTest => false,
+            MessageType::ObjectAdd => true,
+            MessageType::ObjectAttach => true,
+            MessageType::ObjectBuy => true,
+            MessageType::ObjectCategory => true,
+            MessageType::ObjectClickAction => true,
+            MessageType::ObjectDeGrab => false,
+            MessageType::ObjectDelete => false,
+            MessageType::ObjectDelink => false,
+            MessageType::ObjectDescription => true,
+            MessageType::ObjectDeselect => true,
+            MessageType::ObjectDetach => false,
+            MessageType::ObjectDrop => false,
+            MessageType::ObjectDuplicate => true,
+            MessageType::ObjectDuplicateOnRay => true,
+            MessageType::ObjectExportSelected => true,
+            MessageType::ObjectExtraParams => true,
+            MessageType::ObjectFlagUpdate => true,
+            MessageType::ObjectGrab => true,
+            MessageType::ObjectGrabUpdate => true,
+            MessageType::ObjectGroup => true,
+            MessageType::ObjectImage => true,
+            MessageType::ObjectIncludeInSearch => false,
+            MessageType::ObjectLink => false,
+            MessageType::ObjectMaterial => true,
+            MessageType::ObjectName => true,
+            MessageType::ObjectOwner => true,
+            MessageType::ObjectPermissions => true,
+            MessageType::ObjectPosition => true,
+            MessageType::ObjectProperties => true,
+            MessageType::ObjectPropertiesFamily => true,
+            MessageType::ObjectRotation => true,
+            MessageType::ObjectSaleInfo => true,
+            MessageType::ObjectScale => true,
+            MessageType::ObjectSelect => true,
+            MessageType::ObjectShape => true,
+            MessageType::ObjectSpinStart => true,
+            MessageType::ObjectSpinStop => true,
+            MessageType::ObjectSpinUpdate => true,
+            MessageType::ObjectUpdate => true,
+            MessageType::ObjectUpdateCached => false,
+            MessageType::ObjectUpdateCompressed => false,
+            MessageType::OfferCallingCard => false,
+            MessageType::OfflineNotification => false,
+            MessageType::OnlineNotification => false,
+            MessageType::OpenCircuit => false,
+            MessageType::PacketAck => false,
+            MessageType::ParcelAccessListReply => true,
+            MessageType::ParcelAccessListRequest => true,
+            MessageType::ParcelAccessListUpdate => true,
+            MessageType::ParcelAuctions => false,
+            MessageType::ParcelBuy => true,
+            MessageType::ParcelBuyPass => false,
+            MessageType::ParcelClaim => true,
+            MessageType::ParcelDeedToGroup => false,
+            MessageType::ParcelDisableObjects => true,
+            MessageType::ParcelDivide => false,
+            MessageType::ParcelDwellReply => false,
+            MessageType::ParcelDwellRequest => false,
+            MessageType::ParcelGodForceOwner => true,
+            MessageType::ParcelGodMarkAsContent => false,
+            MessageType::ParcelInfoReply => true,
+            MessageType::ParcelInfoRequest => false,
+            MessageType::ParcelJoin => false,
+            MessageType::ParcelMediaCommandMessage => false,
+            MessageType::ParcelMediaUpdate => false,
+            MessageType::ParcelObjectOwnersReply => true,
+            MessageType::ParcelObjectOwnersRequest => false,
+            MessageType::ParcelOverlay => true,
+            MessageType::ParcelProperties => false,
+            MessageType::ParcelPropertiesRequest => true,
+            MessageType::ParcelPropertiesRequestByID => true,
+            MessageType::ParcelPropertiesUpdate => true,
+            MessageType::ParcelReclaim => false,
+            MessageType::ParcelRelease => false,
+            MessageType::ParcelRename => false,
+            MessageType::ParcelReturnObjects => true,
+            MessageType::ParcelSales => false,
+            MessageType::ParcelSelectObjects => true,
+            MessageType::ParcelSetOtherCleanTime => true,
+            MessageType::PayPriceReply => false,
+            MessageType::PickDelete => false,
+            MessageType::PickGodDelete => false,
+            MessageType::PickInfoReply => false,
+            MessageType::PickInfoUpdate => false,
+            MessageType::PlacesQuery => true,
+            MessageType::PlacesReply => true,
+            MessageType::PreloadSound => false,
+            MessageType::PurgeInventoryDescendents => false,
+            MessageType::RebakeAvatarTextures => false,
+            MessageType::Redo => false,
+            MessageType::RegionHandleRequest => false,
+            MessageType::RegionHandshake => true,
+            MessageType::RegionHandshakeReply => true,
+            MessageType::RegionIDAndHandleReply => false,
+            MessageType::RegionInfo => true,
+            MessageType::RegionPresenceRequestByHandle => false,
+            MessageType::RegionPresenceRequestByRegionID => false,
+            MessageType::RegionPresenceResponse => true,
+            MessageType::RemoveAttachment => false,
+            MessageType::RemoveInventoryFolder => false,
+            MessageType::RemoveInventoryItem => false,
+            MessageType::RemoveInventoryObjects => false,
+            MessageType::RemoveMuteListEntry => false,
+            MessageType::RemoveNameValuePair => false,
+            MessageType::RemoveParcel => false,
+            MessageType::RemoveTaskInventory => true,
+            MessageType::ReplyTaskInventory => true,
+            MessageType::ReportAutosaveCrash => false,
+            MessageType::RequestGodlikePowers => false,
+            MessageType::RequestImage => false,
+            MessageType::RequestInventoryAsset => false,
+            MessageType::RequestMultipleObjects => true,
+            MessageType::RequestObjectPropertiesFamily => true,
+            MessageType::RequestParcelTransfer => true,
+            MessageType::RequestPayPrice => false,
+            MessageType::RequestRegionInfo => false,
+            MessageType::RequestTaskInventory => false,
+            MessageType::RequestTrustedCircuit => false,
+            MessageType::RequestXfer => true,
+            MessageType::RetrieveInstantMessages => false,
+            MessageType::RevokePermissions => false,
+            MessageType::RezMultipleAttachmentsFromInv => true,
+            MessageType::RezObject => true,
+            MessageType::RezObjectFromNotecard => true,
+            MessageType::RezRestoreToWorld => false,
+            MessageType::RezScript => true,
+            MessageType::RezSingleAttachmentFromInv => true,
+            MessageType::RoutedMoneyBalanceReply => true,
+            MessageType::RpcChannelReply => false,
+            MessageType::RpcChannelRequest => false,
+            MessageType::RpcScriptReplyInbound => false,
+            MessageType::RpcScriptRequestInbound => false,
+            MessageType::RpcScriptRequestInboundForward => false,
+            MessageType::SaveAssetIntoInventory => false,
+            MessageType::ScriptAnswerYes => false,
+            MessageType::ScriptControlChange => false,
+            MessageType::ScriptDataReply => false,
+            MessageType::ScriptDataRequest => false,
+            MessageType::ScriptDialog => true,
+            MessageType::ScriptDialogReply => true,
+            MessageType::ScriptMailRegistration => false,
+            MessageType::ScriptQuestion => false,
+            MessageType::ScriptReset => false,
+            MessageType::ScriptRunningReply => false,
+            MessageType::ScriptSensorReply => true,
+            MessageType::ScriptSensorRequest => true,
+            MessageType::ScriptTeleportRequest => false,
+            MessageType::SendPostcard => false,
+            MessageType::SendXferPacket => false,
+            MessageType::SetAlwaysRun => false,
+            MessageType::SetCPURatio => false,
+            MessageType::SetFollowCamProperties => false,
+            MessageType::SetGroupAcceptNotices => false,
+            MessageType::SetGroupContribution => false,
+            MessageType::SetScriptRunning => false,
+            MessageType::SetSimPresenceInDatabase => false,
+            MessageType::SetSimStatusInDatabase => false,
+            MessageType::SetStartLocation => true,
+            MessageType::SetStartLocationRequest => true,
+            MessageType::SimCrashed => false,
+            MessageType::SimStats => false,
+            MessageType::SimStatus => false,
+            MessageType::SimWideDeletes => false,
+            MessageType::SimulatorLoad => false,
+            MessageType::SimulatorMapUpdate => false,
+            MessageType::SimulatorPresentAtLocation => false,
+            MessageType::SimulatorReady => true,
+            MessageType::SimulatorSetMap => false,
+            MessageType::SimulatorShutdownRequest => false,
+            MessageType::SimulatorViewerTimeMessage => false,
+            MessageType::SoundTrigger => false,
+            MessageType::StartAuction => false,
+            MessageType::StartGroupProposal => true,
+            MessageType::StartLure => false,
+            MessageType::StartPingCheck => false,
+            MessageType::StateSave => false,
+            MessageType::SubscribeLoad => false,
+            MessageType::SystemKickUser => false,
+            MessageType::SystemMessage => true,
+            MessageType::TallyVotes => false,
+            MessageType::TelehubInfo => false,
+            MessageType::TeleportCancel => false,
+            MessageType::TeleportFailed => false,
+            MessageType::TeleportFinish => false,
+            MessageType::TeleportLandingStatusChanged => false,
+            MessageType::TeleportLandmarkRequest => true,
+            MessageType::TeleportLocal => false,
+            MessageType::TeleportLocationRequest => false,
+            MessageType::TeleportLureRequest => false,
+            MessageType::TeleportProgress => false,
+            MessageType::TeleportRequest => false,
+            MessageType::TeleportStart => false,
+            MessageType::TerminateFriendship => false,
+            MessageType::TestMessage => true,
+            MessageType::TrackAgent => false,
+            MessageType::TransferAbort => true,
+            MessageType::TransferInfo => true,
+            MessageType::TransferInventory => true,
+            MessageType::TransferInventoryAck => true,
+            MessageType::TransferPacket => false,
+            MessageType::TransferRequest => true,
+            MessageType::UUIDGroupNameReply => false,
+            MessageType::UUIDGroupNameRequest => false,
+            MessageType::UUIDNameReply => false,
+            MessageType::UUIDNameRequest => false,
+            MessageType::Undo => false,
+            MessageType::UndoLand => false,
+            MessageType::UnsubscribeLoad => false,
+            MessageType::UpdateAttachment => false,
+            MessageType::UpdateCreateInventoryItem => true,
+            MessageType::UpdateGroupInfo => true,
+            MessageType::UpdateInventoryFolder => false,
+            MessageType::UpdateInventoryItem => true,
+            MessageType::UpdateMuteListEntry => false,
+            MessageType::UpdateParcel => true,
+            MessageType::UpdateSimulator => false,
+            MessageType::UpdateTaskInventory => true,
+            MessageType::UpdateUserInfo => false,
+            MessageType::UseCachedMuteList => false,
+            MessageType::UseCircuitCode => false,
+            MessageType::UserInfoReply => false,
+            MessageType::UserInfoRequest => false,
+            MessageType::UserReport => true,
+            MessageType::UserReportInternal => true,
+            MessageType::VelocityInterpolateOff => false,
+            MessageType::VelocityInterpolateOn => false,
+            MessageType::ViewerEffect => true,
+            MessageType::ViewerFrozenMessage => false,
+            MessageType::ViewerStartAuction => false,
+            MessageType::ViewerStats => true,
+        }
+    }
+
+    /// Whether the message is only accepted over trusted (simulator to simulator) circuits.
+    pub fn is_trusted(&self) -> bool {
+        match *self {
+            MessageType::AbortXfer => false,
+            MessageType::AcceptCallingCard => false,
+            MessageType::AcceptFriendship => false,
+            MessageType::ActivateGestures => false,
+            MessageType::ActivateGroup => false,
+            MessageType::AddCircuitCode => true,
+            MessageType::AgentAlertMessage => true,
+            MessageType::AgentAnimation => false,
+            MessageType::AgentCachedTexture => false,
+            MessageType::AgentCachedTextureResponse => true,
+            MessageType::AgentDataUpdate => true,
+            MessageType::AgentDataUpdateRequest => false,
+            MessageType::AgentDropGroup => true,
+            MessageType::AgentFOV => false,
+            MessageType::AgentGroupDataUpdate => true,
+            MessageType::AgentHeightWidth => false,
+            MessageType::AgentIsNowWearing => false,
+            MessageType::AgentMovementComplete => false,
+            MessageType::AgentPause => false,
+            MessageType::AgentQuitCopy => false,
+            MessageType::AgentRequestSit => false,
+            MessageType::AgentResume => false,
+            MessageType::AgentSetAppearance => false,
+            MessageType::AgentSit => false,
+            MessageType::AgentThrottle => false,
+            MessageType::AgentUpdate => false,
+            MessageType::AgentWearablesRequest => false,
+            MessageType::AgentWearablesUpdate => true,
+            MessageType::AlertMessage => true,
+            MessageType::AssetUploadComplete => false,
+            MessageType::AssetUploadRequest => false,
+            MessageType::AtomicPassObject => true,
+            MessageType::AttachedSound => true,
+            MessageType::AttachedSoundGainChange => true,
+            MessageType::AvatarAnimation => true,
+            MessageType::AvatarAppearance => true,
+            MessageType::AvatarClassifiedReply => true,
+            MessageType::AvatarGroupsReply => true,
+            MessageType::AvatarInterestsReply => true,
+            MessageType::AvatarInterestsUpdate => false,
+            MessageType::AvatarNotesReply => true,
+            MessageType::AvatarNotesUpdate => false,
+            MessageType::AvatarPickerReply => true,
+            MessageType::AvatarPickerRequest => false,
+            MessageType::AvatarPickerRequestBackend => true,
+            MessageType::AvatarPicksReply => true,
+            MessageType::AvatarPropertiesReply => true,
+            MessageType::AvatarPropertiesRequest => false,
+            MessageType::AvatarPropertiesRequestBackend => true,
+            MessageType::AvatarPropertiesUpdate => false,
+            MessageType::AvatarSitResponse => true,
+            MessageType::AvatarTextureUpdate => true,
+            MessageType::BulkUpdateInventory => true,
+            MessageType::BuyObjectInventory => false,
+            MessageType::CameraConstraint => true,
+            MessageType::CancelAuction => true,
+            MessageType::ChangeInventoryItemFlags => false,
+            MessageType::ChangeUserRights => true,
+            MessageType::ChatFromSimulator => true,
+            MessageType::ChatFromViewer => false,
+            MessageType::ChatPass => true,
+            MessageType::CheckParcelAuctions => true,
+            MessageType::CheckParcelSales => true,
+            MessageType::ChildAgentAlive => true,
+            MessageType::ChildAgentDying => true,
+            MessageType::ChildAgentPositionUpdate => true,
+            MessageType::ChildAgentUnknown => true,
+            MessageType::ChildAgentUpdate => true,
+            MessageType::ClassifiedDelete => false,
+            MessageType::ClassifiedGodDelete => false,
+            MessageType::ClassifiedInfoReply => true,
+            MessageType::ClassifiedInfoRequest => false,
+            MessageType::ClassifiedInfoUpdate => false,
+            MessageType::ClearFollowCamProperties => true,
+            MessageType::CloseCircuit => false,
+            MessageType::CoarseLocationUpdate => true,
+            MessageType::CompleteAgentMovement => false,
+            MessageType::CompleteAuction => true,
+            MessageType::CompletePingCheck => false,
+            MessageType::ConfirmAuctionStart => true,
+            MessageType::ConfirmEnableSimulator => true,
+            MessageType::ConfirmXferPacket => false,
+            MessageType::CopyInventoryFromNotecard => false,
+            MessageType::CopyInventoryItem => false,
+            MessageType::CreateGroupReply => true,
+            MessageType::CreateGroupRequest => false,
+            MessageType::CreateInventoryFolder => false,
+            MessageType::CreateInventoryItem => false,
+            MessageType::CreateLandmarkForEvent => false,
+            MessageType::CreateNewOutfitAttachments => false,
+            MessageType::CreateTrustedCircuit => false,
+            MessageType::CrossedRegion => true,
+            MessageType::DataHomeLocationReply => true,
+            MessageType::DataHomeLocationRequest => true,
+            MessageType::DataServerLogout => true,
+            MessageType::DeRezAck => true,
+            MessageType::DeRezObject => false,
+            MessageType::DeactivateGestures => false,
+            MessageType::DeclineCallingCard => false,
+            MessageType::DeclineFriendship => false,
+            MessageType::DenyTrustedCircuit => false,
+            MessageType::DerezContainer => true,
+            MessageType::DetachAttachmentIntoInv => false,
+            MessageType::DirClassifiedQuery => false,
+            MessageType::DirClassifiedQueryBackend => true,
+            MessageType::DirClassifiedReply => true,
+            MessageType::DirEventsReply => true,
+            MessageType::DirFindQuery => false,
+            MessageType::DirFindQueryBackend => true,
+            MessageType::DirGroupsReply => true,
+            MessageType::DirLandQuery => false,
+            MessageType::DirLandQueryBackend => true,
+            MessageType::DirLandReply => true,
+            MessageType::DirPeopleReply => true,
+            MessageType::DirPlacesQuery => false,
+            MessageType::DirPlacesQueryBackend => true,
+            MessageType::DirPlacesReply => true,
+            MessageType::DirPopularQuery => false,
+            MessageType::DirPopularQueryBackend => true,
+            MessageType::DirPopularReply => true,
+            MessageType::DisableSimulator => true,
+            MessageType::EconomyData => true,
+            MessageType::EconomyDataRequest => false,
+            MessageType::EdgeDataPacket => true,
+            MessageType::EjectGroupMemberReply => true,
+            MessageType::EjectGroupMemberRequest => false,
+            MessageType::EjectUser => false,
+            MessageType::EmailMessageReply => true,
+            MessageType::EmailMessageRequest => true,
+            MessageType::EnableSimulator => true,
+            MessageType::Error => false,
+            MessageType::EstateCovenantReply => true,
+            MessageType::EstateCovenantRequest => false,
+            MessageType::EstateOwnerMessage => false,
+            MessageType::EventGodDelete => false,
+            MessageType::EventInfoReply => true,
+            MessageType::EventInfoRequest => false,
+            MessageType::EventLocationReply => true,
+            MessageType::EventLocationRequest => true,
+            MessageType::EventNotificationAddRequest => false,
+            MessageType::EventNotificationRemoveRequest => false,
+            MessageType::FeatureDisabled => true,
+            MessageType::FetchInventory => false,
+            MessageType::FetchInventoryDescendents => false,
+            MessageType::FetchInventoryReply => true,
+            MessageType::FindAgent => false,
+            MessageType::ForceObjectSelect => true,
+            MessageType::ForceScriptControlRelease => false,
+            MessageType::FormFriendship => true,
+            MessageType::FreezeUser => false,
+            MessageType::GenericMessage => false,
+            MessageType::GetScriptRunning => false,
+            MessageType::GodKickUser => false,
+            MessageType::GodUpdateRegionInfo => false,
+            MessageType::GodlikeMessage => false,
+            MessageType::GrantGodlikePowers => false,
+            MessageType::GrantUserRights => false,
+            MessageType::GroupAccountDetailsReply => true,
+            MessageType::GroupAccountDetailsRequest => false,
+            MessageType::GroupAccountSummaryReply => true,
+            MessageType::GroupAccountSummaryRequest => false,
+            MessageType::GroupAccountTransactionsReply => true,
+            MessageType::GroupAccountTransactionsRequest => false,
+            MessageType::GroupActiveProposalItemReply => true,
+            MessageType::GroupActiveProposalsRequest => false,
+            MessageType::GroupDataUpdate => true,
+            MessageType::GroupMembersReply => true,
+            MessageType::GroupMembersRequest => false,
+            MessageType::GroupNoticeAdd => true,
+            MessageType::GroupNoticeRequest => false,
+            MessageType::GroupNoticesListReply => true,
+            MessageType::GroupNoticesListRequest => false,
+            MessageType::GroupProfileReply => true,
+            MessageType::GroupProfileRequest => false,
+            MessageType::GroupProposalBallot => false,
+            MessageType::GroupRoleChanges => false,
+            MessageType::GroupRoleDataReply => true,
+            MessageType::GroupRoleDataRequest => false,
+            MessageType::GroupRoleMembersReply => true,
+            MessageType::GroupRoleMembersRequest => false,
+            MessageType::GroupRoleUpdate => false,
+            MessageType::GroupTitleUpdate => false,
+            MessageType::GroupTitlesReply => true,
+            MessageType::GroupTitlesRequest => false,
+            MessageType::GroupVoteHistoryItemReply => true,
+            MessageType::GroupVoteHistoryRequest => false,
+            MessageType::HealthMessage => true,
+            MessageType::ImageData => true,
+            MessageType::ImageNotInDatabase => true,
+            MessageType::ImagePacket => true,
+            MessageType::ImprovedInstantMessage => false,
+            MessageType::ImprovedTerseObjectUpdate => true,
+            MessageType::InitiateDownload => false,
+            MessageType::InternalScriptMail => true,
+            MessageType::InventoryAssetResponse => true,
+            MessageType::InventoryDescendents => true,
+            MessageType::InviteGroupRequest => false,
+            MessageType::InviteGroupResponse => true,
+            MessageType::JoinGroupReply => true,
+            MessageType::JoinGroupRequest => false,
+            MessageType::KickUser => true,
+            MessageType::KickUserAck => true,
+            MessageType::KillChildAgents => true,
+            MessageType::KillObject => true,
+            MessageType::LandStatReply => true,
+            MessageType::LandStatRequest => false,
+            MessageType::LayerData => true,
+            MessageType::LeaveGroupReply => true,
+            MessageType::LeaveGroupRequest => false,
+            MessageType::LinkInventoryItem => false,
+            MessageType::LiveHelpGroupReply => true,
+            MessageType::LiveHelpGroupRequest => false,
+            MessageType::LoadURL => true,
+            MessageType::LogDwellTime => true,
+            MessageType::LogFailedMoneyTransaction => true,
+            MessageType::LogParcelChanges => true,
+            MessageType::LogTextMessage => false,
+            MessageType::LogoutReply => true,
+            MessageType::LogoutRequest => false,
+            MessageType::MapBlockReply => true,
+            MessageType::MapBlockRequest => false,
+            MessageType::MapItemReply => true,
+            MessageType::MapItemRequest => false,
+            MessageType::MapLayerReply => true,
+            MessageType::MapLayerRequest => false,
+            MessageType::MapNameRequest => false,
+            MessageType::MeanCollisionAlert => true,
+            MessageType::MergeParcel => true,
+            MessageType::ModifyLand => false,
+            MessageType::MoneyBalanceReply => true,
+            MessageType::MoneyBalanceRequest => false,
+            MessageType::MoneyTransferBackend => true,
+            MessageType::MoneyTransferRequest => false,
+            MessageType::MoveInventoryFolder => false,
+            MessageType::MoveInventoryItem => false,
+            MessageType::MoveTaskInventory => false,
+            MessageType::MultipleObjectUpdate => false,
+            MessageType::MuteListRequest => false,
+            MessageType::MuteListUpdate => true,
+            MessageType::NameValuePair => true,
+            MessageType::NearestLandingRegionReply => true,
+            MessageType::NearestLandingRegionRequest => true,
+            MessageType::NearestLandingRegionUpdated => true,
+            MessageType::NeighborList => true,
+            MessageType::NetTest => false,
+            MessageType::ObjectAdd => false,
+            MessageType::ObjectAttach => false,
+            MessageType::ObjectBuy => false,
+            MessageType::ObjectCategory => false,
+            MessageType::ObjectClickAction => false,
+            MessageType::ObjectDeGrab => false,
+            MessageType::ObjectDelete => false,
+            MessageType::ObjectDelink => false,
+            MessageType::ObjectDescription => false,
+            MessageType::ObjectDeselect => false,
+            MessageType::ObjectDetach => false,
+            MessageType::ObjectDrop => false,
+            MessageType::ObjectDuplicate => false,
+            MessageType::ObjectDuplicateOnRay => false,
+            MessageType::ObjectExportSelected => false,
+            MessageType::ObjectExtraParams => false,
+            MessageType::ObjectFlagUpdate => false,
+            MessageType::ObjectGrab => false,
+            MessageType::ObjectGrabUpdate => false,
+            MessageType::ObjectGroup => false,
+            MessageType::ObjectImage => false,
+            MessageType::ObjectIncludeInSearch => false,
+            MessageType::ObjectLink => false,
+            MessageType::ObjectMaterial => false,
+            MessageType::ObjectName => false,
+            MessageType::ObjectOwner => false,
+            MessageType::ObjectPermissions => false,
+            MessageType::ObjectPosition => false,
+            MessageType::ObjectProperties => true,
+            MessageType::ObjectPropertiesFamily => true,
+            MessageType::ObjectRotation => false,
+            MessageType::ObjectSaleInfo => false,
+            MessageType::ObjectScale => false,
+            MessageType::ObjectSelect => false,
+            MessageType::ObjectShape => false,
+            MessageType::ObjectSpinStart => false,
+            MessageType::ObjectSpinStop => false,
+            MessageType::ObjectSpinUpdate => false,
+            MessageType::ObjectUpdate => true,
+            MessageType::ObjectUpdateCached => true,
+            MessageType::ObjectUpdateCompressed => true,
+            MessageType::OfferCallingCard => false,
+            MessageType::OfflineNotification => false,
+            MessageType::OnlineNotification => false,
+            MessageType::OpenCircuit => false,
+            MessageType::PacketAck => false,
+            MessageType::ParcelAccessListReply => true,
+            MessageType::ParcelAccessListRequest => false,
+            MessageType::ParcelAccessListUpdate => false,
+            MessageType::ParcelAuctions => true,
+            MessageType::ParcelBuy => false,
+            MessageType::ParcelBuyPass => false,
+            MessageType::ParcelClaim => false,
+            MessageType::ParcelDeedToGroup => false,
+            MessageType::ParcelDisableObjects => false,
+            MessageType::ParcelDivide => false,
+            MessageType::ParcelDwellReply => true,
+            MessageType::ParcelDwellRequest => false,
+            MessageType::ParcelGodForceOwner => false,
+            MessageType::ParcelGodMarkAsContent => false,
+            MessageType::ParcelInfoReply => true,
+            MessageType::ParcelInfoRequest => false,
+            MessageType::ParcelJoin => false,
+            MessageType::ParcelMediaCommandMessage => true,
+            MessageType::ParcelMediaUpdate => true,
+            MessageType::ParcelObjectOwnersReply => true,
+            MessageType::ParcelObjectOwnersRequest => false,
+            MessageType::ParcelOverlay => true,
+            MessageType::ParcelProperties => true,
+            MessageType::ParcelPropertiesRequest => false,
+            MessageType::ParcelPropertiesRequestByID => false,
+            MessageType::ParcelPropertiesUpdate => false,
+            MessageType::ParcelReclaim => false,
+            MessageType::ParcelRelease => false,
+            MessageType::ParcelRename => false,
+            MessageType::ParcelReturnObjects => false,
+            MessageType::ParcelSales => true,
+            MessageType::ParcelSelectObjects => false,
+            MessageType::ParcelSetOtherCleanTime => false,
+            MessageType::PayPriceReply => true,
+            MessageType::PickDelete => false,
+            MessageType::PickGodDelete => false,
+            MessageType::PickInfoReply => true,
+            MessageType::PickInfoUpdate => false,
+            MessageType::PlacesQuery => false,
+            MessageType::PlacesReply => true,
+            MessageType::PreloadSound => true,
+            MessageType::PurgeInventoryDescendents => false,
+            MessageType::RebakeAvatarTextures => true,
+            MessageType::Redo => false,
+            MessageType::RegionHandleRequest => false,
+            MessageType::RegionHandshake => true,
+            MessageType::RegionHandshakeReply => false,
+            MessageType::RegionIDAndHandleReply => false,
+            MessageType::RegionInfo => false,
+            MessageType::RegionPresenceRequestByHandle => true,
+            MessageType::RegionPresenceRequestByRegionID => true,
+            MessageType::RegionPresenceResponse => true,
+            MessageType::RemoveAttachment => true,
+            MessageType::RemoveInventoryFolder => false,
+            MessageType::RemoveInventoryItem => false,
+            MessageType::RemoveInventoryObjects => false,
+            MessageType::RemoveMuteListEntry => false,
+            MessageType::RemoveNameValuePair => true,
+            MessageType::RemoveParcel => true,
+            MessageType::RemoveTaskInventory => false,
+            MessageType::ReplyTaskInventory => true,
+            MessageType::ReportAutosaveCrash => false,
+            MessageType::RequestGodlikePowers => false,
+            MessageType::RequestImage => false,
+            MessageType::RequestInventoryAsset => true,
+            MessageType::RequestMultipleObjects => false,
+            MessageType::RequestObjectPropertiesFamily => false,
+            MessageType::RequestParcelTransfer => true,
+            MessageType::RequestPayPrice => false,
+            MessageType::RequestRegionInfo => false,
+            MessageType::RequestTaskInventory => false,
+            MessageType::RequestTrustedCircuit => true,
+            MessageType::RequestXfer => false,
+            MessageType::RetrieveInstantMessages => false,
+            MessageType::RevokePermissions => false,
+            MessageType::RezMultipleAttachmentsFromInv => false,
+            MessageType::RezObject => false,
+            MessageType::RezObjectFromNotecard => false,
+            MessageType::RezRestoreToWorld => false,
+            MessageType::RezScript => false,
+            MessageType::RezSingleAttachmentFromInv => false,
+            MessageType::RoutedMoneyBalanceReply => true,
+            MessageType::RpcChannelReply => true,
+            MessageType::RpcChannelRequest => true,
+            MessageType::RpcScriptReplyInbound => false,
+            MessageType::RpcScriptRequestInbound => false,
+            MessageType::RpcScriptRequestInboundForward => true,
+            MessageType::SaveAssetIntoInventory => true,
+            MessageType::ScriptAnswerYes => false,
+            MessageType::ScriptControlChange => true,
+            MessageType::ScriptDataReply => true,
+            MessageType::ScriptDataRequest => true,
+            MessageType::ScriptDialog => true,
+            MessageType::ScriptDialogReply => false,
+            MessageType::ScriptMailRegistration => true,
+            MessageType::ScriptQuestion => true,
+            MessageType::ScriptReset => false,
+            MessageType::ScriptRunningReply => false,
+            MessageType::ScriptSensorReply => true,
+            MessageType::ScriptSensorRequest => true,
+            MessageType::ScriptTeleportRequest => false,
+            MessageType::SendPostcard => false,
+            MessageType::SendXferPacket => false,
+            MessageType::SetAlwaysRun => false,
+            MessageType::SetCPURatio => false,
+            MessageType::SetFollowCamProperties => true,
+            MessageType::SetGroupAcceptNotices => false,
+            MessageType::SetGroupContribution => false,
+            MessageType::SetScriptRunning => false,
+            MessageType::SetSimPresenceInDatabase => true,
+            MessageType::SetSimStatusInDatabase => true,
+            MessageType::SetStartLocation => true,
+            MessageType::SetStartLocationRequest => false,
+            MessageType::SimCrashed => false,
+            MessageType::SimStats => true,
+            MessageType::SimStatus => true,
+            MessageType::SimWideDeletes => false,
+            MessageType::SimulatorLoad => true,
+            MessageType::SimulatorMapUpdate => true,
+            MessageType::SimulatorPresentAtLocation => true,
+            MessageType::SimulatorReady => true,
+            MessageType::SimulatorSetMap => true,
+            MessageType::SimulatorShutdownRequest => false,
+            MessageType::SimulatorViewerTimeMessage => true,
+            MessageType::SoundTrigger => false,
+            MessageType::StartAuction => true,
+            MessageType::StartGroupProposal => false,
+            MessageType::StartLure => false,
+            MessageType::StartPingCheck => false,
+            MessageType::StateSave => false,
+            MessageType::SubscribeLoad => true,
+            MessageType::SystemKickUser => true,
+            MessageType::SystemMessage => true,
+            MessageType::TallyVotes => true,
+            MessageType::TelehubInfo => true,
+            MessageType::TeleportCancel => false,
+            MessageType::TeleportFailed => true,
+            MessageType::TeleportFinish => true,
+            MessageType::TeleportLandingStatusChanged => true,
+            MessageType::TeleportLandmarkRequest => false,
+            MessageType::TeleportLocal => true,
+            MessageType::TeleportLocationRequest => false,
+            MessageType::TeleportLureRequest => false,
+            MessageType::TeleportProgress => true,
+            MessageType::TeleportRequest => false,
+            MessageType::TeleportStart => true,
+            MessageType::TerminateFriendship => false,
+            MessageType::TestMessage => false,
+            MessageType::TrackAgent => false,
+            MessageType::TransferAbort => false,
+            MessageType::TransferInfo => false,
+            MessageType::TransferInventory => false,
+            MessageType::TransferInventoryAck => false,
+            MessageType::TransferPacket => false,
+            MessageType::TransferRequest => false,
+            MessageType::UUIDGroupNameReply => true,
+            MessageType::UUIDGroupNameRequest => false,
+            MessageType::UUIDNameReply => true,
+            MessageType::UUIDNameRequest => false,
+            MessageType::Undo => false,
+            MessageType::UndoLand => false,
+            MessageType::UnsubscribeLoad => true,
+            MessageType::UpdateAttachment => true,
+            MessageType::UpdateCreateInventoryItem => true,
+            MessageType::UpdateGroupInfo => false,
+            MessageType::UpdateInventoryFolder => false,
+            MessageType::UpdateInventoryItem => false,
+            MessageType::UpdateMuteListEntry => false,
+            MessageType::UpdateParcel => true,
+            MessageType::UpdateSimulator => true,
+            MessageType::UpdateTaskInventory => false,
+            MessageType::UpdateUserInfo => false,
+            MessageType::UseCachedMuteList => false,
+            MessageType::UseCircuitCode => false,
+            MessageType::UserInfoReply => true,
+            MessageType::UserInfoRequest => false,
+            MessageType::UserReport => false,
+            MessageType::UserReportInternal => true,
+            MessageType::VelocityInterpolateOff => false,
+            MessageType::VelocityInterpolateOn => false,
+            MessageType::ViewerEffect => false,
+            MessageType::ViewerFrozenMessage => true,
+            MessageType::ViewerStartAuction => false,
+            MessageType::ViewerStats => false,
+        }
+    }
+
+    /// Whether the message is deprecated, i.e. should no longer be sent over UDP.
+    pub fn is_deprecated(&self) -> bool {
+        match *self {
+            MessageType::AbortXfer => false,
+            MessageType::AcceptCallingCard => false,
+            MessageType::AcceptFriendship => false,
+            MessageType::ActivateGestures => false,
+            MessageType::ActivateGroup => false,
+            MessageType::AddCircuitCode => false,
+            MessageType::AgentAlertMessage => false,
+            MessageType::AgentAnimation => false,
+            MessageType::AgentCachedTexture => false,
+            MessageType::AgentCachedTextureResponse => false,
+            MessageType::AgentDataUpdate => false,
+            MessageType::AgentDataUpdateRequest => false,
+            MessageType::AgentDropGroup => false,
+            MessageType::AgentFOV => false,
+            MessageType::AgentGroupDataUpdate => false,
+            MessageType::AgentHeightWidth => false,
+            MessageType::AgentIsNowWearing => false,
+            MessageType::AgentMovementComplete => false,
+            MessageType::AgentPause => false,
+            MessageType::AgentQuitCopy => false,
+            MessageType::AgentRequestSit => false,
+            MessageType::AgentResume => false,
+            MessageType::AgentSetAppearance => false,
+            MessageType::AgentSit => false,
+            MessageType::AgentThrottle => false,
+            MessageType::AgentUpdate => false,
+            MessageType::AgentWearablesRequest => false,
+            MessageType::AgentWearablesUpdate => false,
+            MessageType::AlertMessage => false,
+            MessageType::AssetUploadComplete => false,
+            MessageType::AssetUploadRequest => false,
+            MessageType::AtomicPassObject => false,
+            MessageType::AttachedSound => false,
+            MessageType::AttachedSoundGainChange => false,
+            MessageType::AvatarAnimation => false,
+            MessageType::AvatarAppearance => false,
+            MessageType::AvatarClassifiedReply => false,
+            MessageType::AvatarGroupsReply => false,
+            MessageType::AvatarInterestsReply => false,
+            MessageType::AvatarInterestsUpdate => false,
+            MessageType::AvatarNotesReply => false,
+            MessageType::AvatarNotesUpdate => false,
+            MessageType::AvatarPickerReply => false,
+            MessageType::AvatarPickerRequest => false,
+            MessageType::AvatarPickerRequestBackend => false,
+            MessageType::AvatarPicksReply => false,
+            MessageType::AvatarPropertiesReply => false,
+            MessageType::AvatarPropertiesRequest => false,
+            MessageType::AvatarPropertiesRequestBackend => false,
+            MessageType::AvatarPropertiesUpdate => false,
+            MessageType::AvatarSitResponse => false,
+            MessageType::AvatarTextureUpdate => false,
+            MessageType::BulkUpdateInventory => false,
+            MessageType::BuyObjectInventory => false,
+            MessageType::CameraConstraint => false,
+            MessageType::CancelAuction => false,
+            MessageType::ChangeInventoryItemFlags => false,
+            MessageType::ChangeUserRights => false,
+            MessageType::ChatFromSimulator => false,
+            MessageType::ChatFromViewer => false,
+            MessageType::ChatPass => false,
+            MessageType::CheckParcelAuctions => false,
+            MessageType::CheckParcelSales => false,
+            MessageType::ChildAgentAlive => false,
+            MessageType::ChildAgentDying => false,
+            MessageType::ChildAgentPositionUpdate => false,
+            MessageType::ChildAgentUnknown => false,
+            MessageType::ChildAgentUpdate => false,
+            MessageType::ClassifiedDelete => false,
+            MessageType::ClassifiedGodDelete => false,
+            MessageType::ClassifiedInfoReply => false,
+            MessageType::ClassifiedInfoRequest => false,
+            MessageType::ClassifiedInfoUpdate => false,
+            MessageType::ClearFollowCamProperties => false,
+            MessageType::CloseCircuit => false,
+            MessageType::CoarseLocationUpdate => false,
+            MessageType::CompleteAgentMovement => false,
+            MessageType::CompleteAuction => false,
+            MessageType::CompletePingCheck => false,
+            MessageType::ConfirmAuctionStart => false,
+            MessageType::ConfirmEnableSimulator => false,
+            MessageType::ConfirmXferPacket => false,
+            MessageType::CopyInventoryFromNotecard => false,
+            MessageType::CopyInventoryItem => false,
+            MessageType::CreateGroupReply => false,
+            MessageType::CreateGroupRequest => false,
+            MessageType::CreateInventoryFolder => false,
+            MessageType::CreateInventoryItem => false,
+            MessageType::CreateLandmarkForEvent => false,
+            MessageType::CreateNewOutfitAttachments => false,
+            MessageType::CreateTrustedCircuit => false,
+            MessageType::CrossedRegion => true,
+            MessageType::DataHomeLocationReply => false,
+            MessageType::DataHomeLocationRequest => false,
+            MessageType::DataServerLogout => false,
+            MessageType::DeRezAck => false,
+            MessageType::DeRezObject => false,
+            MessageType::DeactivateGestures => false,
+            MessageType::DeclineCallingCard => false,
+            MessageType::DeclineFriendship => false,
+            MessageType::DenyTrustedCircuit => false,
+            MessageType::DerezContainer => false,
+            MessageType::DetachAttachmentIntoInv => false,
+            MessageType::DirClassifiedQuery => false,
+            MessageType::DirClassifiedQueryBackend => false,
+            MessageType::DirClassifiedReply => false,
+            MessageType::DirEventsReply => false,
+            MessageType::DirFindQuery => false,
+            MessageType::DirFindQueryBackend => false,
+            MessageType::DirGroupsReply => false,
+            MessageType::DirLandQuery => false,
+            MessageType::DirLandQueryBackend => false,
+            MessageType::DirLandReply => false,
+            MessageType::DirPeopleReply => false,
+            MessageType::DirPlacesQuery => false,
+            MessageType::DirPlacesQueryBackend => false,
+            MessageType::DirPlacesReply => false,
+            MessageType::DirPopularQuery => false,
+            MessageType::DirPopularQueryBackend => false,
+            MessageType::DirPopularReply => false,
+            MessageType::DisableSimulator => false,
+            MessageType::EconomyData => false,
+            MessageType::EconomyDataRequest => false,
+            MessageType::EdgeDataPacket => false,
+            MessageType::EjectGroupMemberReply => false,
+            MessageType::EjectGroupMemberRequest => false,
+            MessageType::EjectUser => false,
+            MessageType::EmailMessageReply => false,
+            MessageType::EmailMessageRequest => false,
+            MessageType::EnableSimulator => false,
+            MessageType::Error => false,
+            MessageType::EstateCovenantReply => false,
+            MessageType::EstateCovenantRequest => false,
+            MessageType::EstateOwnerMessage => false,
+            MessageType::EventGodDelete => false,
+            MessageType::EventInfoReply => false,
+            MessageType::EventInfoRequest => false,
+            MessageType::EventLocationReply => false,
+            MessageType::EventLocationRequest => false,
+            MessageType::EventNotificationAddRequest => false,
+            MessageType::EventNotificationRemoveRequest => false,
+            MessageType::FeatureDisabled => false,
+            MessageType::FetchInventory => false,
+            MessageType::FetchInventoryDescendents => false,
+            MessageType::FetchInventoryReply => false,
+            MessageType::FindAgent => false,
+            MessageType::ForceObjectSelect => false,
+            MessageType::ForceScriptControlRelease => false,
+            MessageType::FormFriendship => false,
+            MessageType::FreezeUser => false,
+            MessageType::GenericMessage => false,
+            MessageType::GetScriptRunning => false,
+            MessageType::GodKickUser => false,
+            MessageType::GodUpdateRegionInfo => false,
+            MessageType::GodlikeMessage => false,
+            MessageType::GrantGodlikePowers => false,
+            MessageType::GrantUserRights => false,
+            MessageType::GroupAccountDetailsReply => false,
+            MessageType::GroupAccountDetailsRequest => false,
+            MessageType::GroupAccountSummaryReply => false,
+            MessageType::GroupAccountSummaryRequest => false,
+            MessageType::GroupAccountTransactionsReply => false,
+            MessageType::GroupAccountTransactionsRequest => false,
+            MessageType::GroupActiveProposalItemReply => false,
+            MessageType::GroupActiveProposalsRequest => false,
+            MessageType::GroupDataUpdate => false,
+            MessageType::GroupMembersReply => false,
+            MessageType::GroupMembersRequest => false,
+            MessageType::GroupNoticeAdd => false,
+            MessageType::GroupNoticeRequest => false,
+            MessageType::GroupNoticesListReply => false,
+            MessageType::GroupNoticesListRequest => false,
+            MessageType::GroupProfileReply => false,
+            MessageType::GroupProfileRequest => false,
+            MessageType::GroupProposalBallot => false,
+            MessageType::GroupRoleChanges => false,
+            MessageType::GroupRoleDataReply => false,
+            MessageType::GroupRoleDataRequest => false,
+            MessageType::GroupRoleMembersReply => false,
+            MessageType::GroupRoleMembersRequest => false,
+            MessageType::GroupRoleUpdate => false,
+            MessageType::GroupTitleUpdate => false,
+            MessageType::GroupTitlesReply => false,
+            MessageType::GroupTitlesRequest => false,
+            MessageType::GroupVoteHistoryItemReply => false,
+            MessageType::GroupVoteHistoryRequest => false,
+            MessageType::HealthMessage => false,
+            MessageType::ImageData => false,
+            MessageType::ImageNotInDatabase => false,
+            MessageType::ImagePacket => false,
+            MessageType::ImprovedInstantMessage => false,
+            MessageType::ImprovedTerseObjectUpdate => false,
+            MessageType::InitiateDownload => false,
+            MessageType::InternalScriptMail => false,
+            MessageType::InventoryAssetResponse => false,
+            MessageType::InventoryDescendents => false,
+            MessageType::InviteGroupRequest => false,
+            MessageType::InviteGroupResponse => false,
+            MessageType::JoinGroupReply => false,
+            MessageType::JoinGroupRequest => false,
+            MessageType::KickUser => false,
+            MessageType::KickUserAck => false,
+            MessageType::KillChildAgents => false,
+            MessageType::KillObject => false,
+            MessageType::LandStatReply => false,
+            MessageType::LandStatRequest => false,
+            MessageType::LayerData => false,
+            MessageType::LeaveGroupReply => false,
+            MessageType::LeaveGroupRequest => false,
+            MessageType::LinkInventoryItem => false,
+            MessageType::LiveHelpGroupReply => false,
+            MessageType::LiveHelpGroupRequest => false,
+            MessageType::LoadURL => false,
+            MessageType::LogDwellTime => false,
+            MessageType::LogFailedMoneyTransaction => false,
+            MessageType::LogParcelChanges => false,
+            MessageType::LogTextMessage => false,
+            MessageType::LogoutReply => false,
+            MessageType::LogoutRequest => false,
+            MessageType::MapBlockReply => false,
+            MessageType::MapBlockRequest => false,
+            MessageType::MapItemReply => false,
+            MessageType::MapItemRequest => false,
+            MessageType::MapLayerReply => false,
+            MessageType::MapLayerRequest => false,
+            MessageType::MapNameRequest => false,
+            MessageType::MeanCollisionAlert => false,
+            MessageType::MergeParcel => false,
+            MessageType::ModifyLand => false,
+            MessageType::MoneyBalanceReply => false,
+            MessageType::MoneyBalanceRequest => false,
+            MessageType::MoneyTransferBackend => false,
+            MessageType::MoneyTransferRequest => false,
+            MessageType::MoveInventoryFolder => false,
+            MessageType::MoveInventoryItem => false,
+            MessageType::MoveTaskInventory => false,
+            MessageType::MultipleObjectUpdate => false,
+            MessageType::MuteListRequest => false,
+            MessageType::MuteListUpdate => false,
+            MessageType::NameValuePair => false,
+            MessageType::NearestLandingRegionReply => false,
+            MessageType::NearestLandingRegionRequest => false,
+            MessageType::NearestLandingRegionUpdated => false,
+            MessageType::NeighborList => false,
+            MessageType::NetTest => false,
+            MessageType::ObjectAdd => false,
+            MessageType::ObjectAttach => false,
+            MessageType::ObjectBuy => false,
+            MessageType::ObjectCategory => false,
+            MessageType::ObjectClickAction => false,
+            MessageType::ObjectDeGrab => false,
+            MessageType::ObjectDelete => false,
+            MessageType::ObjectDelink => false,
+            MessageType::ObjectDescription => false,
+            MessageType::ObjectDeselect => false,
+            MessageType::ObjectDetach => false,
+            MessageType::ObjectDrop => false,
+            MessageType::ObjectDuplicate => false,
+            MessageType::ObjectDuplicateOnRay => false,
+            MessageType::ObjectExportSelected => false,
+            MessageType::ObjectExtraParams => false,
+            MessageType::ObjectFlagUpdate => false,
+            MessageType::ObjectGrab => false,
+            MessageType::ObjectGrabUpdate => false,
+            MessageType::ObjectGroup => false,
+            MessageType::ObjectImage => false,
+            MessageType::ObjectIncludeInSearch => false,
+            MessageType::ObjectLink => false,
+            MessageType::ObjectMaterial => false,
+            MessageType::ObjectName => false,
+            MessageType::ObjectOwner => false,
+            MessageType::ObjectPermissions => false,
+            MessageType::ObjectPosition => false,
+            MessageType::ObjectProperties => false,
+            MessageType::ObjectPropertiesFamily => false,
+            MessageType::ObjectRotation => false,
+            MessageType::ObjectSaleInfo => false,
+            MessageType::ObjectScale => false,
+            MessageType::ObjectSelect => false,
+            MessageType::ObjectShape => false,
+            MessageType::ObjectSpinStart => false,
+            MessageType::ObjectSpinStop => false,
+            MessageType::ObjectSpinUpdate => false,
+            MessageType::ObjectUpdate => false,
+            MessageType::ObjectUpdateCached => false,
+            MessageType::ObjectUpdateCompressed => false,
+            MessageType::OfferCallingCard => false,
+            MessageType::OfflineNotification => false,
+            MessageType::OnlineNotification => false,
+            MessageType::OpenCircuit => false,
+            MessageType::PacketAck => false,
+            MessageType::ParcelAccessListReply => false,
+            MessageType::ParcelAccessListRequest => false,
+            MessageType::ParcelAccessListUpdate => false,
+            MessageType::ParcelAuctions => false,
+            MessageType::ParcelBuy => false,
+            MessageType::ParcelBuyPass => false,
+            MessageType::ParcelClaim => false,
+            MessageType::ParcelDeedToGroup => false,
+            MessageType::ParcelDisableObjects => false,
+            MessageType::ParcelDivide => false,
+            MessageType::ParcelDwellReply => false,
+            MessageType::ParcelDwellRequest => false,
+            MessageType::ParcelGodForceOwner => false,
+            MessageType::ParcelGodMarkAsContent => false,
+            MessageType::ParcelInfoReply => false,
+            MessageType::ParcelInfoRequest => false,
+            MessageType::ParcelJoin => false,
+            MessageType::ParcelMediaCommandMessage => false,
+            MessageType::ParcelMediaUpdate => false,
+            MessageType::ParcelObjectOwnersReply => false,
+            MessageType::ParcelObjectOwnersRequest => false,
+            MessageType::ParcelOverlay => false,
+            MessageType::ParcelProperties => true,
+            MessageType::ParcelPropertiesRequest => false,
+            MessageType::ParcelPropertiesRequestByID => false,
+            MessageType::ParcelPropertiesUpdate => false,
+            MessageType::ParcelReclaim => false,
+            MessageType::ParcelRelease => false,
+            MessageType::ParcelRename => false,
+            MessageType::ParcelReturnObjects => false,
+            MessageType::ParcelSales => false,
+            MessageType::ParcelSelectObjects => false,
+            MessageType::ParcelSetOtherCleanTime => false,
+            MessageType::PayPriceReply => false,
+            MessageType::PickDelete => false,
+            MessageType::PickGodDelete => false,
+            MessageType::PickInfoReply => false,
+            MessageType::PickInfoUpdate => false,
+            MessageType::PlacesQuery => false,
+            MessageType::PlacesReply => false,
+            MessageType::PreloadSound => false,
+            MessageType::PurgeInventoryDescendents => false,
+            MessageType::RebakeAvatarTextures => false,
+            MessageType::Redo => false,
+            MessageType::RegionHandleRequest => false,
+            MessageType::RegionHandshake => false,
+            MessageType::RegionHandshakeReply => false,
+            MessageType::RegionIDAndHandleReply => false,
+            MessageType::RegionInfo => false,
+            MessageType::RegionPresenceRequestByHandle => false,
+            MessageType::RegionPresenceRequestByRegionID => false,
+            MessageType::RegionPresenceResponse => false,
+            MessageType::RemoveAttachment => false,
+            MessageType::RemoveInventoryFolder => false,
+            MessageType::RemoveInventoryItem => false,
+            MessageType::RemoveInventoryObjects => false,
+            MessageType::RemoveMuteListEntry => false,
+            MessageType::RemoveNameValuePair => false,
+            MessageType::RemoveParcel => false,
+            MessageType::RemoveTaskInventory => false,
+            MessageType::ReplyTaskInventory => false,
+            MessageType::ReportAutosaveCrash => false,
+            MessageType::RequestGodlikePowers => false,
+            MessageType::RequestImage => false,
+            MessageType::RequestInventoryAsset => false,
+            MessageType::RequestMultipleObjects => false,
+            MessageType::RequestObjectPropertiesFamily => false,
+            MessageType::RequestParcelTransfer => false,
+            MessageType::RequestPayPrice => false,
+            MessageType::RequestRegionInfo => false,
+            MessageType::RequestTaskInventory => false,
+            MessageType::RequestTrustedCircuit => false,
+            MessageType::RequestXfer => false,
+            MessageType::RetrieveInstantMessages => false,
+            MessageType::RevokePermissions => false,
+            MessageType::RezMultipleAttachmentsFromInv => false,
+            MessageType::RezObject => false,
+            MessageType::RezObjectFromNotecard => false,
+            MessageType::RezRestoreToWorld => true,
+            MessageType::RezScript => false,
+            MessageType::RezSingleAttachmentFromInv => false,
+            MessageType::RoutedMoneyBalanceReply => false,
+            MessageType::RpcChannelReply => false,
+            MessageType::RpcChannelRequest => false,
+            MessageType::RpcScriptReplyInbound => false,
+            MessageType::RpcScriptRequestInbound => false,
+            MessageType::RpcScriptRequestInboundForward => true,
+            MessageType::SaveAssetIntoInventory => false,
+            MessageType::ScriptAnswerYes => false,
+            MessageType::ScriptControlChange => false,
+            MessageType::ScriptDataReply => false,
+            MessageType::ScriptDataRequest => false,
+            MessageType::ScriptDialog => false,
+            MessageType::ScriptDialogReply => false,
+            MessageType::ScriptMailRegistration => false,
+            MessageType::ScriptQuestion => false,
+            MessageType::ScriptReset => false,
+            MessageType::ScriptRunningReply => true,
+            MessageType::ScriptSensorReply => false,
+            MessageType::ScriptSensorRequest => false,
+            MessageType::ScriptTeleportRequest => false,
+            MessageType::SendPostcard => false,
+            MessageType::SendXferPacket => false,
+            MessageType::SetAlwaysRun => false,
+            MessageType::SetCPURatio => false,
+            MessageType::SetFollowCamProperties => false,
+            MessageType::SetGroupAcceptNotices => false,
+            MessageType::SetGroupContribution => false,
+            MessageType::SetScriptRunning => false,
+            MessageType::SetSimPresenceInDatabase => false,
+            MessageType::SetSimStatusInDatabase => false,
+            MessageType::SetStartLocation => false,
+            MessageType::SetStartLocationRequest => false,
+            MessageType::SimCrashed => false,
+            MessageType::SimStats => false,
+            MessageType::SimStatus => false,
+            MessageType::SimWideDeletes => false,
+            MessageType::SimulatorLoad => false,
+            MessageType::SimulatorMapUpdate => false,
+            MessageType::SimulatorPresentAtLocation => false,
+            MessageType::SimulatorReady => false,
+            MessageType::SimulatorSetMap => false,
+            MessageType::SimulatorShutdownRequest => false,
+            MessageType::SimulatorViewerTimeMessage => false,
+            MessageType::SoundTrigger => false,
+            MessageType::StartAuction => false,
+            MessageType::StartGroupProposal => false,
+            MessageType::StartLure => false,
+            MessageType::StartPingCheck => false,
+            MessageType::StateSave => false,
+            MessageType::SubscribeLoad => false,
+            MessageType::SystemKickUser => false,
+            MessageType::SystemMessage => false,
+            MessageType::TallyVotes => false,
+            MessageType::TelehubInfo => false,
+            MessageType::TeleportCancel => false,
+            MessageType::TeleportFailed => false,
+            MessageType::TeleportFinish => true,
+            MessageType::TeleportLandingStatusChanged => false,
+            MessageType::TeleportLandmarkRequest => false,
+            MessageType::TeleportLocal => false,
+            MessageType::TeleportLocationRequest => false,
+            MessageType::TeleportLureRequest => false,
+            MessageType::TeleportProgress => false,
+            MessageType::TeleportRequest => false,
+            MessageType::TeleportStart => false,
+            MessageType::TerminateFriendship => false,
+            MessageType::TestMessage => false,
+            MessageType::TrackAgent => false,
+            MessageType::TransferAbort => false,
+            MessageType::TransferInfo => false,
+            MessageType::TransferInventory => false,
+            MessageType::TransferInventoryAck => false,
+            MessageType::TransferPacket => false,
+            MessageType::TransferRequest => false,
+            MessageType::UUIDGroupNameReply => false,
+            MessageType::UUIDGroupNameRequest => false,
+            MessageType::UUIDNameReply => false,
+            MessageType::UUIDNameRequest => false,
+            MessageType::Undo => false,
+            MessageType::UndoLand => false,
+            MessageType::UnsubscribeLoad => false,
+            MessageType::UpdateAttachment => false,
+            MessageType::UpdateCreateInventoryItem => false,
+            MessageType::UpdateGroupInfo => false,
+            MessageType::UpdateInventoryFolder => false,
+            MessageType::UpdateInventoryItem => false,
+            MessageType::UpdateMuteListEntry => false,
+            MessageType::UpdateParcel => false,
+            MessageType::UpdateSimulator => false,
+            MessageType::UpdateTaskInventory => false,
+            MessageType::UpdateUserInfo => false,
+            MessageType::UseCachedMuteList => false,
+            MessageType::UseCircuitCode => false,
+            MessageType::UserInfoReply => false,
+            MessageType::UserInfoRequest => false,
+            MessageType::UserReport => false,
+            MessageType::UserReportInternal => false,
+            MessageType::VelocityInterpolateOff => false,
+            MessageType::VelocityInterpolateOn => false,
+            MessageType::ViewerEffect => false,
+            MessageType::ViewerFrozenMessage => false,
+            MessageType::ViewerStartAuction => false,
+            MessageType::ViewerStats => false,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum MessageInstance {
     AbortXfer(AbortXfer),
@@ -13,6 +13,21 @@ pub use self::errors::ReadError;
 
 pub type WriteMessageResult = ::std::io::Result<()>;
 
+/// The frequency class of a message as specified in the message template.
+///
+/// Messages expected to be sent more often get a shorter message number.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum MessageFrequency {
+    /// One byte message number.
+    High,
+    /// Two byte message number.
+    Medium,
+    /// Four byte message number.
+    Low,
+    /// Four byte message number, reserved for a few special messages.
+    Fixed,
+}
+
 pub trait Message {
     /// Write the message to a buffer for network transmission.
     fn write_to<W: ?Sized>(&self, buffer: &mut W) -> WriteMessageResult
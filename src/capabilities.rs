@@ -4,18 +4,102 @@ use futures::prelude::{await, *};
 use hyper;
 use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
 use llsd;
+use std::collections::HashMap;
+use std::fmt;
 use tokio_core::reactor::Handle;
 use url::Url;
 
-#[derive(Clone, Debug)]
-pub struct Urls {
-    pub get_texture: Url,
-    // TODO: add more.
+/// Capabilities with special support in this crate.
+///
+/// Any other capability can be requested by name with `Other`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Capability {
+    ChatSessionRequest,
+    EventQueueGet,
+    FetchInventory2,
+    FetchInventoryDescendents2,
+    FetchLib2,
+    FetchLibDescendents2,
+    GetMesh,
+    GetMesh2,
+    GetTexture,
+    NewFileAgentInventory,
+    ParcelPropertiesUpdate,
+    SimulatorFeatures,
+    UpdateNotecardAgentInventory,
+    UpdateScriptAgent,
+    UploadBakedTexture,
+    Other(String),
+}
+
+impl Capability {
+    const KNOWN: [Capability; 15] = [
+        Capability::ChatSessionRequest,
+        Capability::EventQueueGet,
+        Capability::FetchInventory2,
+        Capability::FetchInventoryDescendents2,
+        Capability::FetchLib2,
+        Capability::FetchLibDescendents2,
+        Capability::GetMesh,
+        Capability::GetMesh2,
+        Capability::GetTexture,
+        Capability::NewFileAgentInventory,
+        Capability::ParcelPropertiesUpdate,
+        Capability::SimulatorFeatures,
+        Capability::UpdateNotecardAgentInventory,
+        Capability::UpdateScriptAgent,
+        Capability::UploadBakedTexture,
+    ];
+
+    /// The name of the capability as used in the seed request.
+    pub fn name(&self) -> &str {
+        match *self {
+            Capability::ChatSessionRequest => "ChatSessionRequest",
+            Capability::EventQueueGet => "EventQueueGet",
+            Capability::FetchInventory2 => "FetchInventory2",
+            Capability::FetchInventoryDescendents2 => "FetchInventoryDescendents2",
+            Capability::FetchLib2 => "FetchLib2",
+            Capability::FetchLibDescendents2 => "FetchLibDescendents2",
+            Capability::GetMesh => "GetMesh",
+            Capability::GetMesh2 => "GetMesh2",
+            Capability::GetTexture => "GetTexture",
+            Capability::NewFileAgentInventory => "NewFileAgentInventory",
+            Capability::ParcelPropertiesUpdate => "ParcelPropertiesUpdate",
+            Capability::SimulatorFeatures => "SimulatorFeatures",
+            Capability::UpdateNotecardAgentInventory => "UpdateNotecardAgentInventory",
+            Capability::UpdateScriptAgent => "UpdateScriptAgent",
+            Capability::UploadBakedTexture => "UploadBakedTexture",
+            Capability::Other(ref name) => name,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Self {
+        Self::KNOWN
+            .iter()
+            .find(|cap| cap.name() == name)
+            .cloned()
+            .unwrap_or_else(|| Capability::Other(name.to_string()))
+    }
+
+    /// The capabilities requested if nothing else is specified, these are all
+    /// the known ones.
+    pub fn default_requested() -> Vec<Capability> {
+        Self::KNOWN.to_vec()
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
 }
 
+/// The capabilities granted by a sim.
 #[derive(Clone, Debug)]
 pub struct Capabilities {
-    urls: Urls,
+    seed: hyper::Uri,
+    requested: Vec<Capability>,
+    urls: HashMap<Capability, Url>,
 }
 
 #[derive(Debug, Fail)]
@@ -33,21 +117,52 @@ impl Capabilities {
         Ok(data)
     }
 
-    pub fn urls(&self) -> &Urls {
-        &self.urls
+    /// Returns the URL of a capability, `None` if the sim did not grant it.
+    pub fn get(&self, capability: &Capability) -> Option<&Url> {
+        self.urls.get(capability)
     }
 
+    /// Returns all granted capabilities.
+    pub fn granted(&self) -> impl Iterator<Item = (&Capability, &Url)> {
+        self.urls.iter()
+    }
+
+    /// The capabilities which were requested from the sim.
+    pub fn requested(&self) -> &[Capability] {
+        &self.requested
+    }
+
+    /// Request the same capabilities from the seed capability again.
+    ///
+    /// Sims can rotate the URLs of capabilities, in which case the old ones
+    /// stop working.
+    pub fn refresh(
+        &self,
+        handle: Handle,
+    ) -> impl Future<Item = Capabilities, Error = CapabilitiesError> {
+        Self::setup_capabilities(self.seed.clone(), self.requested.clone(), handle)
+    }
+
+    /// Request capabilities from the seed capability.
+    ///
+    /// Capabilities not granted by the sim are simply missing from the
+    /// result, it's up to the user of a capability to handle its absence.
     #[async]
     pub fn setup_capabilities(
         seed_caps_uri: hyper::Uri,
+        requested: Vec<Capability>,
         handle: Handle,
     ) -> Result<Capabilities, CapabilitiesError> {
-        let requested_caps =
-            llsd::data::Value::Array(vec![llsd::data::Value::new_string("GetTexture")]);
+        let requested_caps = llsd::data::Value::Array(
+            requested
+                .iter()
+                .map(|cap| llsd::data::Value::new_string(cap.name()))
+                .collect(),
+        );
 
         let client = hyper::Client::new();
         let request_body = await!(Self::build_request_body(requested_caps))?;
-        let mut request = hyper::Request::post(seed_caps_uri)
+        let mut request = hyper::Request::post(seed_caps_uri.clone())
             .header(CONTENT_TYPE, "application/llsd+xml")
             .header(CONTENT_LENGTH, request_body.len())
             .body(hyper::Body::from(request_body))
@@ -82,18 +197,22 @@ impl Capabilities {
                 .map_err(|_| CapabilitiesError::Msg("Invalid LLSD".to_string()))?;
 
             match val {
-                llsd::data::Value::Map(mut map) => {
-                    let get_texture = map
-                        .remove("GetTexture")
-                        .and_then(|v| v.scalar())
-                        .and_then(|s| s.as_uri())
-                        .and_then(|u| u.ok())
-                        .ok_or_else(|| CapabilitiesError::Msg("No GetTexture cap.".into()))?;
+                llsd::data::Value::Map(map) => {
+                    let urls = map
+                        .into_iter()
+                        .filter_map(|(name, value)| {
+                            value
+                                .scalar()
+                                .and_then(|s| s.as_uri())
+                                .and_then(|u| u.ok())
+                                .map(|url| (Capability::from_name(&name), url))
+                        })
+                        .collect();
 
                     Ok(Capabilities {
-                        urls: Urls {
-                            get_texture: get_texture,
-                        },
+                        seed: seed_caps_uri,
+                        requested: requested,
+                        urls: urls,
                     })
                 }
                 _ => Err(CapabilitiesError::Msg("LLSD is not a map.".into())),
@@ -103,3 +222,19 @@ impl Capabilities {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capability_names() {
+        for cap in Capability::default_requested() {
+            assert_eq!(Capability::from_name(cap.name()), cap);
+        }
+        assert_eq!(
+            Capability::from_name("RenderMaterials"),
+            Capability::Other("RenderMaterials".to_string())
+        );
+    }
+}
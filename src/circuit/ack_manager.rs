@@ -13,17 +13,28 @@
 // send it in a     dedicated packet if waiting for too long.
 
 use addressable_queue::fifo::Queue as AddressableQueue;
+use circuit::stats::StatsTracker;
+use circuit::throttle::{SendScheduler, ThrottleCategory};
 use circuit::{CircuitConfig, SendMessage, SendMessageError, SendMessageStatus};
-use messages::{MessageInstance, PacketAck, PacketAck_Packets};
+use logging::Logger;
+use messages::{
+    MessageInstance, MessageType, PacketAck, PacketAck_Packets, StartPingCheck,
+    StartPingCheck_PingID,
+};
 use packet::{Packet, PacketFlags};
 use types::SequenceNumber;
 use util::{mpsc_read_many, AtomicU32Counter};
 
+use std::io::Error as IoError;
 use std::sync::mpsc;
 use std::sync::mpsc::TryRecvError;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
 
+/// Size of the packet header preceding the message.
+const PACKET_HEADER_SIZE: usize = 6;
+
 pub struct AckManagerRx {
     /// Messages waiting for their confirmation.
     acks_wait: AddressableQueue<SequenceNumber, PendingMessage>,
@@ -37,13 +48,25 @@ pub struct AckManagerRx {
     /// Messages waiting to be sent out.
     msgs_out: mpsc::Receiver<PendingMessage>,
 
+    /// Messages taken from `msgs_out`, waiting for their turn to be sent.
+    scheduler: SendScheduler<PendingMessage>,
+    /// `CloseCircuit` is held back until all other messages were sent.
+    close_circuit: Option<PendingMessage>,
+    /// Set once all `AckManagerTx` were dropped.
+    senders_gone: bool,
+
     /// Copy of circuit config.
     config: CircuitConfig,
     sequence_counter: AtomicU32Counter,
+
+    /// Round trip time estimation, shared with the reader thread.
+    stats: Arc<Mutex<StatsTracker>>,
+    /// The time our last ping was sent.
+    last_ping: Instant,
 }
 
 impl AckManagerRx {
-    fn _fetch_loop(&mut self) -> (Packet, SendMessage) {
+    fn _fetch_loop(&mut self) -> Option<(Packet, SendMessage)> {
         loop {
             if let Some(pending_msg) = self._next_message() {
                 // Create packet instance and update status.
@@ -57,20 +80,34 @@ impl AckManagerRx {
                     packet.appended_acks.append(&mut acks);
 
                     // Return the packet to be sent.
-                    return (packet, future);
+                    return Some((packet, future));
                 }
+            } else if self.senders_gone {
+                return None;
             } else {
                 thread::sleep(Duration::from_millis(50));
             }
         }
     }
 
-    /// Returns the next packet to be sent to the sim.
+    /// Returns the next packet to be sent to the sim, together with its
+    /// encoded form.
+    ///
+    /// Messages which can't be encoded are failed and returned as error
+    /// instead. Returns `None` once all senders were dropped and there is
+    /// nothing left to send.
     ///
     /// Note that this method will block the current thread until something is
     /// available.
-    pub fn fetch(&mut self) -> Packet {
-        let (packet, future) = self._fetch_loop();
+    pub fn fetch(&mut self) -> Option<Result<(Packet, Vec<u8>), (MessageInstance, IoError)>> {
+        let (packet, future) = self._fetch_loop()?;
+
+        let mut buf = Vec::new();
+        if let Err(e) = packet.write_to(&mut buf) {
+            let mut future = future;
+            future.update_status(SendMessageStatus::Failure(SendMessageError::InvalidMessage));
+            return Some(Err((packet.message, e)));
+        }
 
         if packet.is_reliable() {
             // Put message into wait queue.
@@ -81,9 +118,24 @@ impl AckManagerRx {
                     future: future,
                 },
             );
+        } else {
+            // Unreliable messages are done as soon as they are sent.
+            let mut future = future;
+            future.update_status(SendMessageStatus::Success);
         }
 
-        packet
+        Some(Ok((packet, buf)))
+    }
+
+    /// Mark all messages still waiting for an ack as failed.
+    ///
+    /// To be called once the circuit was closed.
+    pub fn abort_pending(&mut self) {
+        while let Some((_, mut pending_msg)) = self.acks_wait.remove_head() {
+            pending_msg
+                .future
+                .update_status(SendMessageStatus::Failure(SendMessageError::CircuitClosed));
+        }
     }
 
     fn _next_message(&mut self) -> Option<PendingMessage> {
@@ -99,10 +151,39 @@ impl AckManagerRx {
             }
         }
 
+        // Move newly queued messages into the scheduler.
+        loop {
+            match self.msgs_out.try_recv() {
+                Ok(msg) => {
+                    let message_type = msg.message.message_type();
+                    if message_type == MessageType::CloseCircuit {
+                        self.close_circuit = Some(msg);
+                    } else {
+                        let category = ThrottleCategory::of_outgoing(&message_type);
+                        self.scheduler.push(category, msg);
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                // Nothing will be queued anymore, but the remaining messages
+                // are still sent.
+                Err(TryRecvError::Disconnected) => {
+                    self.senders_gone = true;
+                    break;
+                }
+            }
+        }
+        let now = Instant::now();
+
         // First check if there is a message waiting too long for an ack already, then
         // check the other pending messages for sending.
         if let Some((_, wait_oldest)) = self.acks_wait.remove_head() {
-            if wait_oldest.is_too_old() {
+            if wait_oldest.is_too_old()
+                && self.scheduler.try_acquire(
+                    ThrottleCategory::Resend,
+                    message_size(&wait_oldest.message),
+                    now,
+                )
+            {
                 return Some(wait_oldest);
             } else {
                 let seq_number = wait_oldest.sequence_number().unwrap();
@@ -110,33 +191,67 @@ impl AckManagerRx {
             }
         }
 
-        match self.msgs_out.try_recv() {
-            Ok(msg) => Some(msg),
-            Err(TryRecvError::Empty) => {
-                // If there are pending acks to be sent out, return a PacketAck.
-                let acks = mpsc_read_many(&self.acks_out, 255);
-                if acks.is_empty() {
-                    None
-                } else {
-                    Some(PendingMessage {
-                        message: PacketAck {
-                            packets: acks
-                                .iter()
-                                .map(|num| PacketAck_Packets { id: *num })
-                                .collect(),
-                        }
-                        .into(),
-                        future: SendMessage::new(SendMessageStatus::PendingSend {
-                            reliable: false,
-                        }),
-                    })
+        // Ping the sim regularly to keep the round trip time estimate updated.
+        if self.last_ping.elapsed() >= self.config.ping_interval {
+            return Some(self._ping_message());
+        }
+
+        if let Some(msg) = self.scheduler.pop(|m| message_size(&m.message), now) {
+            return Some(msg);
+        }
+        if self.scheduler.is_empty() {
+            if let Some(msg) = self.close_circuit.take() {
+                return Some(msg);
+            }
+        }
+
+        // If there are pending acks to be sent out, return a PacketAck.
+        let acks = mpsc_read_many(&self.acks_out, 255);
+        if acks.is_empty() {
+            None
+        } else {
+            Some(PendingMessage {
+                message: PacketAck {
+                    packets: acks
+                        .iter()
+                        .map(|num| PacketAck_Packets { id: *num })
+                        .collect(),
                 }
+                .into(),
+                future: SendMessage::new(SendMessageStatus::PendingSend { reliable: false }),
+            })
+        }
+    }
+
+    fn _ping_message(&mut self) -> PendingMessage {
+        self.last_ping = Instant::now();
+        let ping_id = self.stats.lock().unwrap().start_ping();
+        let oldest_unacked = self
+            .acks_wait
+            .remove_head()
+            .map(|(seq_number, pending_msg)| {
+                self.acks_wait.insert_head(seq_number, pending_msg);
+                seq_number
+            })
+            .unwrap_or(0);
+
+        PendingMessage {
+            message: StartPingCheck {
+                ping_id: StartPingCheck_PingID {
+                    ping_id: ping_id,
+                    oldest_unacked: oldest_unacked,
+                },
             }
-            // TODO: return error.
-            Err(TryRecvError::Disconnected) => panic!("unimplemented error handling"),
+            .into(),
+            future: SendMessage::new(SendMessageStatus::PendingSend { reliable: false }),
         }
     }
 
+    /// The current retransmission timeout, derived from the round trip time.
+    fn _retransmit_timeout(&self) -> Duration {
+        self.stats.lock().unwrap().retransmit_timeout()
+    }
+
     fn _prepare_packet(&self, msg: PendingMessage) -> (Option<Packet>, SendMessageStatus) {
         let old_status = msg.future.get_status();
         match old_status {
@@ -144,12 +259,14 @@ impl AckManagerRx {
                 let seq_num = self.sequence_counter.next();
                 let new_status = SendMessageStatus::PendingAck {
                     attempt: 0,
-                    timeout: Instant::now() + self.config.send_timeout,
+                    timeout: Instant::now() + self._retransmit_timeout(),
                     id: seq_num,
                 };
 
+                let zerocoded = msg.message.message_type().is_zerocoded();
                 let mut packet = Packet::new(msg.message, seq_num);
                 packet.set_reliable(reliable);
+                packet.set_zerocoded(zerocoded);
                 (Some(packet), new_status)
             }
             SendMessageStatus::PendingAck { attempt, id, .. } => {
@@ -162,11 +279,13 @@ impl AckManagerRx {
                 } else {
                     let new_status = SendMessageStatus::PendingAck {
                         attempt: attempt,
-                        timeout: Instant::now() + self.config.send_timeout,
+                        timeout: Instant::now() + self._retransmit_timeout(),
                         id: id,
                     };
+                    let zerocoded = msg.message.message_type().is_zerocoded();
                     let mut packet = Packet::new(msg.message, id);
                     packet.set_reliable(true);
+                    packet.set_zerocoded(zerocoded);
                     packet.enable_flags(PacketFlags::RESENT);
                     (Some(packet), new_status)
                 }
@@ -184,6 +303,7 @@ pub struct AckManagerTx {
 
     /// Copy of circuit config.
     config: CircuitConfig,
+    logger: Logger,
 }
 
 impl AckManagerTx {
@@ -201,19 +321,34 @@ impl AckManagerTx {
 
     pub fn send_msg(&self, msg: MessageInstance, reliable: bool) -> SendMessage {
         // debug!(self.logger, "send_msg: {:?}", msg);
+        let message_type = msg.message_type();
+        if message_type.is_trusted() {
+            warn!(
+                self.logger,
+                "Sending trusted message {:?}, the sim will most likely drop it.", message_type
+            );
+        }
         let future = SendMessage::new(SendMessageStatus::PendingSend { reliable: reliable });
         let p_m = PendingMessage {
             message: msg,
             future: future.clone(),
         };
 
-        self.msgs_out.send(p_m).unwrap();
+        if self.msgs_out.send(p_m).is_err() {
+            // The sender thread is gone.
+            let mut future = future.clone();
+            future.update_status(SendMessageStatus::Failure(SendMessageError::CircuitClosed));
+        }
         future
     }
 }
 
 /// Create a new instance of the AckManager tx and rx.
-pub fn new(config: CircuitConfig) -> (AckManagerTx, AckManagerRx) {
+pub fn new(
+    config: CircuitConfig,
+    stats: Arc<Mutex<StatsTracker>>,
+    logger: Logger,
+) -> (AckManagerTx, AckManagerRx) {
     let (acks_out_tx, acks_out_rx) = mpsc::channel();
     let (acks_inc_tx, acks_inc_rx) = mpsc::channel();
     let (msgs_out_tx, msgs_out_rx) = mpsc::channel();
@@ -223,19 +358,34 @@ pub fn new(config: CircuitConfig) -> (AckManagerTx, AckManagerRx) {
         acks_inc: acks_inc_tx,
         msgs_out: msgs_out_tx,
         config: config.clone(),
+        logger: logger,
     };
     let rx = AckManagerRx {
         acks_wait: AddressableQueue::new(),
         acks_inc: acks_inc_rx,
         acks_out: acks_out_rx,
         msgs_out: msgs_out_rx,
+        scheduler: SendScheduler::new(config.send_throttle.as_ref(), Instant::now()),
+        close_circuit: None,
+        senders_gone: false,
         config: config,
         sequence_counter: AtomicU32Counter::new(0),
+        stats: stats,
+        last_ping: Instant::now(),
     };
 
     (tx, rx)
 }
 
+/// Estimated size of a packet containing the message, ignoring zerocoding and
+/// appended acks.
+fn message_size(msg: &MessageInstance) -> usize {
+    let mut buf = Vec::new();
+    // Messages which can't be encoded are reported once they are sent.
+    let _ = msg.write_to(&mut buf);
+    PACKET_HEADER_SIZE + buf.len()
+}
+
 #[derive(Debug)]
 pub struct PendingMessage {
     pub message: MessageInstance,
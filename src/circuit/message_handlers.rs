@@ -1,4 +1,5 @@
-use circuit::MessageSender;
+use circuit::{MessageSender, Subscription, Subscriptions};
+use event_queue::Event;
 use failure::Fail;
 use futures_cpupool::CpuPool;
 use messages::{MessageInstance, MessageType};
@@ -7,6 +8,7 @@ use tokio_core::reactor;
 
 type FilterFn = Box<Fn(&MessageInstance) -> bool + Send>;
 type HandlerFn = Box<Fn(MessageInstance, &HandlerContext) -> Result<(), Error> + Send>;
+type EventHandlerFn = Box<Fn(Event, &HandlerContext) -> Result<(), EventError> + Send>;
 
 /// A message handler which handles all messages for which filter evaluates to
 /// true.
@@ -24,6 +26,8 @@ pub struct HandlerContext<'a> {
 pub struct Handlers {
     type_handlers: HashMap<MessageType, HandlerFn>,
     filter_handlers: Vec<FilterHandler>,
+    event_handlers: HashMap<String, EventHandlerFn>,
+    subscriptions: Subscriptions,
 }
 
 impl Handlers {
@@ -31,12 +35,18 @@ impl Handlers {
         Handlers {
             type_handlers: HashMap::new(),
             filter_handlers: Vec::new(),
+            event_handlers: HashMap::new(),
+            subscriptions: Subscriptions::new(),
         }
     }
 
     /// Register a handler for all messages of a specific message type.
-    pub fn register_type(&mut self, m_type: MessageType, handler: HandlerFn) {
-        self.type_handlers.insert(m_type, handler);
+    ///
+    /// There can only be one handler per message type, if there already was
+    /// one it is replaced and returned. To observe messages without taking
+    /// them from their handler, use `subscribe`.
+    pub fn register_type(&mut self, m_type: MessageType, handler: HandlerFn) -> Option<HandlerFn> {
+        self.type_handlers.insert(m_type, handler)
     }
 
     /// Register a handler for all messages for which the filter evaluates to
@@ -48,11 +58,65 @@ impl Handlers {
         });
     }
 
+    /// Subscribe to all messages of a specific message type.
+    ///
+    /// Subscribers receive a copy of each message, before it is passed to
+    /// the handlers.
+    pub fn subscribe(&self, m_type: MessageType) -> Subscription {
+        self.subscriptions.subscribe(m_type)
+    }
+
+    /// Subscribe to all messages for which the filter evaluates to true.
+    pub fn subscribe_filter(&self, filter: FilterFn) -> Subscription {
+        self.subscriptions.subscribe_filter(filter)
+    }
+
+    /// Returns the subscriptions, which can still be added to after the
+    /// handlers were passed to the circuit.
+    pub fn subscriptions(&self) -> Subscriptions {
+        self.subscriptions.clone()
+    }
+
+    /// Register a handler for all events of the event queue with the given
+    /// name, i.e. `EstablishAgentCommunication`.
+    ///
+    /// Events equivalent to UDP messages are passed to the message handlers
+    /// instead, so they are handled the same way independent of how they were
+    /// delivered.
+    pub fn register_event(&mut self, name: &str, handler: EventHandlerFn) {
+        self.event_handlers.insert(name.to_string(), handler);
+    }
+
+    pub(crate) fn handle_event(
+        &self,
+        event: Event,
+        context: &HandlerContext,
+    ) -> Result<(), EventError> {
+        match event {
+            Event::Message(msg) => self.handle(msg, context).map_err(|err| EventError {
+                event: Event::Message(err.msg),
+                kind: err.kind,
+            }),
+            event => {
+                let name = event.name().to_string();
+                match self.event_handlers.get(&name) {
+                    Some(h) => h(event, context),
+                    None => Err(EventError {
+                        event: event,
+                        kind: ErrorKind::NoHandler,
+                    }),
+                }
+            }
+        }
+    }
+
     pub(crate) fn handle(
         &self,
         msg: MessageInstance,
         context: &HandlerContext,
     ) -> Result<(), Error> {
+        self.subscriptions.notify(&msg);
+
         if let Some(h) = self.type_handlers.get(&msg.message_type()) {
             h(msg, context)
         } else {
@@ -75,6 +139,12 @@ pub struct Error {
     pub kind: ErrorKind,
 }
 
+#[derive(Debug)]
+pub struct EventError {
+    pub event: Event,
+    pub kind: ErrorKind,
+}
+
 #[derive(Debug)]
 pub enum ErrorKind {
     NoHandler,
@@ -85,27 +155,6 @@ pub enum ErrorKind {
 
 impl Default for Handlers {
     fn default() -> Self {
-        let mut handlers = Handlers::new();
-        handlers.register_type(MessageType::StartPingCheck, Box::new(handle_ping_check));
-        handlers
+        Handlers::new()
     }
 }
-
-fn handle_ping_check(msg: MessageInstance, context: &HandlerContext) -> Result<(), Error> {
-    use messages::all::{CompletePingCheck, CompletePingCheck_PingID};
-
-    let start_ping_check = match msg {
-        MessageInstance::StartPingCheck(m) => Ok(m),
-        _ => Err(Error {
-            msg: msg,
-            kind: ErrorKind::WrongHandler,
-        }),
-    }?;
-    let response = CompletePingCheck {
-        ping_id: CompletePingCheck_PingID {
-            ping_id: start_ping_check.ping_id.ping_id,
-        },
-    };
-    context.message_sender.send(response, false);
-    Ok(())
-}
@@ -7,33 +7,43 @@
 //!
 //! First it will be checked if there is a handler specific to the
 //! `MessageType` of the message, if none is found, handlers with a filter will
-//! be queried for the message (TODO implement when needed), giving the message
-//! to the first handler found to accept it.
+//! be queried for the message, giving the message to the first handler found
+//! to accept it.
 //!
 //! If no handler was found for a message, it will remain in the queue and can
 //! be received from the Circuit with the `read` and `try_read` functions.
 //!
+//! # Subscriptions
+//!
+//! Any number of subscribers can observe incoming messages of a type or
+//! matching a filter with `Circuit::subscribe`, receiving a copy of each
+//! message as a `Stream`, regardless of how the message is handled.
+//!
+//! # Errors
+//!
+//! Errors which don't stop the circuit, i.e. failing handlers, undecodable
+//! packets or a sim which stopped responding, are reported as `CircuitEvent`s
+//! on the channel returned by `Circuit::events`.
+//!
 //! # Backlog (TODO)
 //!
 //! - IPv6 support (blocked by OpenSim support)
 
 // TODO:
-// - Proper shutdown of circuit
-// → This should be accompanied by a systems module providing functionality
-// to send the correct messages to the sim to make sure the agent is
-// actually disconnected from the sim and doesn't end up failing the next
-// authentication. - Figure out max packet size and apply the value to our read
+// - Figure out max packet size and apply the value to our read
 // and write buffers. - Make sure acks are not sent twice?
 // - Do acks need to be sent with a reliable packet?
 // - Make sure the code is free from deadlock and starvation.
-// - Improve error handling.
 // - Once the rest is done: cleanup + verify corectness.
 // - Eliminate all unwraps from this module except where we can verify it will
 // never fail.
 
+use crossbeam_channel;
+use event_queue::{Event, EventDispatcher};
 use logging::Log;
-use messages::MessageInstance;
-use packet::Packet;
+use messages::all::{CloseCircuit, CompletePingCheck, CompletePingCheck_PingID};
+use messages::{MessageInstance, MessageType};
+use packet::{Packet, ReadPacketError};
 use simulator::ConnectInfo;
 use types::SequenceNumber;
 use util::FifoCache;
@@ -42,10 +52,12 @@ use futures_cpupool::CpuPool;
 use std::error::Error;
 use std::fmt;
 use std::io::Error as IoError;
+use std::io::ErrorKind as IoErrorKind;
 use std::net::{SocketAddr, SocketAddrV4, UdpSocket};
-use std::sync::mpsc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio_core::reactor;
 
 mod ack_manager;
@@ -55,8 +67,18 @@ mod status;
 use self::status::SendMessageStatus;
 pub use self::status::{SendMessage, SendMessageError};
 
+mod stats;
+pub use self::stats::CircuitStats;
+use self::stats::StatsTracker;
+
+mod throttle;
+pub use self::throttle::{Throttle, ThrottleCategory};
+
 pub mod message_handlers;
 
+mod subscriptions;
+pub use self::subscriptions::{Subscription, Subscriptions};
+
 #[derive(Debug)]
 pub enum ReadMessageError {
     Disconnected,
@@ -112,14 +134,89 @@ impl MessageSender {
     }
 }
 
+#[derive(Debug, Fail)]
+pub enum CloseCircuitError {
+    #[fail(display = "The {} thread of the circuit panicked.", 0)]
+    ThreadPanicked(&'static str),
+}
+
+/// Number of events buffered in the event channel of a circuit, further events
+/// are dropped until the application reads them.
+const EVENT_CHANNEL_SIZE: usize = 256;
+
+/// Problems encountered by the circuit which did not stop it.
+#[derive(Debug)]
+pub enum CircuitEvent {
+    /// A message handler returned an error.
+    HandlerError(message_handlers::Error),
+
+    /// A handler of an event queue event returned an error.
+    EventHandlerError(message_handlers::EventError),
+
+    /// A received packet could not be decoded, it was dropped.
+    InvalidPacket {
+        data: Vec<u8>,
+        error: ReadPacketError,
+    },
+
+    /// A message could not be encoded, it was not sent.
+    InvalidMessage {
+        message: MessageInstance,
+        error: IoError,
+    },
+
+    /// Sending or receiving on the socket failed.
+    SocketError(IoError),
+
+    /// The sender thread stopped unexpectedly. As nothing can be sent to the
+    /// sim anymore, not even acks, the circuit stopped receiving as well.
+    SenderStopped,
+
+    /// The sim closed the circuit by sending `CloseCircuit`.
+    ClosedBySim,
+
+    /// Nothing was received from the sim for longer than
+    /// `CircuitConfig::receive_timeout`, the sim is most likely gone.
+    ///
+    /// This is reported once, and again if the sim comes back and goes silent
+    /// again.
+    Timeout,
+}
+
+/// Sending end of the event channel.
+#[derive(Clone)]
+struct EventReporter {
+    sender: crossbeam_channel::Sender<CircuitEvent>,
+    log: Log,
+}
+
+impl EventReporter {
+    fn report(&self, event: CircuitEvent) {
+        warn!(self.log.slog_logger(), "Circuit event: {:?}", event);
+        // If the application does not read the events, drop them instead of
+        // blocking the circuit.
+        let _ = self.sender.try_send(event);
+    }
+}
+
 /// Encapsulates a so called circuit (networking link) between our viewer and a
 /// simulator.
-///
-/// TODO:
-/// - Stop/exit functionality.
 pub struct Circuit {
     incoming: mpsc::Receiver<MessageInstance>,
     ackmgr_tx: AckManagerTx,
+
+    /// Events from the event queue, to be dispatched by the reader thread.
+    events: mpsc::Sender<Event>,
+    subscriptions: Subscriptions,
+
+    stats: Arc<Mutex<StatsTracker>>,
+    events_out: crossbeam_channel::Receiver<CircuitEvent>,
+
+    /// Set to true to request the reader thread to exit.
+    shutdown: Arc<AtomicBool>,
+    /// Taken once the threads were stopped.
+    sender_thread: Option<thread::JoinHandle<()>>,
+    reader_thread: Option<thread::JoinHandle<()>>,
 }
 
 impl Circuit {
@@ -137,38 +234,76 @@ impl Circuit {
 
         // Queue for incoming messages.
         let (incoming_tx, incoming_rx) = mpsc::channel::<MessageInstance>();
+        let (events_tx, events_rx) = mpsc::channel::<Event>();
+        let subscriptions = msg_handlers.subscriptions();
 
         // Create sockets.
         let socket_out = UdpSocket::bind("0.0.0.0:0")?;
         socket_out.connect(sim_address)?;
-        socket_out.set_read_timeout(None)?;
+        // The reader thread has to wake up regularly to check whether it
+        // should exit, and to dispatch events of the event queue.
+        socket_out.set_read_timeout(Some(Duration::from_millis(100)))?;
         socket_out.set_nonblocking(false)?;
         let socket_in = socket_out.try_clone()?;
 
         // Setup AckManager.
-        let (ackmgr_tx, mut ackmgr_rx) = self::ack_manager::new(config);
+        let (events_out_tx, events_out_rx) = crossbeam_channel::bounded(EVENT_CHANNEL_SIZE);
+        let reporter = EventReporter {
+            sender: events_out_tx,
+            log: log.clone(),
+        };
+        let reporter_sender = reporter.clone();
+        let receive_timeout = config.receive_timeout;
+
+        let stats = Arc::new(Mutex::new(StatsTracker::new(config.send_timeout)));
+        let stats_reader = Arc::clone(&stats);
+        let (ackmgr_tx, mut ackmgr_rx) =
+            self::ack_manager::new(config, Arc::clone(&stats), log.slog_logger());
         let ackmgr_tx_1 = ackmgr_tx;
         let ackmgr_tx_2 = ackmgr_tx_1.clone();
         let message_sender = MessageSender {
             ackmgr_tx: ackmgr_tx_1.clone(),
         };
 
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let shutdown_reader = Arc::clone(&shutdown);
+
         // Create sender thread (1).
+        //
+        // It exits after sending CloseCircuit, which is sent as the last
+        // message by `Circuit::close`.
         let log1 = log.clone();
-        thread::spawn(move || {
-            // TODO: proper shutdown mechanism
-            loop {
-                let packet = ackmgr_rx.fetch();
-                let mut buf = Vec::<u8>::new();
-                packet.write_to(&mut buf).unwrap();
+        let sender_thread = thread::spawn(move || {
+            // Also exits once all senders were dropped.
+            while let Some(fetched) = ackmgr_rx.fetch() {
+                let (packet, buf) = match fetched {
+                    Ok(fetched) => fetched,
+                    Err((message, error)) => {
+                        reporter_sender.report(CircuitEvent::InvalidMessage {
+                            message: message,
+                            error: error,
+                        });
+                        continue;
+                    }
+                };
                 log1.log_packet_send(&buf, &packet);
 
-                socket_out.send(&buf).unwrap();
+                if let Err(e) = socket_out.send(&buf) {
+                    reporter_sender.report(CircuitEvent::SocketError(e));
+                }
+
+                if let MessageInstance::CloseCircuit(_) = packet.message {
+                    break;
+                }
             }
+
+            // Nothing will be sent anymore, so the remaining reliable
+            // messages can't be acknowledged anymore.
+            ackmgr_rx.abort_pending();
         });
 
         // Create reader thread (2).
-        thread::spawn(move || {
+        let reader_thread = thread::spawn(move || {
             // TODO: Determine good maximum size. If it's too be big we are wasting memory,
             // if it's too small things will explode.
             //
@@ -184,29 +319,80 @@ impl Circuit {
                 reactor: reactor_remote,
             };
 
-            loop {
+            let logger = log.slog_logger();
+            let mut last_received = Instant::now();
+            let mut timed_out = false;
+
+            while !shutdown_reader.load(Ordering::SeqCst) {
+                // Dispatch the events received through the event queue.
+                while let Ok(event) = events_rx.try_recv() {
+                    let _ = msg_handlers
+                        .handle_event(event, &handler_context)
+                        .map_err(|err| match err.kind {
+                            message_handlers::ErrorKind::NoHandler => match err.event {
+                                Event::Message(msg) => {
+                                    let _ = incoming_tx.send(msg);
+                                }
+                                event => debug!(logger, "No handler for event: {}", event.name()),
+                            },
+                            _ => reporter.report(CircuitEvent::EventHandlerError(err)),
+                        });
+                }
+
                 // TODO: move back up after debugging
                 let mut buf = [0u8; 4096];
-                // Read from socket in blocking way.
-                let (buf_size, _) = socket_in.recv_from(&mut buf).unwrap();
+                // Read from socket in blocking way, waking up after the read
+                // timeout to check for shutdown.
+                let buf_size = match socket_in.recv_from(&mut buf) {
+                    Ok((buf_size, _)) => buf_size,
+                    Err(ref e)
+                        if e.kind() == IoErrorKind::WouldBlock
+                            || e.kind() == IoErrorKind::TimedOut =>
+                    {
+                        if !timed_out && last_received.elapsed() > receive_timeout {
+                            timed_out = true;
+                            reporter.report(CircuitEvent::Timeout);
+                        }
+                        continue;
+                    }
+                    // I.e. the sim is not reachable (yet), which might be
+                    // temporary, so keep reading.
+                    Err(e) => {
+                        reporter.report(CircuitEvent::SocketError(e));
+                        continue;
+                    }
+                };
+                last_received = Instant::now();
+                timed_out = false;
 
                 // Parse the packet.
                 let packet_res = Packet::read(&buf[..buf_size]);
                 log.log_packet_recv(&buf[..buf_size], &packet_res);
                 let packet = match packet_res {
                     Ok(pkt) => pkt,
-                    Err(_) => continue,
+                    Err(e) => {
+                        reporter.report(CircuitEvent::InvalidPacket {
+                            data: buf[..buf_size].to_vec(),
+                            error: e,
+                        });
+                        continue;
+                    }
                 };
 
                 // Read appended acks and send ack if requested (reliable packet).
+                // This only fails if the sender thread is gone.
+                if packet
+                    .appended_acks
+                    .iter()
+                    .any(|ack| ackmgr_tx_1.register_ack(*ack).is_err())
                 {
-                    for ack in packet.appended_acks.iter() {
-                        ackmgr_tx_1.register_ack(*ack).unwrap();
-                    }
+                    reporter.report(CircuitEvent::SenderStopped);
+                    break;
                 }
                 if packet.is_reliable() {
-                    {
-                        ackmgr_tx_1.send_ack(packet.sequence_number).unwrap();
+                    if ackmgr_tx_1.send_ack(packet.sequence_number).is_err() {
+                        reporter.report(CircuitEvent::SenderStopped);
+                        break;
                     }
 
                     // Check if we did receive the packet already and the remote just resent it
@@ -221,21 +407,41 @@ impl Circuit {
                 match packet.message {
                     MessageInstance::PacketAck(msg) => {
                         // Pass the acks to the ack manager (and don't yield the packet).
-                        for packet_ack in msg.packets {
-                            ackmgr_tx_1.register_ack(packet_ack.id).unwrap();
+                        if msg
+                            .packets
+                            .iter()
+                            .any(|packet_ack| ackmgr_tx_1.register_ack(packet_ack.id).is_err())
+                        {
+                            reporter.report(CircuitEvent::SenderStopped);
+                            break;
                         }
                     }
+                    MessageInstance::StartPingCheck(msg) => {
+                        let response = CompletePingCheck {
+                            ping_id: CompletePingCheck_PingID {
+                                ping_id: msg.ping_id.ping_id,
+                            },
+                        };
+                        ackmgr_tx_1.send_msg(response.into(), false);
+                    }
+                    MessageInstance::CompletePingCheck(msg) => {
+                        stats_reader
+                            .lock()
+                            .unwrap()
+                            .complete_ping(msg.ping_id.ping_id);
+                    }
                     msg => {
+                        if let MessageInstance::CloseCircuit(_) = msg {
+                            reporter.report(CircuitEvent::ClosedBySim);
+                        }
+
                         let _ = msg_handlers.handle(msg, &handler_context).map_err(|err| {
                             match err.kind {
                                 message_handlers::ErrorKind::NoHandler => {
                                     // Yield the message to the incoming message channel.
-                                    incoming_tx.send(err.msg).unwrap();
-                                }
-                                _ => {
-                                    // TODO: handle the error somewhere
-                                    panic!("unhandled error: {:?}", err);
+                                    let _ = incoming_tx.send(err.msg);
                                 }
+                                _ => reporter.report(CircuitEvent::HandlerError(err)),
                             }
                         });
                     }
@@ -246,9 +452,49 @@ impl Circuit {
         Ok(Circuit {
             incoming: incoming_rx,
             ackmgr_tx: ackmgr_tx_2,
+            events: events_tx,
+            subscriptions: subscriptions,
+            stats: stats,
+            events_out: events_out_rx,
+            shutdown: shutdown,
+            sender_thread: Some(sender_thread),
+            reader_thread: Some(reader_thread),
         })
     }
 
+    /// Close the circuit.
+    ///
+    /// This stops the reader thread, then sends `CloseCircuit` to the sim
+    /// together with all acks still pending, and finally waits for the sender
+    /// thread to exit. Messages which were queued before calling this will
+    /// still be sent, but reliable messages waiting for an ack will fail.
+    ///
+    /// Note that this does not log out the agent, for that see
+    /// `Simulator::logout`. Dropping the circuit closes it as well, but
+    /// ignores the errors.
+    pub fn close(mut self) -> Result<(), CloseCircuitError> {
+        self.stop_threads()
+    }
+
+    /// Stop both threads, even if one of them panicked.
+    fn stop_threads(&mut self) -> Result<(), CloseCircuitError> {
+        self.shutdown.store(true, Ordering::SeqCst);
+        let reader_result = match self.reader_thread.take() {
+            Some(reader_thread) => reader_thread
+                .join()
+                .map_err(|_| CloseCircuitError::ThreadPanicked("reader")),
+            None => Ok(()),
+        };
+
+        if let Some(sender_thread) = self.sender_thread.take() {
+            self.ackmgr_tx.send_msg(CloseCircuit {}.into(), false);
+            sender_thread
+                .join()
+                .map_err(|_| CloseCircuitError::ThreadPanicked("sender"))?;
+        }
+        reader_result
+    }
+
     pub fn message_sender(&self) -> MessageSender {
         MessageSender {
             ackmgr_tx: self.ackmgr_tx.clone(),
@@ -257,6 +503,9 @@ impl Circuit {
 
     /// Send a message through the circuit.
     ///
+    /// Messages which are zerocoded according to the message template will be
+    /// zerocoded automatically.
+    ///
     /// This returns a `SendMessage` instance which is a `Future`.
     /// However once you send it using this method you needn't
     /// necessarily poll it for progress to be made. It will be handed over
@@ -266,6 +515,46 @@ impl Circuit {
         self.ackmgr_tx.send_msg(msg.into(), reliable)
     }
 
+    /// Returns the channel on which errors and other problems of the circuit
+    /// are reported.
+    ///
+    /// Only a limited number of events is buffered, further events are
+    /// dropped until the application reads them.
+    pub fn events(&self) -> crossbeam_channel::Receiver<CircuitEvent> {
+        self.events_out.clone()
+    }
+
+    /// Subscribe to all incoming messages of a specific message type.
+    ///
+    /// Messages are passed to all subscribers in addition to the handlers or
+    /// the queue read by `read`.
+    pub fn subscribe(&self, m_type: MessageType) -> Subscription {
+        self.subscriptions.subscribe(m_type)
+    }
+
+    /// Subscribe to all incoming messages for which the filter evaluates to
+    /// true.
+    pub fn subscribe_filter(
+        &self,
+        filter: Box<Fn(&MessageInstance) -> bool + Send>,
+    ) -> Subscription {
+        self.subscriptions.subscribe_filter(filter)
+    }
+
+    /// Returns a dispatcher passing events of the event queue to the message
+    /// handlers of this circuit.
+    pub fn event_dispatcher(&self) -> EventDispatcher {
+        EventDispatcher {
+            events: self.events.clone(),
+        }
+    }
+
+    /// Returns the current statistics of the circuit, i.e. the round trip
+    /// time estimate.
+    pub fn stats(&self) -> CircuitStats {
+        self.stats.lock().unwrap().snapshot()
+    }
+
     /// Reads a message and returns it.
     ///
     /// If there is no message available yet it will block the current thread
@@ -286,15 +575,37 @@ impl Circuit {
     }
 }
 
+impl Drop for Circuit {
+    fn drop(&mut self) {
+        let _ = self.stop_threads();
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CircuitConfig {
-    /// The number of seconds before an unconfirmed packet becomes invalid.
+    /// The maximal time before an unconfirmed packet is resent.
+    /// The actual timeout is derived from the measured round trip time, this
+    /// value is only used until a first measurement is available and as an
+    /// upper bound.
     /// If multiple attempts are allowed, each single attempt will get at most
-    /// this time before
-    /// timing out.
+    /// this time before timing out.
     pub send_timeout: Duration,
 
+    /// The interval at which the sim is pinged to measure the round trip time.
+    pub ping_interval: Duration,
+
+    /// If nothing is received from the sim for this long,
+    /// `CircuitEvent::Timeout` is reported. This should be a multiple of
+    /// `ping_interval`, as the sim answers our pings.
+    pub receive_timeout: Duration,
+
     /// The number of times resending an unacknowledged packet before reporting
     /// it as failure.
     pub send_attempts: usize,
+
+    /// Limits for the bandwidth of our outgoing packets by category.
+    /// If `None` outgoing packets are only prioritized but not rate limited.
+    ///
+    /// To limit the bandwidth of the sim see `Simulator::set_throttle`.
+    pub send_throttle: Option<Throttle>,
 }
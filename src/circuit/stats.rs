@@ -0,0 +1,211 @@
+//! Round trip time estimation and statistics of a circuit.
+//!
+//! The round trip time is measured with `StartPingCheck` messages sent by us
+//! and smoothed the same way TCP does it (RFC 6298). The retransmission
+//! timeout of reliable packets is derived from it.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Lower bound of the retransmission timeout.
+const MIN_RETRANSMIT_TIMEOUT_MS: u64 = 250;
+
+/// Number of pings without reply to keep track of, older pings are
+/// considered lost.
+const MAX_PENDING_PINGS: usize = 16;
+
+/// A snapshot of the statistics of a circuit.
+#[derive(Clone, Debug)]
+pub struct CircuitStats {
+    /// Smoothed round trip time, `None` if no ping was answered yet.
+    pub rtt: Option<Duration>,
+
+    /// Variance of the round trip time, `None` if no ping was answered yet.
+    pub rtt_variance: Option<Duration>,
+
+    /// The timeout after which an unacknowledged reliable packet is resent.
+    pub retransmit_timeout: Duration,
+
+    /// Number of `StartPingCheck` messages we sent.
+    pub pings_sent: u32,
+
+    /// Number of our pings which were answered by the sim.
+    pub pings_completed: u32,
+}
+
+/// Keeps track of pending pings and the round trip time estimate.
+pub(crate) struct StatsTracker {
+    /// Smoothed round trip time in seconds.
+    srtt: Option<f64>,
+    /// Round trip time variance in seconds.
+    rttvar: f64,
+
+    pending_pings: HashMap<u8, Instant>,
+    next_ping_id: u8,
+    pings_sent: u32,
+    pings_completed: u32,
+
+    /// Used as retransmission timeout until the first sample is available,
+    /// and as upper bound afterwards.
+    max_timeout: Duration,
+}
+
+impl StatsTracker {
+    pub fn new(max_timeout: Duration) -> Self {
+        StatsTracker {
+            srtt: None,
+            rttvar: 0.,
+            pending_pings: HashMap::new(),
+            next_ping_id: 0,
+            pings_sent: 0,
+            pings_completed: 0,
+            max_timeout: max_timeout,
+        }
+    }
+
+    /// Register a new ping which is about to be sent and return its id.
+    pub fn start_ping(&mut self) -> u8 {
+        let id = self.next_ping_id;
+        self.next_ping_id = self.next_ping_id.wrapping_add(1);
+        self.pings_sent += 1;
+
+        // Forget about pings which were most likely lost.
+        if self.pending_pings.len() >= MAX_PENDING_PINGS {
+            let oldest = self
+                .pending_pings
+                .iter()
+                .min_by_key(|&(_, sent)| *sent)
+                .map(|(id, _)| *id);
+            if let Some(oldest) = oldest {
+                self.pending_pings.remove(&oldest);
+            }
+        }
+
+        self.pending_pings.insert(id, Instant::now());
+        id
+    }
+
+    /// Register the reply of the sim to one of our pings.
+    ///
+    /// Replies to unknown pings are ignored.
+    pub fn complete_ping(&mut self, id: u8) {
+        if let Some(sent) = self.pending_pings.remove(&id) {
+            self.pings_completed += 1;
+            self.add_sample(sent.elapsed());
+        }
+    }
+
+    /// Update the estimate with a new round trip time measurement.
+    pub fn add_sample(&mut self, rtt: Duration) {
+        let r = duration_to_secs(rtt);
+        match self.srtt {
+            None => {
+                self.srtt = Some(r);
+                self.rttvar = r / 2.;
+            }
+            Some(srtt) => {
+                self.rttvar = 0.75 * self.rttvar + 0.25 * (srtt - r).abs();
+                self.srtt = Some(0.875 * srtt + 0.125 * r);
+            }
+        }
+    }
+
+    /// The timeout after which an unacknowledged packet should be resent.
+    pub fn retransmit_timeout(&self) -> Duration {
+        match self.srtt {
+            None => self.max_timeout,
+            Some(srtt) => {
+                let rto = secs_to_duration(srtt + 4. * self.rttvar);
+                let min = Duration::from_millis(MIN_RETRANSMIT_TIMEOUT_MS);
+                if rto < min {
+                    min
+                } else if rto > self.max_timeout {
+                    self.max_timeout
+                } else {
+                    rto
+                }
+            }
+        }
+    }
+
+    pub fn snapshot(&self) -> CircuitStats {
+        CircuitStats {
+            rtt: self.srtt.map(secs_to_duration),
+            rtt_variance: self.srtt.map(|_| secs_to_duration(self.rttvar)),
+            retransmit_timeout: self.retransmit_timeout(),
+            pings_sent: self.pings_sent,
+            pings_completed: self.pings_completed,
+        }
+    }
+}
+
+fn duration_to_secs(d: Duration) -> f64 {
+    d.as_secs() as f64 + d.subsec_nanos() as f64 * 1e-9
+}
+
+fn secs_to_duration(secs: f64) -> Duration {
+    let whole = secs.trunc();
+    Duration::new(whole as u64, ((secs - whole) * 1e9) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(d: Duration) -> u64 {
+        d.as_secs() * 1000 + (d.subsec_nanos() / 1_000_000) as u64
+    }
+
+    #[test]
+    fn initial_timeout() {
+        let stats = StatsTracker::new(Duration::from_secs(5));
+        assert_eq!(stats.retransmit_timeout(), Duration::from_secs(5));
+        assert!(stats.snapshot().rtt.is_none());
+    }
+
+    #[test]
+    fn smoothed_estimate() {
+        let mut stats = StatsTracker::new(Duration::from_secs(5));
+        stats.add_sample(Duration::from_millis(200));
+        assert_eq!(millis(stats.snapshot().rtt.unwrap()), 200);
+        // srtt + 4 * (srtt / 2)
+        assert_eq!(millis(stats.retransmit_timeout()), 600);
+
+        stats.add_sample(Duration::from_millis(200));
+        let snapshot = stats.snapshot();
+        assert_eq!(millis(snapshot.rtt.unwrap()), 200);
+        assert_eq!(millis(snapshot.rtt_variance.unwrap()), 75);
+    }
+
+    #[test]
+    fn timeout_bounds() {
+        let mut stats = StatsTracker::new(Duration::from_secs(5));
+        for _ in 0..50 {
+            stats.add_sample(Duration::from_millis(1));
+        }
+        assert_eq!(
+            stats.retransmit_timeout(),
+            Duration::from_millis(MIN_RETRANSMIT_TIMEOUT_MS)
+        );
+
+        let mut stats = StatsTracker::new(Duration::from_secs(5));
+        stats.add_sample(Duration::from_secs(10));
+        assert_eq!(stats.retransmit_timeout(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn ping_ids() {
+        let mut stats = StatsTracker::new(Duration::from_secs(5));
+        let a = stats.start_ping();
+        let b = stats.start_ping();
+        assert_ne!(a, b);
+
+        stats.complete_ping(b);
+        stats.complete_ping(b);
+        stats.complete_ping(a.wrapping_add(100));
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.pings_sent, 2);
+        assert_eq!(snapshot.pings_completed, 1);
+        assert!(snapshot.rtt.is_some());
+    }
+}
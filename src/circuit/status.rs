@@ -70,17 +70,29 @@ impl Future for SendMessage {
 pub enum SendMessageError {
     /// Remote failed to acknowledge the packet.
     FailedAck,
+    /// The circuit was closed before the packet was acknowledged.
+    CircuitClosed,
+    /// The message could not be encoded.
+    InvalidMessage,
 }
 
 impl ::std::fmt::Display for SendMessageError {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> Result<(), ::std::fmt::Error> {
-        write!(f, "ack failed.")
+        match *self {
+            SendMessageError::FailedAck => write!(f, "ack failed."),
+            SendMessageError::CircuitClosed => write!(f, "circuit closed."),
+            SendMessageError::InvalidMessage => write!(f, "invalid message."),
+        }
     }
 }
 
 impl ::std::error::Error for SendMessageError {
     fn description(&self) -> &str {
-        "Ack failed."
+        match *self {
+            SendMessageError::FailedAck => "Ack failed.",
+            SendMessageError::CircuitClosed => "Circuit closed.",
+            SendMessageError::InvalidMessage => "Invalid message.",
+        }
     }
 }
 
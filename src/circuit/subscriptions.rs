@@ -0,0 +1,186 @@
+//! Subscriptions to incoming messages.
+//!
+//! In contrast to message handlers, which take ownership of the messages they
+//! handle, any number of subscribers can observe the same messages. Each
+//! subscriber receives a copy of every matching message, independent of
+//! whether a handler took care of it.
+
+use futures::sync::mpsc;
+use futures::{Poll, Stream};
+use messages::{MessageInstance, MessageType};
+use std::sync::{Arc, Mutex, Weak};
+
+type FilterFn = Box<Fn(&MessageInstance) -> bool + Send>;
+
+enum Selector {
+    Type(MessageType),
+    Filter(FilterFn),
+}
+
+impl Selector {
+    fn matches(&self, msg: &MessageInstance) -> bool {
+        match *self {
+            Selector::Type(ref m_type) => msg.message_type() == *m_type,
+            Selector::Filter(ref filter) => filter(msg),
+        }
+    }
+}
+
+struct Subscriber {
+    id: u64,
+    selector: Selector,
+    sender: mpsc::UnboundedSender<MessageInstance>,
+}
+
+struct Inner {
+    next_id: u64,
+    subscribers: Vec<Subscriber>,
+}
+
+/// The set of subscribers of a circuit.
+///
+/// This can be cloned and shared between threads, all clones refer to the
+/// same set of subscribers.
+#[derive(Clone)]
+pub struct Subscriptions {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl Subscriptions {
+    pub(crate) fn new() -> Self {
+        Subscriptions {
+            inner: Arc::new(Mutex::new(Inner {
+                next_id: 0,
+                subscribers: Vec::new(),
+            })),
+        }
+    }
+
+    /// Subscribe to all messages of a specific message type.
+    pub fn subscribe(&self, m_type: MessageType) -> Subscription {
+        self.add(Selector::Type(m_type))
+    }
+
+    /// Subscribe to all messages for which the filter evaluates to true.
+    pub fn subscribe_filter(&self, filter: FilterFn) -> Subscription {
+        self.add(Selector::Filter(filter))
+    }
+
+    fn add(&self, selector: Selector) -> Subscription {
+        let (sender, receiver) = mpsc::unbounded();
+        let mut inner = self.inner.lock().unwrap();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.subscribers.push(Subscriber {
+            id: id,
+            selector: selector,
+            sender: sender,
+        });
+
+        Subscription {
+            id: id,
+            subscriptions: Arc::downgrade(&self.inner),
+            receiver: receiver,
+        }
+    }
+
+    /// Pass a copy of the message to all matching subscribers.
+    pub(crate) fn notify(&self, msg: &MessageInstance) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.subscribers.retain(|subscriber| {
+            if subscriber.selector.matches(msg) {
+                subscriber.sender.unbounded_send(msg.clone()).is_ok()
+            } else {
+                true
+            }
+        });
+    }
+
+    /// Returns the number of active subscriptions.
+    pub fn count(&self) -> usize {
+        self.inner.lock().unwrap().subscribers.len()
+    }
+}
+
+/// A stream of the messages matching a subscription.
+///
+/// The subscription is cancelled when this is dropped. The stream ends when
+/// the circuit is gone.
+pub struct Subscription {
+    id: u64,
+    subscriptions: Weak<Mutex<Inner>>,
+    receiver: mpsc::UnboundedReceiver<MessageInstance>,
+}
+
+impl Stream for Subscription {
+    type Item = MessageInstance;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<MessageInstance>, ()> {
+        self.receiver.poll()
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if let Some(inner) = self.subscriptions.upgrade() {
+            let id = self.id;
+            inner
+                .lock()
+                .unwrap()
+                .subscribers
+                .retain(|subscriber| subscriber.id != id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use messages::all::{CloseCircuit, DisableSimulator};
+
+    #[test]
+    fn multiple_subscribers() {
+        let subscriptions = Subscriptions::new();
+        let a = subscriptions.subscribe(MessageType::CloseCircuit);
+        let b = subscriptions.subscribe(MessageType::CloseCircuit);
+        let c = subscriptions.subscribe_filter(Box::new(|msg: &MessageInstance| match *msg {
+            MessageInstance::DisableSimulator(_) => true,
+            _ => false,
+        }));
+
+        subscriptions.notify(&CloseCircuit {}.into());
+        subscriptions.notify(&DisableSimulator {}.into());
+
+        let mut a = a.wait();
+        let mut b = b.wait();
+        let mut c = c.wait();
+        match a.next() {
+            Some(Ok(MessageInstance::CloseCircuit(_))) => {}
+            m => panic!("unexpected: {:?}", m),
+        }
+        match b.next() {
+            Some(Ok(MessageInstance::CloseCircuit(_))) => {}
+            m => panic!("unexpected: {:?}", m),
+        }
+        match c.next() {
+            Some(Ok(MessageInstance::DisableSimulator(_))) => {}
+            m => panic!("unexpected: {:?}", m),
+        }
+    }
+
+    #[test]
+    fn unsubscribe_on_drop() {
+        let subscriptions = Subscriptions::new();
+        let a = subscriptions.subscribe(MessageType::CloseCircuit);
+        let b = subscriptions.subscribe(MessageType::CloseCircuit);
+        assert_eq!(subscriptions.count(), 2);
+
+        drop(a);
+        assert_eq!(subscriptions.count(), 1);
+
+        // The stream ends once the subscriptions are gone.
+        drop(subscriptions);
+        assert!(b.wait().next().is_none());
+    }
+}
@@ -0,0 +1,413 @@
+//! Bandwidth throttling of a circuit.
+//!
+//! There are two sides to this:
+//!
+//! - `Throttle` tells the sim how much bandwidth we accept for each
+//!   category of traffic it sends to us, by sending it as `AgentThrottle`.
+//! - The sender thread of the circuit uses the same categories to limit and
+//!   prioritize our own outgoing packets with a token bucket per category, so
+//!   that for example a large asset upload can't delay `AgentUpdate` messages.
+//!   Messages which don't belong to any category (agent updates, acks, pings,
+//!   chat, ...) are never throttled and always sent first.
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use messages::all::{AgentThrottle, AgentThrottle_AgentData, AgentThrottle_Throttle};
+use messages::MessageType;
+use types::Uuid;
+
+use std::collections::VecDeque;
+use std::io::Cursor;
+use std::time::Instant;
+
+/// Size of the encoded throttle values in an `AgentThrottle` message.
+const ENCODED_SIZE: usize = 4 * 7;
+
+/// Amount of time worth of traffic a category is allowed to send in a
+/// single burst.
+const BURST_SECS: f64 = 0.5;
+
+/// The smallest possible burst size in bytes, which has to fit at least one
+/// maximum sized packet.
+const MIN_BURST_BYTES: f64 = 1500.;
+
+/// Outgoing rates below this value (bits per second) are raised to it, so
+/// that no category can stall completely.
+const MIN_RATE_BPS: f32 = 10_000.;
+
+/// The categories bandwidth is allocated to.
+///
+/// The order of the variants is both the order of the values in
+/// `AgentThrottle` and the priority of our outgoing packets.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ThrottleCategory {
+    /// Resending unacknowledged packets.
+    Resend,
+    /// `LayerData` terrain.
+    Land,
+    /// `LayerData` wind.
+    Wind,
+    /// `LayerData` clouds.
+    Cloud,
+    /// Objects in the scene.
+    Task,
+    /// Textures.
+    Texture,
+    /// Assets other than textures.
+    Asset,
+}
+
+impl ThrottleCategory {
+    pub const ALL: [ThrottleCategory; 7] = [
+        ThrottleCategory::Resend,
+        ThrottleCategory::Land,
+        ThrottleCategory::Wind,
+        ThrottleCategory::Cloud,
+        ThrottleCategory::Task,
+        ThrottleCategory::Texture,
+        ThrottleCategory::Asset,
+    ];
+
+    /// The category an outgoing message is throttled in, `None` if it is not
+    /// throttled.
+    ///
+    /// Resent packets are always throttled in `Resend`, independent of the
+    /// message they contain.
+    pub fn of_outgoing(message_type: &MessageType) -> Option<ThrottleCategory> {
+        match *message_type {
+            MessageType::ModifyLand => Some(ThrottleCategory::Land),
+            MessageType::ObjectAdd
+            | MessageType::ObjectDelete
+            | MessageType::ObjectDuplicate
+            | MessageType::ObjectExtraParams
+            | MessageType::ObjectImage
+            | MessageType::ObjectLink
+            | MessageType::ObjectDelink
+            | MessageType::ObjectMaterial
+            | MessageType::ObjectName
+            | MessageType::ObjectDescription
+            | MessageType::ObjectPermissions
+            | MessageType::ObjectPosition
+            | MessageType::ObjectRotation
+            | MessageType::ObjectScale
+            | MessageType::ObjectShape
+            | MessageType::MultipleObjectUpdate
+            | MessageType::RequestMultipleObjects
+            | MessageType::RezObject
+            | MessageType::DeRezObject
+            | MessageType::UpdateTaskInventory => Some(ThrottleCategory::Task),
+            MessageType::RequestImage => Some(ThrottleCategory::Texture),
+            MessageType::AssetUploadRequest
+            | MessageType::SendXferPacket
+            | MessageType::RequestXfer
+            | MessageType::TransferRequest
+            | MessageType::TransferPacket => Some(ThrottleCategory::Asset),
+            _ => None,
+        }
+    }
+}
+
+/// Maximum bandwidth per category in bits per second.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Throttle {
+    pub resend: f32,
+    pub land: f32,
+    pub wind: f32,
+    pub cloud: f32,
+    pub task: f32,
+    pub texture: f32,
+    pub asset: f32,
+}
+
+impl Default for Throttle {
+    /// The distribution the official viewer uses for a total of 1000 kbit/s.
+    fn default() -> Self {
+        Throttle {
+            resend: 100_000.,
+            land: 100_000.,
+            wind: 20_000.,
+            cloud: 20_000.,
+            task: 310_000.,
+            texture: 310_000.,
+            asset: 140_000.,
+        }
+    }
+}
+
+impl Throttle {
+    /// Returns the rate of a category in bits per second.
+    pub fn rate(&self, category: ThrottleCategory) -> f32 {
+        match category {
+            ThrottleCategory::Resend => self.resend,
+            ThrottleCategory::Land => self.land,
+            ThrottleCategory::Wind => self.wind,
+            ThrottleCategory::Cloud => self.cloud,
+            ThrottleCategory::Task => self.task,
+            ThrottleCategory::Texture => self.texture,
+            ThrottleCategory::Asset => self.asset,
+        }
+    }
+
+    /// The sum of the rates of all categories in bits per second.
+    pub fn total(&self) -> f32 {
+        ThrottleCategory::ALL.iter().map(|c| self.rate(*c)).sum()
+    }
+
+    /// Encode the rates in the format of `AgentThrottle`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(ENCODED_SIZE);
+        for category in ThrottleCategory::ALL.iter() {
+            buf.write_f32::<LittleEndian>(self.rate(*category)).unwrap();
+        }
+        buf
+    }
+
+    /// Decode the rates from the format of `AgentThrottle`.
+    ///
+    /// Returns `None` if there are not enough bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < ENCODED_SIZE {
+            return None;
+        }
+        let mut reader = Cursor::new(bytes);
+        let mut next = || reader.read_f32::<LittleEndian>().unwrap();
+        Some(Throttle {
+            resend: next(),
+            land: next(),
+            wind: next(),
+            cloud: next(),
+            task: next(),
+            texture: next(),
+            asset: next(),
+        })
+    }
+
+    /// Create the `AgentThrottle` message informing the sim about this
+    /// throttle.
+    ///
+    /// The sim ignores messages with a `gen_counter` lower than the one it
+    /// received last, so it should be increased with each update.
+    pub fn to_message(
+        &self,
+        agent_id: Uuid,
+        session_id: Uuid,
+        circuit_code: u32,
+        gen_counter: u32,
+    ) -> AgentThrottle {
+        AgentThrottle {
+            agent_data: AgentThrottle_AgentData {
+                agent_id: agent_id,
+                session_id: session_id,
+                circuit_code: circuit_code,
+            },
+            throttle: AgentThrottle_Throttle {
+                gen_counter: gen_counter,
+                throttles: self.to_bytes(),
+            },
+        }
+    }
+}
+
+/// Token bucket limiting the rate of one category.
+///
+/// A packet may be sent as long as there are tokens left, even if it is
+/// larger than the remaining tokens, in which case the bucket goes into debt.
+/// This way packets never get stuck because they don't fit into the bucket.
+struct TokenBucket {
+    /// Refill rate in bytes per second, `None` if unlimited.
+    rate: Option<f64>,
+    capacity: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate_bps: Option<f32>, now: Instant) -> Self {
+        let rate = rate_bps.map(|r| r.max(MIN_RATE_BPS) as f64 / 8.);
+        let capacity = rate
+            .map(|r| (r * BURST_SECS).max(MIN_BURST_BYTES))
+            .unwrap_or(0.);
+        TokenBucket {
+            rate: rate,
+            capacity: capacity,
+            tokens: capacity,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        if let Some(rate) = self.rate {
+            if now > self.last_refill {
+                let elapsed = now - self.last_refill;
+                let secs = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 * 1e-9;
+                self.tokens = (self.tokens + secs * rate).min(self.capacity);
+            }
+        }
+        self.last_refill = now;
+    }
+
+    fn is_ready(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        self.rate.is_none() || self.tokens > 0.
+    }
+
+    fn consume(&mut self, bytes: usize) {
+        if self.rate.is_some() {
+            self.tokens -= bytes as f64;
+        }
+    }
+}
+
+/// Outgoing queue of the circuit, ordering messages by category and rate.
+pub(crate) struct SendScheduler<T> {
+    unthrottled: VecDeque<T>,
+    /// One queue per category, in order of priority.
+    throttled: Vec<(ThrottleCategory, TokenBucket, VecDeque<T>)>,
+}
+
+impl<T> SendScheduler<T> {
+    /// Create a new scheduler, if `throttle` is `None` the categories are
+    /// only used for prioritization but not rate limited.
+    pub fn new(throttle: Option<&Throttle>, now: Instant) -> Self {
+        SendScheduler {
+            unthrottled: VecDeque::new(),
+            throttled: ThrottleCategory::ALL
+                .iter()
+                .map(|c| {
+                    let bucket = TokenBucket::new(throttle.map(|t| t.rate(*c)), now);
+                    (*c, bucket, VecDeque::new())
+                })
+                .collect(),
+        }
+    }
+
+    /// Queue an item to be sent.
+    pub fn push(&mut self, category: Option<ThrottleCategory>, item: T) {
+        match category {
+            None => self.unthrottled.push_back(item),
+            Some(category) => {
+                let queue = self
+                    .throttled
+                    .iter_mut()
+                    .find(|&&mut (c, _, _)| c == category)
+                    .map(|&mut (_, _, ref mut queue)| queue)
+                    .unwrap();
+                queue.push_back(item);
+            }
+        }
+    }
+
+    /// Acquire bandwidth for an item which is not managed by the queue, i.e.
+    /// a resent packet.
+    ///
+    /// Returns false if the category has no bandwidth left right now.
+    pub fn try_acquire(&mut self, category: ThrottleCategory, bytes: usize, now: Instant) -> bool {
+        let bucket = self
+            .throttled
+            .iter_mut()
+            .find(|&&mut (c, _, _)| c == category)
+            .map(|&mut (_, ref mut bucket, _)| bucket)
+            .unwrap();
+        if bucket.is_ready(now) {
+            bucket.consume(bytes);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the next item to be sent, if there is one which may be sent
+    /// right now.
+    ///
+    /// `size` returns the number of bytes an item will take on the wire.
+    pub fn pop<F: Fn(&T) -> usize>(&mut self, size: F, now: Instant) -> Option<T> {
+        if let Some(item) = self.unthrottled.pop_front() {
+            return Some(item);
+        }
+
+        for &mut (_, ref mut bucket, ref mut queue) in self.throttled.iter_mut() {
+            if queue.is_empty() || !bucket.is_ready(now) {
+                continue;
+            }
+            let item = queue.pop_front().unwrap();
+            bucket.consume(size(&item));
+            return Some(item);
+        }
+        None
+    }
+
+    /// Returns true if no items are queued.
+    pub fn is_empty(&self) -> bool {
+        self.unthrottled.is_empty() && self.throttled.iter().all(|&(_, _, ref q)| q.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn encode_roundtrip() {
+        let throttle = Throttle::default();
+        let bytes = throttle.to_bytes();
+        assert_eq!(bytes.len(), ENCODED_SIZE);
+        assert_eq!(&bytes[0..4], &[0x00, 0x50, 0xc3, 0x47]);
+        assert_eq!(Throttle::from_bytes(&bytes), Some(throttle));
+        assert_eq!(Throttle::from_bytes(&bytes[1..]), None);
+    }
+
+    #[test]
+    fn unthrottled_first() {
+        let now = Instant::now();
+        let mut scheduler = SendScheduler::new(Some(&Throttle::default()), now);
+        scheduler.push(Some(ThrottleCategory::Asset), "upload");
+        scheduler.push(Some(ThrottleCategory::Texture), "texture");
+        scheduler.push(None, "agent update");
+
+        let size = |_: &&str| 1000;
+        assert_eq!(scheduler.pop(size, now), Some("agent update"));
+        assert_eq!(scheduler.pop(size, now), Some("texture"));
+        assert_eq!(scheduler.pop(size, now), Some("upload"));
+        assert_eq!(scheduler.pop(size, now), None);
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn rate_limited() {
+        let now = Instant::now();
+        let mut throttle = Throttle::default();
+        // 8000 bytes per second, burst of 4000 bytes.
+        throttle.asset = 64_000.;
+        let mut scheduler = SendScheduler::new(Some(&throttle), now);
+        for i in 0..10 {
+            scheduler.push(Some(ThrottleCategory::Asset), i);
+        }
+
+        let size = |_: &i32| 1000;
+        let sent = (0..10).filter_map(|_| scheduler.pop(size, now)).count();
+        assert_eq!(sent, 4);
+
+        // Half a second later the bucket is full again.
+        let later = now + Duration::from_millis(500);
+        let sent = (0..10).filter_map(|_| scheduler.pop(size, later)).count();
+        assert_eq!(sent, 4);
+
+        // Unthrottled items are not affected.
+        scheduler.push(None, 100);
+        assert_eq!(scheduler.pop(size, later), Some(100));
+    }
+
+    #[test]
+    fn no_throttle() {
+        let now = Instant::now();
+        let mut scheduler = SendScheduler::new(None, now);
+        for i in 0..100 {
+            scheduler.push(Some(ThrottleCategory::Asset), i);
+        }
+        let sent = (0..100)
+            .filter_map(|_| scheduler.pop(|_| 1500, now))
+            .count();
+        assert_eq!(sent, 100);
+        assert!(scheduler.try_acquire(ThrottleCategory::Resend, 1500, now));
+    }
+}
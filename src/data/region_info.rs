@@ -18,7 +18,8 @@ pub struct RegionInfo {
 
     pub water_height: f32,
 
-    // TODO ? Also do we need this be exposed?
+    /// Identifies the objects cached for the region, it changes whenever
+    /// they become invalid.
     pub cache_id: Uuid,
 
     pub terrain_base: [Uuid; 4],
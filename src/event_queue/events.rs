@@ -0,0 +1,542 @@
+//! Typed events received through the event queue.
+//!
+//! Events which have an equivalent in the UDP message template are decoded
+//! into the corresponding `MessageInstance`, so handlers don't need to care
+//! about which way the sim chose to deliver them.
+
+use llsd::data::{Map, Scalar, Value};
+use messages::all::{
+    CrossedRegion, CrossedRegion_AgentData, CrossedRegion_Info, CrossedRegion_RegionData,
+    DisableSimulator, EnableSimulator, EnableSimulator_SimulatorInfo, ParcelProperties,
+    ParcelProperties_AgeVerificationBlock, ParcelProperties_ParcelData, TeleportFinish,
+    TeleportFinish_Info,
+};
+use messages::MessageInstance;
+use types::{Ip4Addr, Uuid, Vector3};
+use url::Url;
+
+use std::net::SocketAddr;
+
+#[derive(Debug, Fail)]
+pub enum DecodeEventError {
+    #[fail(display = "Missing field in event: {}", 0)]
+    MissingField(&'static str),
+
+    #[fail(display = "Field of event has the wrong type: {}", 0)]
+    WrongType(&'static str),
+}
+
+#[derive(Clone, Debug)]
+pub enum Event {
+    /// An event equivalent to a message of the UDP protocol, i.e.
+    /// `EnableSimulator`, `DisableSimulator`, `TeleportFinish`,
+    /// `CrossedRegion` or `ParcelProperties`.
+    Message(MessageInstance),
+
+    EstablishAgentCommunication(EstablishAgentCommunication),
+    ChatterBoxInvitation(ChatterBoxInvitation),
+    ChatterBoxSessionStartReply(ChatterBoxSessionStartReply),
+
+    /// Any other event, left undecoded.
+    Unknown {
+        name: String,
+        body: Value,
+    },
+}
+
+/// Sent after `EnableSimulator` once the child agent in the neighbour
+/// region is ready.
+#[derive(Clone, Debug)]
+pub struct EstablishAgentCommunication {
+    pub agent_id: Uuid,
+    pub sim_address: SocketAddr,
+    pub seed_capability: Url,
+}
+
+/// An invitation to a chat session, i.e. an instant message.
+#[derive(Clone, Debug)]
+pub struct ChatterBoxInvitation {
+    pub session_id: Uuid,
+    pub session_name: String,
+    pub from_id: Uuid,
+    pub from_name: String,
+    pub message: String,
+}
+
+/// The reply of the sim to our request to start a chat session.
+#[derive(Clone, Debug)]
+pub struct ChatterBoxSessionStartReply {
+    pub session_id: Uuid,
+    pub temp_session_id: Uuid,
+    pub success: bool,
+}
+
+impl Event {
+    /// The name of the event as used in the event queue.
+    pub fn name(&self) -> &str {
+        match *self {
+            Event::Message(ref msg) => match *msg {
+                MessageInstance::EnableSimulator(_) => "EnableSimulator",
+                MessageInstance::DisableSimulator(_) => "DisableSimulator",
+                MessageInstance::TeleportFinish(_) => "TeleportFinish",
+                MessageInstance::CrossedRegion(_) => "CrossedRegion",
+                MessageInstance::ParcelProperties(_) => "ParcelProperties",
+                _ => "Message",
+            },
+            Event::EstablishAgentCommunication(_) => "EstablishAgentCommunication",
+            Event::ChatterBoxInvitation(_) => "ChatterBoxInvitation",
+            Event::ChatterBoxSessionStartReply(_) => "ChatterBoxSessionStartReply",
+            Event::Unknown { ref name, .. } => name,
+        }
+    }
+
+    /// Decode an event from its name and LLSD body.
+    pub fn decode(name: &str, body: Value) -> Result<Event, DecodeEventError> {
+        let map = match body {
+            Value::Map(map) => map,
+            body => {
+                return Ok(Event::Unknown {
+                    name: name.to_string(),
+                    body: body,
+                })
+            }
+        };
+
+        match name {
+            "EnableSimulator" => {
+                let info = first_block(&map, "SimulatorInfo")?;
+                Ok(Event::Message(
+                    EnableSimulator {
+                        simulator_info: EnableSimulator_SimulatorInfo {
+                            handle: binary_u64(info, "Handle")?,
+                            ip: binary_ip(info, "IP")?,
+                            port: int(info, "Port")? as u16,
+                        },
+                    }
+                    .into(),
+                ))
+            }
+            "DisableSimulator" => Ok(Event::Message(DisableSimulator {}.into())),
+            "TeleportFinish" => {
+                let info = first_block(&map, "Info")?;
+                Ok(Event::Message(
+                    TeleportFinish {
+                        info: TeleportFinish_Info {
+                            agent_id: uuid(info, "AgentID")?,
+                            location_id: int(info, "LocationID")? as u32,
+                            sim_ip: binary_ip(info, "SimIP")?,
+                            sim_port: int(info, "SimPort")? as u16,
+                            region_handle: binary_u64(info, "RegionHandle")?,
+                            seed_capability: string(info, "SeedCapability")?.into_bytes(),
+                            sim_access: int(info, "SimAccess")? as u8,
+                            teleport_flags: binary_u32(info, "TeleportFlags")?,
+                            region_size_x: int(info, "RegionSizeX").unwrap_or(256) as u32,
+                            region_size_y: int(info, "RegionSizeY").unwrap_or(256) as u32,
+                        },
+                    }
+                    .into(),
+                ))
+            }
+            "CrossedRegion" => {
+                let agent_data = first_block(&map, "AgentData")?;
+                let info = first_block(&map, "Info")?;
+                let region_data = first_block(&map, "RegionData")?;
+                Ok(Event::Message(
+                    CrossedRegion {
+                        agent_data: CrossedRegion_AgentData {
+                            agent_id: uuid(agent_data, "AgentID")?,
+                            session_id: uuid(agent_data, "SessionID")?,
+                        },
+                        region_data: CrossedRegion_RegionData {
+                            sim_ip: binary_ip(region_data, "SimIP")?,
+                            sim_port: int(region_data, "SimPort")? as u16,
+                            region_handle: binary_u64(region_data, "RegionHandle")?,
+                            seed_capability: string(region_data, "SeedCapability")?.into_bytes(),
+                        },
+                        info: CrossedRegion_Info {
+                            position: vector3(info, "Position")?,
+                            look_at: vector3(info, "LookAt")?,
+                        },
+                    }
+                    .into(),
+                ))
+            }
+            "ParcelProperties" => {
+                let data = first_block(&map, "ParcelData")?;
+                // Only sent by newer sims.
+                let region_deny_age_unverified = first_block(&map, "AgeVerificationBlock")
+                    .and_then(|block| boolean(block, "RegionDenyAgeUnverified"))
+                    .unwrap_or(false);
+                Ok(Event::Message(
+                    ParcelProperties {
+                        parcel_data: ParcelProperties_ParcelData {
+                            request_result: int(data, "RequestResult")?,
+                            sequence_id: int(data, "SequenceID")?,
+                            snap_selection: flag(data, "SnapSelection")?,
+                            self_count: int(data, "SelfCount")?,
+                            other_count: int(data, "OtherCount")?,
+                            public_count: int(data, "PublicCount")?,
+                            local_id: int(data, "LocalID")?,
+                            owner_id: uuid(data, "OwnerID")?,
+                            is_group_owned: flag(data, "IsGroupOwned")?,
+                            auction_id: uint(data, "AuctionID")?,
+                            // Not always sent as integer, it isn't used anyway.
+                            claim_date: int(data, "ClaimDate").unwrap_or(0),
+                            claim_price: int(data, "ClaimPrice")?,
+                            rent_price: int(data, "RentPrice")?,
+                            aabb_min: vector3(data, "AABBMin")?,
+                            aabb_max: vector3(data, "AABBMax")?,
+                            bitmap: binary(data, "Bitmap")?.to_vec(),
+                            area: int(data, "Area")?,
+                            status: int(data, "Status")? as u8,
+                            sim_wide_max_prims: int(data, "SimWideMaxPrims")?,
+                            sim_wide_total_prims: int(data, "SimWideTotalPrims")?,
+                            max_prims: int(data, "MaxPrims")?,
+                            total_prims: int(data, "TotalPrims")?,
+                            owner_prims: int(data, "OwnerPrims")?,
+                            group_prims: int(data, "GroupPrims")?,
+                            other_prims: int(data, "OtherPrims")?,
+                            selected_prims: int(data, "SelectedPrims")?,
+                            parcel_prim_bonus: real(data, "ParcelPrimBonus")? as f32,
+                            other_clean_time: int(data, "OtherCleanTime")?,
+                            parcel_flags: uint(data, "ParcelFlags")?,
+                            sale_price: int(data, "SalePrice")?,
+                            name: string(data, "Name")?.into_bytes(),
+                            desc: string(data, "Desc")?.into_bytes(),
+                            music_url: string(data, "MusicURL")?.into_bytes(),
+                            media_url: string(data, "MediaURL")?.into_bytes(),
+                            media_id: uuid(data, "MediaID")?,
+                            media_auto_scale: flag(data, "MediaAutoScale")? as u8,
+                            group_id: uuid(data, "GroupID")?,
+                            pass_price: int(data, "PassPrice")?,
+                            pass_hours: real(data, "PassHours")? as f32,
+                            category: int(data, "Category")? as u8,
+                            auth_buyer_id: uuid(data, "AuthBuyerID")?,
+                            snapshot_id: uuid(data, "SnapshotID")?,
+                            user_location: vector3(data, "UserLocation")?,
+                            user_look_at: vector3(data, "UserLookAt")?,
+                            landing_type: int(data, "LandingType")? as u8,
+                            region_push_override: flag(data, "RegionPushOverride")?,
+                            region_deny_anonymous: flag(data, "RegionDenyAnonymous")?,
+                            region_deny_identified: flag(data, "RegionDenyIdentified")?,
+                            region_deny_transacted: flag(data, "RegionDenyTransacted")?,
+                        },
+                        age_verification_block: ParcelProperties_AgeVerificationBlock {
+                            region_deny_age_unverified: region_deny_age_unverified,
+                        },
+                    }
+                    .into(),
+                ))
+            }
+            "EstablishAgentCommunication" => Ok(Event::EstablishAgentCommunication(
+                EstablishAgentCommunication {
+                    agent_id: uuid(&map, "agent-id")?,
+                    sim_address: string(&map, "sim-ip-and-port")?
+                        .parse()
+                        .map_err(|_| DecodeEventError::WrongType("sim-ip-and-port"))?,
+                    seed_capability: url(&map, "seed-capability")?,
+                },
+            )),
+            "ChatterBoxInvitation" => {
+                let params = get(&map, "instantmessage")
+                    .and_then(|v| as_map(v, "instantmessage"))
+                    .and_then(|m| get(m, "message_params"))
+                    .and_then(|v| as_map(v, "message_params"))?;
+                Ok(Event::ChatterBoxInvitation(ChatterBoxInvitation {
+                    session_id: uuid(&map, "session_id")?,
+                    session_name: string(&map, "session_name").unwrap_or_default(),
+                    from_id: uuid(params, "from_id")?,
+                    from_name: string(&map, "from_name")?,
+                    message: string(params, "message")?,
+                }))
+            }
+            "ChatterBoxSessionStartReply" => Ok(Event::ChatterBoxSessionStartReply(
+                ChatterBoxSessionStartReply {
+                    session_id: uuid(&map, "session_id")?,
+                    temp_session_id: uuid(&map, "temp_session_id")?,
+                    success: boolean(&map, "success")?,
+                },
+            )),
+            _ => Ok(Event::Unknown {
+                name: name.to_string(),
+                body: Value::Map(map),
+            }),
+        }
+    }
+}
+
+fn get<'a>(map: &'a Map, key: &'static str) -> Result<&'a Value, DecodeEventError> {
+    map.get(key).ok_or(DecodeEventError::MissingField(key))
+}
+
+fn as_map<'a>(value: &'a Value, key: &'static str) -> Result<&'a Map, DecodeEventError> {
+    match *value {
+        Value::Map(ref map) => Ok(map),
+        _ => Err(DecodeEventError::WrongType(key)),
+    }
+}
+
+fn scalar<'a>(map: &'a Map, key: &'static str) -> Result<&'a Scalar, DecodeEventError> {
+    match *get(map, key)? {
+        Value::Scalar(ref s) => Ok(s),
+        _ => Err(DecodeEventError::WrongType(key)),
+    }
+}
+
+/// Blocks of the UDP messages are encoded as an array of maps.
+fn first_block<'a>(map: &'a Map, key: &'static str) -> Result<&'a Map, DecodeEventError> {
+    match *get(map, key)? {
+        Value::Array(ref blocks) => blocks
+            .first()
+            .ok_or(DecodeEventError::MissingField(key))
+            .and_then(|block| as_map(block, key)),
+        _ => Err(DecodeEventError::WrongType(key)),
+    }
+}
+
+fn int(map: &Map, key: &'static str) -> Result<i32, DecodeEventError> {
+    match *scalar(map, key)? {
+        Scalar::Integer(i) => Ok(i),
+        _ => Err(DecodeEventError::WrongType(key)),
+    }
+}
+
+fn boolean(map: &Map, key: &'static str) -> Result<bool, DecodeEventError> {
+    match *scalar(map, key)? {
+        Scalar::Boolean(b) => Ok(b),
+        _ => Err(DecodeEventError::WrongType(key)),
+    }
+}
+
+/// Booleans of the UDP messages are sometimes sent as integers.
+fn flag(map: &Map, key: &'static str) -> Result<bool, DecodeEventError> {
+    match *scalar(map, key)? {
+        Scalar::Boolean(b) => Ok(b),
+        Scalar::Integer(i) => Ok(i != 0),
+        _ => Err(DecodeEventError::WrongType(key)),
+    }
+}
+
+fn real(map: &Map, key: &'static str) -> Result<f64, DecodeEventError> {
+    match *scalar(map, key)? {
+        Scalar::Real(r) => Ok(r),
+        Scalar::Integer(i) => Ok(i as f64),
+        _ => Err(DecodeEventError::WrongType(key)),
+    }
+}
+
+fn uuid(map: &Map, key: &'static str) -> Result<Uuid, DecodeEventError> {
+    match *scalar(map, key)? {
+        Scalar::Uuid(ref id) => Ok(id.clone()),
+        Scalar::String(ref s) => s.parse().map_err(|_| DecodeEventError::WrongType(key)),
+        _ => Err(DecodeEventError::WrongType(key)),
+    }
+}
+
+fn string(map: &Map, key: &'static str) -> Result<String, DecodeEventError> {
+    match *scalar(map, key)? {
+        Scalar::String(ref s) => Ok(s.clone()),
+        Scalar::Uri(ref u) => Ok(u.to_string()),
+        _ => Err(DecodeEventError::WrongType(key)),
+    }
+}
+
+fn url(map: &Map, key: &'static str) -> Result<Url, DecodeEventError> {
+    Url::parse(&string(map, key)?).map_err(|_| DecodeEventError::WrongType(key))
+}
+
+fn binary<'a>(map: &'a Map, key: &'static str) -> Result<&'a [u8], DecodeEventError> {
+    match *scalar(map, key)? {
+        Scalar::Binary(ref data) => Ok(data),
+        _ => Err(DecodeEventError::WrongType(key)),
+    }
+}
+
+/// Integers which don't fit into the signed 32 bits of LLSD are sent as big
+/// endian binary.
+fn binary_u64(map: &Map, key: &'static str) -> Result<u64, DecodeEventError> {
+    let data = binary(map, key)?;
+    if data.len() != 8 {
+        return Err(DecodeEventError::WrongType(key));
+    }
+    Ok(data.iter().fold(0, |acc, b| (acc << 8) | *b as u64))
+}
+
+fn binary_u32(map: &Map, key: &'static str) -> Result<u32, DecodeEventError> {
+    let data = binary(map, key)?;
+    if data.len() != 4 {
+        return Err(DecodeEventError::WrongType(key));
+    }
+    Ok(data.iter().fold(0, |acc, b| (acc << 8) | *b as u32))
+}
+
+/// Unsigned integers are sent as binary by some sims and as integer by others.
+fn uint(map: &Map, key: &'static str) -> Result<u32, DecodeEventError> {
+    match *scalar(map, key)? {
+        Scalar::Integer(i) => Ok(i as u32),
+        _ => binary_u32(map, key),
+    }
+}
+
+fn binary_ip(map: &Map, key: &'static str) -> Result<Ip4Addr, DecodeEventError> {
+    let data = binary(map, key)?;
+    if data.len() != 4 {
+        return Err(DecodeEventError::WrongType(key));
+    }
+    Ok(Ip4Addr::new(data[0], data[1], data[2], data[3]))
+}
+
+fn vector3(map: &Map, key: &'static str) -> Result<Vector3<f32>, DecodeEventError> {
+    let values = match *get(map, key)? {
+        Value::Array(ref values) if values.len() == 3 => values,
+        _ => return Err(DecodeEventError::WrongType(key)),
+    };
+    let mut v = [0f32; 3];
+    for (i, value) in values.iter().enumerate() {
+        v[i] = match *value {
+            Value::Scalar(Scalar::Real(r)) => r as f32,
+            Value::Scalar(Scalar::Integer(i)) => i as f32,
+            _ => return Err(DecodeEventError::WrongType(key)),
+        };
+    }
+    Ok(Vector3::new(v[0], v[1], v[2]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llsd;
+
+    fn decode(name: &str, xml: &str) -> Event {
+        let body = llsd::xml::read_value(xml.as_bytes()).unwrap();
+        Event::decode(name, body).unwrap()
+    }
+
+    #[test]
+    fn enable_simulator() {
+        let event = decode(
+            "EnableSimulator",
+            "<llsd><map><key>SimulatorInfo</key><array><map>\
+             <key>Handle</key><binary encoding=\"base64\">AAPoAAAD6QA=</binary>\
+             <key>IP</key><binary encoding=\"base64\">fwAAAQ==</binary>\
+             <key>Port</key><integer>9001</integer>\
+             </map></array></map></llsd>",
+        );
+        match event {
+            Event::Message(MessageInstance::EnableSimulator(msg)) => {
+                let info = msg.simulator_info;
+                assert_eq!(info.handle, 1099511628032256);
+                assert_eq!(info.ip, Ip4Addr::new(127, 0, 0, 1));
+                assert_eq!(info.port, 9001);
+            }
+            e => panic!("wrong event: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn establish_agent_communication() {
+        let event = decode(
+            "EstablishAgentCommunication",
+            "<llsd><map>\
+             <key>agent-id</key><uuid>10b2de5f-2030-4ac4-ab53-9a8f082af748</uuid>\
+             <key>sim-ip-and-port</key><string>127.0.0.1:9001</string>\
+             <key>seed-capability</key><string>http://127.0.0.1:9000/CAPS/abc/</string>\
+             </map></llsd>",
+        );
+        match event {
+            Event::EstablishAgentCommunication(e) => {
+                assert_eq!(e.sim_address, "127.0.0.1:9001".parse().unwrap());
+                assert_eq!(
+                    e.seed_capability.as_str(),
+                    "http://127.0.0.1:9000/CAPS/abc/"
+                );
+            }
+            e => panic!("wrong event: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parcel_properties() {
+        let event = decode(
+            "ParcelProperties",
+            "<llsd><map><key>ParcelData</key><array><map>\
+             <key>RequestResult</key><integer>0</integer>\
+             <key>SequenceID</key><integer>-10000</integer>\
+             <key>SnapSelection</key><boolean>false</boolean>\
+             <key>SelfCount</key><integer>0</integer>\
+             <key>OtherCount</key><integer>0</integer>\
+             <key>PublicCount</key><integer>0</integer>\
+             <key>LocalID</key><integer>1</integer>\
+             <key>OwnerID</key><uuid>10b2de5f-2030-4ac4-ab53-9a8f082af748</uuid>\
+             <key>IsGroupOwned</key><boolean>false</boolean>\
+             <key>AuctionID</key><binary encoding=\"base64\">AAAAAA==</binary>\
+             <key>ClaimDate</key><integer>0</integer>\
+             <key>ClaimPrice</key><integer>0</integer>\
+             <key>RentPrice</key><integer>0</integer>\
+             <key>AABBMin</key><array><real>0</real><real>0</real><real>0</real></array>\
+             <key>AABBMax</key><array><real>256</real><real>256</real><real>0</real></array>\
+             <key>Bitmap</key><binary encoding=\"base64\">/w==</binary>\
+             <key>Area</key><integer>65536</integer>\
+             <key>Status</key><integer>0</integer>\
+             <key>SimWideMaxPrims</key><integer>15000</integer>\
+             <key>SimWideTotalPrims</key><integer>12</integer>\
+             <key>MaxPrims</key><integer>15000</integer>\
+             <key>TotalPrims</key><integer>12</integer>\
+             <key>OwnerPrims</key><integer>12</integer>\
+             <key>GroupPrims</key><integer>0</integer>\
+             <key>OtherPrims</key><integer>0</integer>\
+             <key>SelectedPrims</key><integer>0</integer>\
+             <key>ParcelPrimBonus</key><real>1</real>\
+             <key>OtherCleanTime</key><integer>0</integer>\
+             <key>ParcelFlags</key><binary encoding=\"base64\">BAAAAQ==</binary>\
+             <key>SalePrice</key><integer>0</integer>\
+             <key>Name</key><string>Your Parcel</string>\
+             <key>Desc</key><string></string>\
+             <key>MusicURL</key><string></string>\
+             <key>MediaURL</key><string></string>\
+             <key>MediaID</key><uuid>00000000-0000-0000-0000-000000000000</uuid>\
+             <key>MediaAutoScale</key><integer>0</integer>\
+             <key>GroupID</key><uuid>00000000-0000-0000-0000-000000000000</uuid>\
+             <key>PassPrice</key><integer>0</integer>\
+             <key>PassHours</key><real>0</real>\
+             <key>Category</key><integer>0</integer>\
+             <key>AuthBuyerID</key><uuid>00000000-0000-0000-0000-000000000000</uuid>\
+             <key>SnapshotID</key><uuid>00000000-0000-0000-0000-000000000000</uuid>\
+             <key>UserLocation</key><array><real>128</real><real>128</real><real>25</real></array>\
+             <key>UserLookAt</key><array><real>0</real><real>0</real><real>0</real></array>\
+             <key>LandingType</key><integer>2</integer>\
+             <key>RegionPushOverride</key><boolean>false</boolean>\
+             <key>RegionDenyAnonymous</key><boolean>false</boolean>\
+             <key>RegionDenyIdentified</key><boolean>false</boolean>\
+             <key>RegionDenyTransacted</key><boolean>false</boolean>\
+             </map></array>\
+             <key>AgeVerificationBlock</key><array><map>\
+             <key>RegionDenyAgeUnverified</key><boolean>true</boolean>\
+             </map></array></map></llsd>",
+        );
+        assert_eq!(event.name(), "ParcelProperties");
+        match event {
+            Event::Message(MessageInstance::ParcelProperties(msg)) => {
+                let data = msg.parcel_data;
+                assert_eq!(data.local_id, 1);
+                assert_eq!(data.area, 65536);
+                assert_eq!(data.parcel_flags, 0x0400_0001);
+                assert_eq!(data.name, b"Your Parcel".to_vec());
+                assert_eq!(data.bitmap, vec![0xff]);
+                assert_eq!(data.user_location, Vector3::new(128., 128., 25.));
+                assert_eq!(data.landing_type, 2);
+                assert!(msg.age_verification_block.region_deny_age_unverified);
+            }
+            e => panic!("wrong event: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn unknown_and_invalid() {
+        let event = decode("SomethingNew", "<llsd><map></map></llsd>");
+        assert_eq!(event.name(), "SomethingNew");
+
+        let body = llsd::xml::read_value("<llsd><map></map></llsd>".as_bytes()).unwrap();
+        assert!(Event::decode("TeleportFinish", body).is_err());
+    }
+}
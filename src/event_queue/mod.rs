@@ -0,0 +1,216 @@
+//! Client for the `EventQueueGet` capability.
+//!
+//! Many events (i.e. `EnableSimulator`, `TeleportFinish`, `CrossedRegion` or
+//! chat session events) are not sent over the circuit but delivered through
+//! the event queue. It is a HTTP long-poll: each request is answered once
+//! there are events available (or after a timeout without any), and the
+//! next request acknowledges the received events by their id.
+//!
+//! Received events are passed to the circuit, which dispatches them through
+//! the same `message_handlers::Handlers` as the UDP messages. Events which are
+//! equivalent to UDP messages which no handler accepted can be read with
+//! `Circuit::read` like any other message.
+
+use futures::prelude::{await, *};
+use hyper;
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::StatusCode;
+use llsd;
+use llsd::data::{Map, Scalar, Value};
+use logging::Logger;
+use tokio_core::reactor::{Handle, Timeout};
+use util::http;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+mod events;
+pub use self::events::{
+    ChatterBoxInvitation, ChatterBoxSessionStartReply, DecodeEventError,
+    EstablishAgentCommunication, Event,
+};
+
+/// Delay before retrying a failed request, doubled with each failure.
+const RETRY_DELAY_MIN_MS: u64 = 500;
+const RETRY_DELAY_MAX_MS: u64 = 30_000;
+
+#[derive(Debug, Fail)]
+pub enum EventQueueError {
+    #[fail(display = "Request to the event queue failed: {}", 0)]
+    Request(String),
+
+    #[fail(display = "The event queue returned an error status: {}", 0)]
+    Status(StatusCode),
+
+    #[fail(display = "The event queue returned invalid LLSD.")]
+    InvalidLlsd,
+}
+
+/// Hands events over to the circuit for dispatching.
+#[derive(Clone)]
+pub struct EventDispatcher {
+    pub(crate) events: mpsc::Sender<Event>,
+}
+
+impl EventDispatcher {
+    /// Returns false if the circuit was closed.
+    fn dispatch(&self, event: Event) -> bool {
+        self.events.send(event).is_ok()
+    }
+}
+
+/// Long-polls the event queue until `shutdown` is set, the circuit is closed
+/// or the sim removes the queue.
+///
+/// Failed requests are retried with an increasing delay, as the long-poll
+/// connections are often reset by proxies or the sim.
+///
+/// Note that `shutdown` is only checked between two requests, so the future
+/// can take as long as a poll request to complete after setting it. Once it
+/// is set a last request with `done` is sent, telling the sim that it can
+/// release the queue.
+#[async]
+pub fn poll(
+    uri: hyper::Uri,
+    dispatcher: EventDispatcher,
+    shutdown: Arc<AtomicBool>,
+    handle: Handle,
+    logger: Logger,
+) -> Result<(), EventQueueError> {
+    let client = http::client().map_err(|e| EventQueueError::Request(format!("{}", e)))?;
+    let mut ack = None;
+    let mut retry_delay = RETRY_DELAY_MIN_MS;
+
+    while !shutdown.load(Ordering::SeqCst) {
+        let events = match await!(request_events(client.clone(), uri.clone(), ack)) {
+            Ok(Some((id, events))) => {
+                ack = Some(id);
+                events
+            }
+            // The poll timed out without any events.
+            Ok(None) => Vec::new(),
+            // The sim removed the queue, i.e. because the agent left.
+            Err(EventQueueError::Status(StatusCode::NOT_FOUND)) => {
+                info!(logger, "Event queue was closed by the sim.");
+                return Ok(());
+            }
+            Err(e) => {
+                warn!(
+                    logger,
+                    "Event queue request failed, retrying in {} ms: {}", retry_delay, e
+                );
+                let delay = Timeout::new(Duration::from_millis(retry_delay), &handle)
+                    .map_err(|e| EventQueueError::Request(format!("{}", e)))?;
+                await!(delay).map_err(|e| EventQueueError::Request(format!("{}", e)))?;
+                retry_delay = (retry_delay * 2).min(RETRY_DELAY_MAX_MS);
+                continue;
+            }
+        };
+        retry_delay = RETRY_DELAY_MIN_MS;
+
+        for (name, body) in events {
+            match Event::decode(&name, body) {
+                Ok(event) => {
+                    debug!(logger, "Received event: {}", name);
+                    if !dispatcher.dispatch(event) {
+                        return Ok(());
+                    }
+                }
+                Err(e) => warn!(logger, "Decoding event {} failed: {}", name, e),
+            }
+        }
+    }
+
+    // The response doesn't carry any events anymore, so it is not checked.
+    let request = post_request(&uri, ack, true)?;
+    await!(client.request(request)).map_err(|e| EventQueueError::Request(format!("{}", e)))?;
+    debug!(logger, "Event queue released.");
+    Ok(())
+}
+
+/// Perform one poll request, returns `None` if it timed out without events.
+#[async]
+fn request_events(
+    client: http::Client,
+    uri: hyper::Uri,
+    ack: Option<i32>,
+) -> Result<Option<(i32, Vec<(String, Value)>)>, EventQueueError> {
+    let request = post_request(&uri, ack, false)?;
+    let response =
+        await!(client.request(request)).map_err(|e| EventQueueError::Request(format!("{}", e)))?;
+    match response.status() {
+        StatusCode::BAD_GATEWAY | StatusCode::GATEWAY_TIMEOUT => return Ok(None),
+        status if !status.is_success() => return Err(EventQueueError::Status(status)),
+        _ => {}
+    }
+
+    let raw_data = await!(response
+        .into_body()
+        .concat2()
+        .map_err(|e| EventQueueError::Request(format!("{}", e))))?;
+    let value = llsd::xml::read_value(&raw_data[..]).map_err(|_| EventQueueError::InvalidLlsd)?;
+    parse_response(value).map(Some)
+}
+
+fn post_request(
+    uri: &hyper::Uri,
+    ack: Option<i32>,
+    done: bool,
+) -> Result<hyper::Request<hyper::Body>, EventQueueError> {
+    let body = request_body(ack, done);
+    hyper::Request::post(uri.clone())
+        .header(CONTENT_TYPE, "application/llsd+xml")
+        .header(CONTENT_LENGTH, body.len())
+        .body(hyper::Body::from(body))
+        .map_err(|e| EventQueueError::Request(format!("{}", e)))
+}
+
+fn request_body(ack: Option<i32>, done: bool) -> Vec<u8> {
+    let mut map = Map::new();
+    map.insert(
+        "ack".to_string(),
+        Value::Scalar(match ack {
+            Some(id) => Scalar::Integer(id),
+            None => Scalar::Undefined,
+        }),
+    );
+    map.insert("done".to_string(), Value::Scalar(Scalar::Boolean(done)));
+
+    let mut data = Vec::new();
+    llsd::xml::write_doc(&mut data, &Value::Map(map)).unwrap();
+    data
+}
+
+/// Returns the id of the response and the names and bodies of the events.
+fn parse_response(value: Value) -> Result<(i32, Vec<(String, Value)>), EventQueueError> {
+    let mut map = match value {
+        Value::Map(map) => map,
+        _ => return Err(EventQueueError::InvalidLlsd),
+    };
+    let id = match map.remove("id") {
+        Some(Value::Scalar(Scalar::Integer(id))) => id,
+        _ => return Err(EventQueueError::InvalidLlsd),
+    };
+    let events = match map.remove("events") {
+        Some(Value::Array(events)) => events,
+        _ => return Err(EventQueueError::InvalidLlsd),
+    };
+
+    let mut result = Vec::with_capacity(events.len());
+    for event in events {
+        let mut event = match event {
+            Value::Map(map) => map,
+            _ => return Err(EventQueueError::InvalidLlsd),
+        };
+        let name = match event.remove("message") {
+            Some(Value::Scalar(Scalar::String(name))) => name,
+            _ => return Err(EventQueueError::InvalidLlsd),
+        };
+        let body = event
+            .remove("body")
+            .unwrap_or_else(|| Value::Map(Map::new()));
+        result.push((name, body));
+    }
+    Ok((id, result))
+}
@@ -0,0 +1,199 @@
+//! Discovery of a grid's services through its `get_grid_info` document.
+//!
+//! OpenSimulator grids publish the URIs of their services at
+//! `<grid uri>/get_grid_info`, so knowing the address of a grid is enough to
+//! find its login server and the other services.
+
+use futures::prelude::{await, *};
+use hyper;
+use std::collections::HashMap;
+use url::Url;
+use util::http;
+use xml::reader::{EventReader, XmlEvent};
+
+#[derive(Debug, Fail)]
+pub enum GridInfoError {
+    #[fail(display = "Request for the grid info failed: {}", 0)]
+    Request(String),
+
+    #[fail(display = "The grid returned an error status: {}", 0)]
+    Status(hyper::StatusCode),
+
+    #[fail(display = "Invalid grid info document: {}", 0)]
+    Invalid(String),
+}
+
+/// The services of a grid.
+///
+/// Only `login` is mandatory, grids publish the other services as they see
+/// fit.
+#[derive(Clone, Debug)]
+pub struct GridInfo {
+    /// The URI of the login server, to be used with `login::login`.
+    pub login: Url,
+    pub grid_name: Option<String>,
+    pub grid_nick: Option<String>,
+
+    /// Page shown by viewers on the login screen.
+    pub welcome: Option<Url>,
+    pub economy: Option<Url>,
+    pub about: Option<Url>,
+    pub register: Option<Url>,
+    /// Page for resetting a forgotten password.
+    pub password: Option<Url>,
+    pub map_server: Option<Url>,
+    pub search: Option<Url>,
+    pub profile: Option<Url>,
+    /// The gatekeeper service of the grid, entry point for hypergrid visitors.
+    pub gatekeeper: Option<Url>,
+
+    /// All entries of the document by name, including the ones without a
+    /// field above.
+    pub entries: HashMap<String, String>,
+}
+
+impl GridInfo {
+    /// Parse a `get_grid_info` document.
+    pub fn parse(document: &str) -> Result<GridInfo, GridInfoError> {
+        // The document is a flat list of elements below <gridinfo>.
+        let mut entries = HashMap::new();
+        let mut depth = 0;
+        let mut entry: Option<(String, String)> = None;
+        for event in EventReader::from_str(document) {
+            match event.map_err(|e| GridInfoError::Invalid(format!("{}", e)))? {
+                XmlEvent::StartElement { name, .. } => {
+                    depth += 1;
+                    if depth == 1 && name.local_name != "gridinfo" {
+                        return Err(GridInfoError::Invalid("missing <gridinfo>".to_string()));
+                    } else if depth == 2 {
+                        entry = Some((name.local_name, String::new()));
+                    }
+                }
+                XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                    if let Some((_, ref mut value)) = entry {
+                        if depth == 2 {
+                            value.push_str(&text);
+                        }
+                    }
+                }
+                XmlEvent::EndElement { .. } => {
+                    if depth == 2 {
+                        if let Some((name, value)) = entry.take() {
+                            entries.insert(name, value.trim().to_string());
+                        }
+                    }
+                    depth -= 1;
+                }
+                _ => {}
+            }
+        }
+
+        let login = match entries.get("login") {
+            Some(login) => Url::parse(login)
+                .map_err(|e| GridInfoError::Invalid(format!("invalid login URI: {}", e)))?,
+            None => return Err(GridInfoError::Invalid("missing <login>".to_string())),
+        };
+        let text = |name: &str| {
+            entries
+                .get(name)
+                .filter(|v| !v.is_empty())
+                .map(|v| v.to_string())
+        };
+        // Invalid or empty URLs are treated as absent, grids tend to leave the
+        // defaults of the config file in place.
+        let url = |names: &[&str]| {
+            names
+                .iter()
+                .filter_map(|name| entries.get(*name))
+                .filter_map(|v| Url::parse(v).ok())
+                .next()
+        };
+
+        Ok(GridInfo {
+            login: login,
+            grid_name: text("gridname"),
+            grid_nick: text("gridnick"),
+            welcome: url(&["welcome"]),
+            economy: url(&["economy"]),
+            about: url(&["about"]),
+            register: url(&["register"]),
+            password: url(&["password"]),
+            map_server: url(&["MapServerURI", "mapserver", "map"]),
+            search: url(&["search"]),
+            profile: url(&["profile"]),
+            gatekeeper: url(&["gatekeeper"]),
+            entries: entries.clone(),
+        })
+    }
+}
+
+/// Fetch the grid info of the grid at `grid_uri`, i.e.
+/// `http://grid.example.org:8002/`.
+#[async]
+pub fn get_grid_info(grid_uri: hyper::Uri) -> Result<GridInfo, GridInfoError> {
+    let uri: hyper::Uri = format!(
+        "{}/get_grid_info",
+        grid_uri.to_string().trim_end_matches('/')
+    )
+    .parse()
+    .map_err(|e| GridInfoError::Request(format!("{}", e)))?;
+
+    let client = http::client().map_err(|e| GridInfoError::Request(format!("{}", e)))?;
+    let response = await!(client.get(uri)).map_err(|e| GridInfoError::Request(format!("{}", e)))?;
+    if !response.status().is_success() {
+        return Err(GridInfoError::Status(response.status()));
+    }
+
+    let raw_data = await!(response
+        .into_body()
+        .concat2()
+        .map_err(|e| GridInfoError::Request(format!("{}", e))))?;
+    let document = String::from_utf8(raw_data.to_vec())
+        .map_err(|_| GridInfoError::Invalid("not UTF-8".to_string()))?;
+    GridInfo::parse(&document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_grid_info() {
+        let document = "<?xml version=\"1.0\"?>\n<gridinfo>\n\
+                        <login>http://grid.example.org:8002/</login>\n\
+                        <gridname>Example Grid</gridname>\n\
+                        <gridnick>example</gridnick>\n\
+                        <welcome>http://grid.example.org/welcome?a=1&amp;b=2</welcome>\n\
+                        <economy></economy>\n\
+                        <gatekeeper>http://grid.example.org:8002/</gatekeeper>\n\
+                        <platform>OpenSim</platform>\n\
+                        </gridinfo>";
+        let info = GridInfo::parse(document).unwrap();
+        assert_eq!(info.login.as_str(), "http://grid.example.org:8002/");
+        assert_eq!(info.grid_name, Some("Example Grid".to_string()));
+        assert_eq!(info.grid_nick, Some("example".to_string()));
+        assert_eq!(
+            info.welcome.unwrap().as_str(),
+            "http://grid.example.org/welcome?a=1&b=2"
+        );
+        assert!(info.economy.is_none());
+        assert!(info.gatekeeper.is_some());
+        assert_eq!(info.entries["platform"], "OpenSim");
+    }
+
+    #[test]
+    fn root_with_attributes() {
+        let login = "<login>http://grid.example.org:8002/</login>";
+        for root in &["<gridinfo >", "<gridinfo\n>", "<gridinfo xmlns=\"urn:example\">"] {
+            let document = format!("{}{}</gridinfo >", root, login);
+            assert!(GridInfo::parse(&document).is_ok(), "{}", document);
+        }
+        assert!(GridInfo::parse(&format!("<gridinfos>{}</gridinfos>", login)).is_err());
+    }
+
+    #[test]
+    fn missing_login() {
+        assert!(GridInfo::parse("<gridinfo><gridname>x</gridname></gridinfo>").is_err());
+        assert!(GridInfo::parse("<html></html>").is_err());
+    }
+}
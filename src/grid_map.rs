@@ -2,7 +2,7 @@ use messages::all::MapItemReply;
 use types::Uuid;
 
 pub mod region_handle {
-    #[derive(Clone, Debug, Eq, PartialEq)]
+    #[derive(Clone, Debug, Eq, Hash, PartialEq)]
     pub struct RegionHandle {
         x: u32,
         y: u32,
@@ -43,7 +43,6 @@ pub mod region_handle {
             self.handle
         }
     }
-
 }
 
 // TODO: Move this macro to a better place, and include an example
@@ -0,0 +1,259 @@
+//! Client for the gatekeeper service of foreign grids.
+//!
+//! Hypergrid allows agents to visit regions of other grids. A region of a
+//! foreign grid is addressed as `grid.example.org:8002:Region Name`, where
+//! host and port are the ones of the foreign grid's gatekeeper, which can be
+//! asked with `link_region` for the id and handle of the region.
+//!
+//! The handle returned by the gatekeeper is only meaningful to the foreign
+//! grid. To teleport there the current sim has to create its own hyperlink
+//! to the region, it is asked to with a `MapNameRequest` for the
+//! `hyperlink_name` and replies with the local handle of the hyperlink, see
+//! `Simulator::teleport_hypergrid`. The circuit information of the
+//! destination arrives with `TeleportFinish`.
+
+use futures::prelude::{await, *};
+use grid_map::region_handle::RegionHandle;
+use hyper;
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use login::rpc::{self, Value as XmlValue};
+use login::{Fault, Gatekeeper, RegionAddress};
+use messages::all::MapBlockReply;
+use std::collections::BTreeMap;
+use types::Uuid;
+use util::http;
+
+#[derive(Debug, Fail)]
+pub enum HypergridError {
+    #[fail(display = "Request to the gatekeeper failed: {}", 0)]
+    Request(String),
+
+    #[fail(display = "The gatekeeper returned an error status: {}", 0)]
+    Status(hyper::StatusCode),
+
+    #[fail(display = "Invalid response from the gatekeeper: {}", 0)]
+    InvalidResponse(String),
+
+    #[fail(display = "The gatekeeper returned a fault: {}", 0)]
+    Fault(Fault),
+
+    #[fail(display = "The gatekeeper refused the request: {}", 0)]
+    Refused(String),
+
+    #[fail(display = "Not a hypergrid address: {}", 0)]
+    NotHypergrid(RegionAddress),
+
+    #[fail(display = "The sim did not link the region: {}", 0)]
+    NotLinked(RegionAddress),
+}
+
+/// Access level of the block terminating a `MapBlockReply`.
+const SIM_ACCESS_NONEXISTENT: u8 = 255;
+
+/// A region of a foreign grid, as returned by `link_region`.
+#[derive(Clone, Debug)]
+pub struct LinkedRegion {
+    pub region_id: Uuid,
+    pub region_handle: RegionHandle,
+    /// Name of the region for display, including the grid.
+    pub external_name: String,
+    pub image_url: Option<String>,
+}
+
+/// The name to look up with `MapNameRequest` to make the sim link a region
+/// of a foreign grid, `host:port Region Name`.
+pub fn hyperlink_name(gatekeeper: &Gatekeeper, region: &str) -> String {
+    format!("{}:{} {}", gatekeeper.host, gatekeeper.port, region)
+        .trim_end()
+        .to_string()
+}
+
+/// Find the hyperlink in the sim's reply to a `MapNameRequest` for `name`.
+///
+/// Sims terminate the results with a block named like the request, which
+/// has no position. Returns `None` if the reply belongs to another request,
+/// and `Some(None)` if the sim did not link the region.
+pub fn find_hyperlink(reply: &MapBlockReply, name: &str) -> Option<Option<RegionHandle>> {
+    let mut answered = false;
+    for block in &reply.data {
+        let block_name = String::from_utf8_lossy(&block.name);
+        if !block_name.trim_end_matches('\0').eq_ignore_ascii_case(name) {
+            continue;
+        }
+        answered = true;
+        if block.access != SIM_ACCESS_NONEXISTENT && (block.x, block.y) != (0, 0) {
+            // The position is given in regions.
+            let x = u32::from(block.x) * 256;
+            let y = u32::from(block.y) * 256;
+            return Some(Some(RegionHandle::from_xy(x, y)));
+        }
+    }
+    if answered {
+        Some(None)
+    } else {
+        None
+    }
+}
+
+/// Look up a region of the foreign grid by name.
+///
+/// An empty name selects the default region of the grid.
+#[async]
+pub fn link_region(
+    gatekeeper: Gatekeeper,
+    region_name: String,
+) -> Result<LinkedRegion, HypergridError> {
+    let mut params = BTreeMap::new();
+    params.insert("region_name".to_string(), XmlValue::String(region_name));
+    let response = await!(call(gatekeeper, "link_region", params))?;
+
+    let handle: u64 = get_string(&response, "handle")?
+        .parse()
+        .map_err(|_| invalid("handle"))?;
+    Ok(LinkedRegion {
+        region_id: get_uuid(&response, "uuid")?,
+        region_handle: RegionHandle::from_handle(handle),
+        external_name: get_string(&response, "external_name")
+            .unwrap_or("")
+            .to_string(),
+        image_url: get_string(&response, "region_image")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string()),
+    })
+}
+
+/// Perform a XML-RPC call and return the response struct, if the gatekeeper
+/// indicated success.
+#[async]
+fn call(
+    gatekeeper: Gatekeeper,
+    method: &'static str,
+    params: BTreeMap<String, XmlValue>,
+) -> Result<BTreeMap<String, XmlValue>, HypergridError> {
+    let uri: hyper::Uri = gatekeeper
+        .uri()
+        .parse()
+        .map_err(|e| HypergridError::Request(format!("{}", e)))?;
+    let body = rpc::method_call(method, &[XmlValue::Struct(params)]);
+
+    let client = http::client().map_err(|e| HypergridError::Request(format!("{}", e)))?;
+    let request = hyper::Request::post(uri)
+        .header(CONTENT_TYPE, "text/xml")
+        .header(CONTENT_LENGTH, body.len())
+        .body(hyper::Body::from(body))
+        .map_err(|e| HypergridError::Request(format!("{}", e)))?;
+    let response =
+        await!(client.request(request)).map_err(|e| HypergridError::Request(format!("{}", e)))?;
+    if !response.status().is_success() {
+        return Err(HypergridError::Status(response.status()));
+    }
+
+    let raw_data = await!(response
+        .into_body()
+        .concat2()
+        .map_err(|e| HypergridError::Request(format!("{}", e))))?;
+    let text = String::from_utf8(raw_data.to_vec())
+        .map_err(|_| HypergridError::InvalidResponse("not UTF-8".to_string()))?;
+    let value = rpc::parse_response(&text)
+        .map_err(|e| HypergridError::InvalidResponse(format!("{}", e)))?
+        .map_err(HypergridError::Fault)?;
+
+    let response = match value {
+        XmlValue::Struct(s) => s,
+        _ => return Err(HypergridError::InvalidResponse("not a struct".to_string())),
+    };
+    // The gatekeeper answers with the strings "True" and "False".
+    match response.get("result") {
+        Some(&XmlValue::String(ref result)) if result.eq_ignore_ascii_case("true") => Ok(response),
+        Some(&XmlValue::Bool(true)) => Ok(response),
+        _ => Err(HypergridError::Refused(
+            get_string(&response, "message")
+                .unwrap_or("no reason given")
+                .to_string(),
+        )),
+    }
+}
+
+fn invalid(field: &str) -> HypergridError {
+    HypergridError::InvalidResponse(format!("missing or invalid field: {}", field))
+}
+
+fn get_string<'a>(
+    response: &'a BTreeMap<String, XmlValue>,
+    key: &str,
+) -> Result<&'a str, HypergridError> {
+    match response.get(key) {
+        Some(&XmlValue::String(ref s)) => Ok(s),
+        _ => Err(invalid(key)),
+    }
+}
+
+fn get_uuid(response: &BTreeMap<String, XmlValue>, key: &str) -> Result<Uuid, HypergridError> {
+    Uuid::parse_str(get_string(response, key)?).map_err(|_| invalid(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use messages::all::{MapBlockReply_AgentData, MapBlockReply_Data};
+
+    fn block(name: &str, x: u16, y: u16, access: u8) -> MapBlockReply_Data {
+        MapBlockReply_Data {
+            x: x,
+            y: y,
+            name: name.as_bytes().to_vec(),
+            access: access,
+            region_flags: 0,
+            water_height: 0,
+            agents: 0,
+            map_image_id: Uuid::nil(),
+        }
+    }
+
+    fn reply(data: Vec<MapBlockReply_Data>) -> MapBlockReply {
+        MapBlockReply {
+            agent_data: MapBlockReply_AgentData {
+                agent_id: Uuid::nil(),
+                flags: 2,
+            },
+            data: data,
+        }
+    }
+
+    #[test]
+    fn find_hyperlink_in_reply() {
+        let gatekeeper = Gatekeeper {
+            host: "grid.example.org".to_string(),
+            port: 8002,
+        };
+        let name = hyperlink_name(&gatekeeper, "Welcome");
+        assert_eq!(name, "grid.example.org:8002 Welcome");
+        assert_eq!(hyperlink_name(&gatekeeper, ""), "grid.example.org:8002");
+
+        let linked = reply(vec![
+            block("grid.example.org:8002 welcome", 4000, 4001, 13),
+            block(
+                "grid.example.org:8002 Welcome",
+                0,
+                0,
+                SIM_ACCESS_NONEXISTENT,
+            ),
+        ]);
+        assert_eq!(
+            find_hyperlink(&linked, &name),
+            Some(Some(RegionHandle::from_xy(4000 * 256, 4001 * 256)))
+        );
+
+        let not_linked = reply(vec![block(
+            "grid.example.org:8002 Welcome",
+            0,
+            0,
+            SIM_ACCESS_NONEXISTENT,
+        )]);
+        assert_eq!(find_hyperlink(&not_linked, &name), Some(None));
+
+        let other = reply(vec![block("Other", 1000, 1000, 13)]);
+        assert_eq!(find_hyperlink(&other, &name), None);
+    }
+}
@@ -16,13 +16,13 @@ extern crate failure;
 extern crate futures_await as futures;
 extern crate futures_cpupool;
 extern crate hyper;
+extern crate hyper_tls;
 extern crate image;
 extern crate jpeg2000;
 #[macro_use]
 extern crate lazy_static;
 extern crate llsd;
 extern crate regex;
-extern crate reqwest;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
@@ -33,6 +33,7 @@ extern crate slog_async;
 extern crate slog_term;
 extern crate tokio_core;
 extern crate url;
+extern crate xml;
 extern crate xmlrpc;
 
 pub extern crate opensim_messages as messages;
@@ -43,9 +44,13 @@ pub mod circuit;
 /// experimental (TODO)
 pub mod coordinates;
 pub mod data;
+pub mod event_queue;
+pub mod grid_info;
+pub mod hypergrid;
 pub mod layer_data;
 pub mod logging;
 pub mod login;
+pub mod neighbours;
 pub mod packet;
 pub mod services;
 pub mod simulator;
@@ -58,3 +63,6 @@ pub mod grid_map;
 
 /// experimental
 pub mod object_update;
+
+#[cfg(feature = "mock-sim")]
+pub mod mock_sim;
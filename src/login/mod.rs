@@ -0,0 +1,575 @@
+//! Logging in to a grid.
+//!
+//! The login server authenticates the agent and tells it which sim to connect
+//! to, the returned `LoginResponse` can be passed to `Simulator::connect`.
+
+use self::rpc::Value as XmlValue;
+use crypto::digest::Digest;
+use crypto::md5::Md5;
+use failure::Error;
+use futures::prelude::{await, *};
+use grid_map::region_handle::RegionHandle;
+use hyper;
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use types::{Ip4Addr, Uuid, Vector3};
+use url::Url;
+use util::http;
+
+pub(crate) mod rpc;
+pub use self::rpc::Fault;
+
+mod start_location;
+pub use self::start_location::{Gatekeeper, RegionAddress, StartLocation, StartLocationError};
+
+/// Performing a LoginRequest is the first step at gaining access to a sim.
+#[derive(Debug)]
+pub struct LoginRequest {
+    /// User first name
+    pub first_name: String,
+    /// User last name
+    pub last_name: String,
+    /// Hashed password
+    pub password_hash: String,
+    /// Start location
+    pub start: StartLocation,
+    /// Additional data requested from the login server, i.e.
+    /// "inventory-root" or "buddy-list".
+    pub options: Vec<String>,
+}
+
+impl LoginRequest {
+    /// The options for which `LoginResponse` has fields.
+    pub fn default_options() -> Vec<String> {
+        vec![
+            "inventory-root".to_string(),
+            "inventory-skeleton".to_string(),
+            "buddy-list".to_string(),
+            "login-flags".to_string(),
+        ]
+    }
+
+    fn to_xmlrpc(&self, method: &str) -> String {
+        let mut data: BTreeMap<String, XmlValue> = BTreeMap::new();
+        data.insert("first".to_string(), XmlValue::from(&self.first_name[..]));
+        data.insert("last".to_string(), XmlValue::from(&self.last_name[..]));
+        data.insert(
+            "passwd".to_string(),
+            XmlValue::from(&self.password_hash[..]),
+        );
+        data.insert(
+            "start".to_string(),
+            XmlValue::String(self.start.to_string()),
+        );
+        data.insert("version".to_string(), XmlValue::from("0.1.0"));
+        data.insert("channel".to_string(), XmlValue::from("tokio-opensim"));
+        data.insert("platform".to_string(), XmlValue::from("Linux"));
+        data.insert(
+            "options".to_string(),
+            XmlValue::Array(
+                self.options
+                    .iter()
+                    .map(|o| XmlValue::from(&o[..]))
+                    .collect(),
+            ),
+        );
+
+        rpc::method_call(method, &[XmlValue::Struct(data)])
+    }
+}
+
+/// Hash a plain text password into the format required for login.
+pub fn hash_password(password_raw: &str) -> String {
+    // Hash the password.
+    let mut digest = Md5::new();
+    digest.input_str(password_raw);
+
+    // Bring into required format.
+    "$1$".to_string() + &digest.result_str()
+}
+
+#[derive(Debug, Fail)]
+pub enum LoginError {
+    #[fail(display = "There was a network error: {}", 0)]
+    Network(Error),
+
+    #[fail(display = "Parsing the response failed: {}", 0)]
+    ParseResponse(Error),
+
+    #[fail(display = "Login failure: {}", message)]
+    Explicit {
+        reason: LoginFailure,
+        /// The explanation of the server, intended to be shown to the user.
+        ///
+        /// Empty if the server only gave a reason.
+        message: String,
+    },
+
+    #[fail(display = "Login server redirected more than {} times.", 0)]
+    TooManyRedirects(usize),
+
+    #[fail(display = "Login failed due to server denying it: {}", 0)]
+    LoginDenied(Fault),
+}
+
+/// The reason of a failed login, as indicated by the server.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LoginFailure {
+    /// The agent is already logged in, i.e. because the previous session was
+    /// not logged out yet. Retrying after a while usually succeeds.
+    Presence,
+    /// Unknown agent or wrong password.
+    Key,
+    /// The login server requires a newer client.
+    Update,
+    /// The agent has to accept the terms of service (i.e. on the website).
+    Tos,
+    /// The account is disabled or banned.
+    Disabled,
+    /// The login server could not decide and wants the request to be repeated
+    /// against `next_url` with the XML-RPC method `next_method`.
+    ///
+    /// `login` follows these redirects itself.
+    Indeterminate {
+        next_url: String,
+        next_method: String,
+    },
+    /// Any other reason reported by the server.
+    Other(String),
+}
+
+impl LoginFailure {
+    fn extract(response: &BTreeMap<String, XmlValue>) -> LoginFailure {
+        match get_string(response, "reason").unwrap_or("") {
+            "presence" => LoginFailure::Presence,
+            "key" => LoginFailure::Key,
+            "update" => LoginFailure::Update,
+            "tos" => LoginFailure::Tos,
+            "disabled" => LoginFailure::Disabled,
+            "indeterminate" => match (
+                get_string(response, "next_url"),
+                get_string(response, "next_method"),
+            ) {
+                (Some(url), Some(method)) => LoginFailure::Indeterminate {
+                    next_url: url.to_string(),
+                    next_method: method.to_string(),
+                },
+                _ => LoginFailure::Other("indeterminate".to_string()),
+            },
+            reason => LoginFailure::Other(reason.to_string()),
+        }
+    }
+}
+
+/// The home location of the agent.
+#[derive(Clone, Debug)]
+pub struct Home {
+    pub region_handle: RegionHandle,
+    pub position: Vector3<f32>,
+    pub look_at: Vector3<f32>,
+}
+
+/// A folder of the inventory skeleton.
+#[derive(Clone, Debug)]
+pub struct InventoryFolder {
+    pub folder_id: Uuid,
+    pub parent_id: Uuid,
+    pub name: String,
+    pub type_default: i32,
+    pub version: i32,
+}
+
+/// An entry of the buddy list.
+#[derive(Clone, Debug)]
+pub struct Buddy {
+    pub buddy_id: Uuid,
+    /// Rights granted to the buddy.
+    pub rights_given: i32,
+    /// Rights granted by the buddy.
+    pub rights_has: i32,
+}
+
+#[derive(Clone, Debug)]
+pub struct LoginResponse {
+    pub look_at: Vector3<f32>,
+    pub circuit_code: u32,
+    pub session_id: Uuid,
+    pub agent_id: Uuid,
+    pub first_name: String,
+    pub last_name: String,
+
+    /// The URL where capabilities can be queried.
+    pub seed_capability: Url,
+
+    /// The IP address of the simulator to connect to.
+    pub sim_ip: Ip4Addr,
+    /// The port of the simulator to connect to.
+    pub sim_port: u16,
+    /// The global position of the simulator's region in meters.
+    pub region_x: u32,
+    pub region_y: u32,
+
+    /// The maturity rating the agent has access to ("PG", "M" or "A").
+    pub agent_access: Option<String>,
+    /// The server time at login.
+    pub seconds_since_epoch: Option<u32>,
+    /// The message of the day.
+    pub message: Option<String>,
+    pub home: Option<Home>,
+
+    /// Only present if the "inventory-root" option was requested.
+    pub inventory_root: Option<Uuid>,
+    /// Only filled if the "inventory-skeleton" option was requested.
+    pub inventory_skeleton: Vec<InventoryFolder>,
+    /// Only filled if the "buddy-list" option was requested.
+    pub buddy_list: Vec<Buddy>,
+}
+
+fn missing(field: &str) -> LoginError {
+    LoginError::ParseResponse(format_err!("Missing response field: {}", field))
+}
+
+fn get_string<'a>(map: &'a BTreeMap<String, XmlValue>, key: &str) -> Option<&'a str> {
+    match map.get(key) {
+        Some(&XmlValue::String(ref s)) => Some(s),
+        _ => None,
+    }
+}
+
+fn get_int(map: &BTreeMap<String, XmlValue>, key: &str) -> Option<i32> {
+    match map.get(key) {
+        Some(&XmlValue::Int(i)) => Some(i),
+        // Some servers send numbers as strings.
+        Some(&XmlValue::String(ref s)) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn get_uuid(map: &BTreeMap<String, XmlValue>, key: &str) -> Result<Uuid, LoginError> {
+    let raw = get_string(map, key).ok_or_else(|| missing(key))?;
+    Uuid::parse_str(raw).map_err(|e| LoginError::ParseResponse(e.into()))
+}
+
+/// Returns the structs of an array of structs.
+fn get_structs<'a>(
+    map: &'a BTreeMap<String, XmlValue>,
+    key: &str,
+) -> Vec<&'a BTreeMap<String, XmlValue>> {
+    match map.get(key) {
+        Some(&XmlValue::Array(ref values)) => values
+            .iter()
+            .filter_map(|v| match *v {
+                XmlValue::Struct(ref s) => Some(s),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+impl LoginResponse {
+    fn extract_vector3(raw: &str) -> Result<Vector3<f32>, LoginError> {
+        let re = Regex::new(r"\[r([0-9\.-]+),r([0-9\.-]+),r([0-9\.-]+)\]").unwrap();
+        match re.captures(raw) {
+            Some(caps) => {
+                let x = f32::from_str(&caps[1]).map_err(|e| LoginError::ParseResponse(e.into()))?;
+                let y = f32::from_str(&caps[2]).map_err(|e| LoginError::ParseResponse(e.into()))?;
+                let z = f32::from_str(&caps[3]).map_err(|e| LoginError::ParseResponse(e.into()))?;
+                Ok(Vector3::new(x, y, z))
+            }
+            _ => Err(LoginError::ParseResponse(format_err!(
+                "Invalid vector3: '{}'",
+                raw
+            ))),
+        }
+    }
+
+    /// Parses the LLSD notation string of the "home" field, i.e.
+    /// `{'region_handle':[r256000,r256000], 'position':[r128,r128,r21], 'look_at':[r0,r1,r0]}`.
+    fn extract_home(raw: &str) -> Result<Home, LoginError> {
+        let invalid = || LoginError::ParseResponse(format_err!("Invalid home: '{}'", raw));
+        let re = Regex::new(
+            r"'region_handle':\s*\[r([0-9\.]+),\s*r([0-9\.]+)\].*'position':\s*(\[[^\]]*\]).*'look_at':\s*(\[[^\]]*\])",
+        )
+        .unwrap();
+        let caps = re.captures(raw).ok_or_else(&invalid)?;
+        let x = f32::from_str(&caps[1]).map_err(|_| invalid())?;
+        let y = f32::from_str(&caps[2]).map_err(|_| invalid())?;
+
+        Ok(Home {
+            region_handle: RegionHandle::from_xy(x as u32, y as u32),
+            position: LoginResponse::extract_vector3(&caps[3])?,
+            look_at: LoginResponse::extract_vector3(&caps[4])?,
+        })
+    }
+
+    fn extract(response: BTreeMap<String, XmlValue>) -> Result<LoginResponse, LoginError> {
+        // Check if the login was a success.
+        let failure = get_string(&response, "login") != Some("true");
+
+        if failure {
+            // Redirects usually come without a message.
+            let message = get_string(&response, "message");
+            if message.is_none() && get_string(&response, "reason").is_none() {
+                return Err(LoginError::ParseResponse(format_err!(
+                    "Server indicated error but did not explain it."
+                )));
+            }
+            Err(LoginError::Explicit {
+                reason: LoginFailure::extract(&response),
+                message: message.unwrap_or("").to_string(),
+            })
+        } else {
+            let look_at = get_string(&response, "look_at").ok_or_else(|| missing("look_at"))?;
+            let seed_capability = get_string(&response, "seed_capability")
+                .ok_or_else(|| missing("seed_capability"))?;
+            let sim_ip = get_string(&response, "sim_ip").ok_or_else(|| missing("sim_ip"))?;
+            let home = match get_string(&response, "home") {
+                Some(raw) => Some(LoginResponse::extract_home(raw)?),
+                None => None,
+            };
+
+            let inventory_root = match get_structs(&response, "inventory-root").first() {
+                Some(folder) => Some(get_uuid(folder, "folder_id")?),
+                None => None,
+            };
+            let mut inventory_skeleton = Vec::new();
+            for folder in get_structs(&response, "inventory-skeleton") {
+                inventory_skeleton.push(InventoryFolder {
+                    folder_id: get_uuid(folder, "folder_id")?,
+                    parent_id: get_uuid(folder, "parent_id")?,
+                    name: get_string(folder, "name").unwrap_or("").to_string(),
+                    type_default: get_int(folder, "type_default").unwrap_or(-1),
+                    version: get_int(folder, "version").unwrap_or(0),
+                });
+            }
+            let mut buddy_list = Vec::new();
+            for buddy in get_structs(&response, "buddy-list") {
+                buddy_list.push(Buddy {
+                    buddy_id: get_uuid(buddy, "buddy_id")?,
+                    rights_given: get_int(buddy, "buddy_rights_given").unwrap_or(0),
+                    rights_has: get_int(buddy, "buddy_rights_has").unwrap_or(0),
+                });
+            }
+
+            Ok(LoginResponse {
+                look_at: LoginResponse::extract_vector3(look_at)?,
+                circuit_code: get_int(&response, "circuit_code")
+                    .ok_or_else(|| missing("circuit_code"))? as u32,
+                session_id: get_uuid(&response, "session_id")?,
+                agent_id: get_uuid(&response, "agent_id")?,
+                // OpenSim puts the first name in quotes.
+                first_name: get_string(&response, "first_name")
+                    .ok_or_else(|| missing("first_name"))?
+                    .trim_matches('"')
+                    .to_string(),
+                last_name: get_string(&response, "last_name")
+                    .ok_or_else(|| missing("last_name"))?
+                    .trim_matches('"')
+                    .to_string(),
+                seed_capability: Url::parse(seed_capability)
+                    .map_err(|e| LoginError::ParseResponse(e.into()))?,
+                sim_ip: Ip4Addr::from_str(sim_ip)
+                    .map_err(|e| LoginError::ParseResponse(e.into()))?,
+                sim_port: get_int(&response, "sim_port").ok_or_else(|| missing("sim_port"))? as u16,
+                region_x: get_int(&response, "region_x").ok_or_else(|| missing("region_x"))? as u32,
+                region_y: get_int(&response, "region_y").ok_or_else(|| missing("region_y"))? as u32,
+                agent_access: get_string(&response, "agent_access").map(|s| s.to_string()),
+                seconds_since_epoch: get_int(&response, "seconds_since_epoch").map(|s| s as u32),
+                message: get_string(&response, "message").map(|s| s.to_string()),
+                home: home,
+                inventory_root: inventory_root,
+                inventory_skeleton: inventory_skeleton,
+                buddy_list: buddy_list,
+            })
+        }
+    }
+}
+
+/// The maximum number of indeterminate login redirects which are followed.
+const MAX_REDIRECTS: usize = 5;
+
+/// Perform the login request against the login server at `uri`.
+///
+/// Indeterminate responses are followed to the login server they redirect to.
+///
+/// Hypergrid start locations are resolved by the login server, which hands
+/// the agent over to the foreign grid's gatekeeper. The returned
+/// `LoginResponse` then describes the sim of the foreign grid.
+#[async]
+pub fn login(request: LoginRequest, uri: hyper::Uri) -> Result<LoginResponse, LoginError> {
+    let mut uri = uri;
+    let mut method = "login_to_simulator".to_string();
+    let mut redirects = 0;
+
+    loop {
+        let body = request.to_xmlrpc(&method);
+        match await!(login_call(body, uri.clone())) {
+            Err(LoginError::Explicit {
+                reason:
+                    LoginFailure::Indeterminate {
+                        next_url,
+                        next_method,
+                    },
+                ..
+            }) => {
+                if redirects == MAX_REDIRECTS {
+                    return Err(LoginError::TooManyRedirects(redirects));
+                }
+                redirects += 1;
+                uri = next_url.parse().map_err(|e| {
+                    LoginError::ParseResponse(format_err!("Invalid next_url '{}': {}", next_url, e))
+                })?;
+                method = next_method;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[async]
+fn login_call(body: String, uri: hyper::Uri) -> Result<LoginResponse, LoginError> {
+    let client = http::client().map_err(|e| LoginError::Network(e.into()))?;
+    let http_request = hyper::Request::post(uri)
+        .header(CONTENT_TYPE, "text/xml")
+        .header(CONTENT_LENGTH, body.len())
+        .body(hyper::Body::from(body))
+        .map_err(|e| LoginError::Network(e.into()))?;
+    let response =
+        await!(client.request(http_request)).map_err(|e| LoginError::Network(e.into()))?;
+    if !response.status().is_success() {
+        return Err(LoginError::Network(format_err!(
+            "Login server returned status: {}",
+            response.status()
+        )));
+    }
+
+    let raw_data =
+        await!(response.into_body().concat2()).map_err(|e| LoginError::Network(e.into()))?;
+    let text =
+        String::from_utf8(raw_data.to_vec()).map_err(|e| LoginError::ParseResponse(e.into()))?;
+    let value = rpc::parse_response(&text)
+        .map_err(|e| LoginError::ParseResponse(e.into()))?
+        .map_err(LoginError::LoginDenied)?;
+
+    match value {
+        XmlValue::Struct(s) => LoginResponse::extract(s),
+        _ => Err(LoginError::ParseResponse(format_err!(
+            "value is not a struct"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_vector3() {
+        // Test correct behavior.
+        let result = LoginResponse::extract_vector3("[r0.171732,r0.9851437,r0]").unwrap();
+        let eps = 0.00001;
+        assert!((result.x - 0.171732).abs() < eps);
+        assert!((result.y - 0.9851437).abs() < eps);
+        assert!((result.z - 0.).abs() < eps);
+
+        // Test graceful failure.
+        assert!(LoginResponse::extract_vector3("Lorem ipsum").is_err());
+    }
+
+    #[test]
+    fn extract_home() {
+        let home = LoginResponse::extract_home(
+            "{'region_handle':[r256000,r257024], 'position':[r128,r64.5,r21], 'look_at':[r0,r1,r0]}",
+        )
+        .unwrap();
+        assert_eq!(home.region_handle.xy(), (256000, 257024));
+        assert_eq!(home.position, Vector3::new(128., 64.5, 21.));
+        assert_eq!(home.look_at, Vector3::new(0., 1., 0.));
+    }
+
+    #[test]
+    fn extract_failure() {
+        let mut response = BTreeMap::new();
+        response.insert("login".to_string(), XmlValue::from("false"));
+        response.insert("reason".to_string(), XmlValue::from("presence"));
+        response.insert(
+            "message".to_string(),
+            XmlValue::from("You appear to be already logged in."),
+        );
+        match LoginResponse::extract(response.clone()) {
+            Err(LoginError::Explicit { reason, message }) => {
+                assert_eq!(reason, LoginFailure::Presence);
+                assert_eq!(message, "You appear to be already logged in.");
+            }
+            r => panic!("unexpected result: {:?}", r),
+        }
+
+        response.insert("reason".to_string(), XmlValue::from("indeterminate"));
+        response.insert(
+            "next_url".to_string(),
+            XmlValue::from("http://login2.example.com/"),
+        );
+        response.insert("next_method".to_string(), XmlValue::from("login_2"));
+        match LoginResponse::extract(response) {
+            Err(LoginError::Explicit { reason, .. }) => assert_eq!(
+                reason,
+                LoginFailure::Indeterminate {
+                    next_url: "http://login2.example.com/".to_string(),
+                    next_method: "login_2".to_string(),
+                }
+            ),
+            r => panic!("unexpected result: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn extract_redirect_without_message() {
+        let mut response = BTreeMap::new();
+        response.insert("login".to_string(), XmlValue::from("indeterminate"));
+        response.insert("reason".to_string(), XmlValue::from("indeterminate"));
+        response.insert(
+            "next_url".to_string(),
+            XmlValue::from("http://login2.example.com/"),
+        );
+        response.insert("next_method".to_string(), XmlValue::from("login_2"));
+        match LoginResponse::extract(response) {
+            Err(LoginError::Explicit { reason, message }) => {
+                assert_eq!(
+                    reason,
+                    LoginFailure::Indeterminate {
+                        next_url: "http://login2.example.com/".to_string(),
+                        next_method: "login_2".to_string(),
+                    }
+                );
+                assert_eq!(message, "");
+            }
+            r => panic!("unexpected result: {:?}", r),
+        }
+
+        // Without a reason there is nothing to go by.
+        let mut response = BTreeMap::new();
+        response.insert("login".to_string(), XmlValue::from("false"));
+        match LoginResponse::extract(response) {
+            Err(LoginError::ParseResponse(_)) => {}
+            r => panic!("unexpected result: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn request_contains_options() {
+        let request = LoginRequest {
+            first_name: "Test".to_string(),
+            last_name: "User".to_string(),
+            password_hash: hash_password("password"),
+            start: StartLocation::Last,
+            options: vec!["inventory-root".to_string()],
+        };
+        let body = request.to_xmlrpc("login_to_simulator");
+        assert!(body.contains(
+            "<name>options</name><value><array><data><value><string>inventory-root</string></value></data></array></value>"
+        ));
+    }
+}
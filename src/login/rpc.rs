@@ -0,0 +1,101 @@
+//! XML-RPC encoding for the login and gatekeeper protocols.
+//!
+//! The values are encoded and decoded by the `xmlrpc` crate, but as it only
+//! performs blocking requests the calls are sent with our own HTTP client.
+
+use std::error::Error;
+use xmlrpc::{Request, Transport};
+
+pub use xmlrpc::{Fault, Value};
+
+#[derive(Debug, Fail)]
+#[fail(display = "Invalid XML-RPC response: {}", 0)]
+pub struct ParseError(String);
+
+/// Encode a method call with the given parameters.
+pub fn method_call(method: &str, params: &[Value]) -> String {
+    let request = params.iter().fold(Request::new(method), |request, param| {
+        request.arg(param.clone())
+    });
+    let mut out = Vec::new();
+    request
+        .write_as_xml(&mut out)
+        .expect("writing to a Vec can't fail");
+    String::from_utf8(out).expect("the encoded call is not UTF-8")
+}
+
+/// Decode the response to a method call, which is either a value or a fault.
+pub fn parse_response(data: &str) -> Result<Result<Value, Fault>, ParseError> {
+    match Request::new("").call(Received(data.as_bytes())) {
+        Ok(value) => Ok(Ok(value)),
+        Err(e) => match e.fault() {
+            Some(fault) => Ok(Err(fault.clone())),
+            None => Err(ParseError(format!("{}", e))),
+        },
+    }
+}
+
+/// Hands a response which was already received to the parser of the crate.
+struct Received<'a>(&'a [u8]);
+
+impl<'a> Transport for Received<'a> {
+    type Stream = &'a [u8];
+
+    fn transmit(self, _request: &Request) -> Result<Self::Stream, Box<Error + Send + Sync>> {
+        Ok(self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_struct() {
+        let data = "<?xml version=\"1.0\"?>\n<methodResponse>\n<params><param><value><struct>\
+                    <member><name>login</name><value><string>true</string></value></member>\
+                    <member><name>sim_port</name><value><i4>9000</i4></value></member>\
+                    <member><name>message</name><value>a &amp; b</value></member>\
+                    <member><name>empty</name><value><string/></value></member>\
+                    <member><name>list</name><value><array><data>\
+                    <value><struct><member><name>folder_id</name><value>abc</value></member></struct></value>\
+                    </data></array></value></member>\
+                    </struct></value></param></params>\n</methodResponse>";
+        let value = parse_response(data).unwrap().unwrap();
+        let members = match value {
+            Value::Struct(members) => members,
+            v => panic!("not a struct: {:?}", v),
+        };
+        assert_eq!(members["login"], Value::String("true".to_string()));
+        assert_eq!(members["sim_port"], Value::Int(9000));
+        assert_eq!(members["message"], Value::String("a & b".to_string()));
+        assert_eq!(members["empty"], Value::String(String::new()));
+        match members["list"] {
+            Value::Array(ref values) => assert_eq!(values.len(), 1),
+            ref v => panic!("not an array: {:?}", v),
+        }
+    }
+
+    #[test]
+    fn parse_fault() {
+        let data = "<methodResponse><fault><value><struct>\
+                    <member><name>faultCode</name><value><int>4</int></value></member>\
+                    <member><name>faultString</name><value><string>Too many parameters.</string></value></member>\
+                    </struct></value></fault></methodResponse>";
+        let fault = parse_response(data).unwrap().unwrap_err();
+        assert_eq!(fault.fault_code, 4);
+        assert_eq!(fault.fault_string, "Too many parameters.");
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(parse_response("<html></html>").is_err());
+    }
+
+    #[test]
+    fn encode_call() {
+        let call = method_call("login", &[Value::String("<&>".to_string())]);
+        assert!(call.contains("<methodName>login</methodName>"));
+        assert!(call.contains("&lt;&amp;&gt;"));
+    }
+}
@@ -0,0 +1,300 @@
+//! The start location of a login.
+
+use std::fmt;
+use std::str::FromStr;
+use types::Vector3;
+use url::percent_encoding::percent_decode;
+
+#[derive(Debug, Fail, PartialEq)]
+pub enum StartLocationError {
+    #[fail(display = "Invalid start location: '{}'", 0)]
+    InvalidFormat(String),
+
+    #[fail(display = "Invalid coordinate: '{}'", 0)]
+    InvalidCoordinate(String),
+
+    #[fail(display = "Invalid port: '{}'", 0)]
+    InvalidPort(String),
+
+    #[fail(display = "The region name is empty.")]
+    EmptyRegion,
+}
+
+/// The gatekeeper service of a grid, which is the entry point for hypergrid
+/// visitors.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Gatekeeper {
+    pub host: String,
+    pub port: u16,
+}
+
+impl Gatekeeper {
+    /// The URI of the gatekeeper's XML-RPC interface.
+    pub fn uri(&self) -> String {
+        format!("http://{}:{}/", self.host, self.port)
+    }
+}
+
+/// A region, either of the grid the agent is on or of a foreign grid reached
+/// through hypergrid.
+///
+/// The string form is `Region Name` for local regions and
+/// `host:port:Region Name` for hypergrid regions, where the region name can
+/// be empty to select the default region of the foreign grid.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegionAddress {
+    /// Only set for regions of foreign grids.
+    pub gatekeeper: Option<Gatekeeper>,
+    pub region: String,
+}
+
+impl RegionAddress {
+    pub fn local(region: &str) -> Self {
+        RegionAddress {
+            gatekeeper: None,
+            region: region.to_string(),
+        }
+    }
+
+    pub fn is_hypergrid(&self) -> bool {
+        self.gatekeeper.is_some()
+    }
+}
+
+impl fmt::Display for RegionAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref gatekeeper) = self.gatekeeper {
+            write!(f, "{}:{}:", gatekeeper.host, gatekeeper.port)?;
+        }
+        write!(f, "{}", escape(&self.region))
+    }
+}
+
+impl FromStr for RegionAddress {
+    type Err = StartLocationError;
+
+    fn from_str(s: &str) -> Result<Self, StartLocationError> {
+        let parts: Vec<&str> = s.splitn(3, ':').collect();
+        match parts.len() {
+            1 => {
+                let region = unescape(parts[0])?;
+                if region.trim().is_empty() {
+                    return Err(StartLocationError::EmptyRegion);
+                }
+                Ok(RegionAddress {
+                    gatekeeper: None,
+                    region: region,
+                })
+            }
+            2 | 3 => {
+                let host = parts[0].trim();
+                if host.is_empty() {
+                    return Err(StartLocationError::InvalidFormat(s.to_string()));
+                }
+                let port = parts[1]
+                    .trim()
+                    .parse()
+                    .map_err(|_| StartLocationError::InvalidPort(parts[1].to_string()))?;
+                let region = match parts.get(2) {
+                    Some(region) => unescape(region)?,
+                    None => String::new(),
+                };
+                Ok(RegionAddress {
+                    gatekeeper: Some(Gatekeeper {
+                        host: host.to_string(),
+                        port: port,
+                    }),
+                    region: region,
+                })
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Where the agent is placed after logging in.
+///
+/// The string form is the one expected by login servers: `last`, `home` or
+/// `uri:Region Name&128&128&25`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StartLocation {
+    /// The location where the agent logged out the last time.
+    Last,
+    /// The home location of the agent.
+    Home,
+    /// A specific position in a region.
+    Region {
+        address: RegionAddress,
+        position: Vector3<f32>,
+    },
+}
+
+impl StartLocation {
+    /// Start in the given region at the given position.
+    ///
+    /// Fails if the region name is empty or the position is invalid.
+    pub fn region(
+        address: RegionAddress,
+        position: Vector3<f32>,
+    ) -> Result<Self, StartLocationError> {
+        if address.gatekeeper.is_none() && address.region.trim().is_empty() {
+            return Err(StartLocationError::EmptyRegion);
+        }
+        for c in position.iter() {
+            check_coordinate(*c)?;
+        }
+        Ok(StartLocation::Region {
+            address: address,
+            position: position,
+        })
+    }
+}
+
+/// The position used if a start location specifies none.
+fn default_position() -> Vector3<f32> {
+    Vector3::new(128., 128., 0.)
+}
+
+fn check_coordinate(c: f32) -> Result<f32, StartLocationError> {
+    if c.is_finite() && c >= 0. {
+        Ok(c)
+    } else {
+        Err(StartLocationError::InvalidCoordinate(c.to_string()))
+    }
+}
+
+impl fmt::Display for StartLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            StartLocation::Last => write!(f, "last"),
+            StartLocation::Home => write!(f, "home"),
+            StartLocation::Region {
+                ref address,
+                ref position,
+            } => write!(
+                f,
+                "uri:{}&{}&{}&{}",
+                address, position.x, position.y, position.z
+            ),
+        }
+    }
+}
+
+impl FromStr for StartLocation {
+    type Err = StartLocationError;
+
+    fn from_str(s: &str) -> Result<Self, StartLocationError> {
+        match s {
+            "last" => return Ok(StartLocation::Last),
+            "home" => return Ok(StartLocation::Home),
+            _ => {}
+        }
+        if !s.starts_with("uri:") {
+            return Err(StartLocationError::InvalidFormat(s.to_string()));
+        }
+
+        let parts: Vec<&str> = s[4..].split('&').collect();
+        let address = RegionAddress::from_str(parts[0])?;
+        let position = match parts.len() {
+            1 => default_position(),
+            4 => {
+                let mut coords = [0f32; 3];
+                for (coord, raw) in coords.iter_mut().zip(&parts[1..]) {
+                    let value = raw
+                        .trim()
+                        .parse()
+                        .map_err(|_| StartLocationError::InvalidCoordinate(raw.to_string()))?;
+                    *coord = check_coordinate(value)?;
+                }
+                Vector3::new(coords[0], coords[1], coords[2])
+            }
+            _ => return Err(StartLocationError::InvalidFormat(s.to_string())),
+        };
+        StartLocation::region(address, position)
+    }
+}
+
+/// Escape the characters with special meaning in start locations.
+fn escape(region: &str) -> String {
+    let mut out = String::with_capacity(region.len());
+    for c in region.chars() {
+        match c {
+            '%' => out.push_str("%25"),
+            '&' => out.push_str("%26"),
+            ':' => out.push_str("%3A"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape(raw: &str) -> Result<String, StartLocationError> {
+    percent_decode(raw.as_bytes())
+        .decode_utf8()
+        .map(|s| s.into_owned())
+        .map_err(|_| StartLocationError::InvalidFormat(raw.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_format() {
+        for raw in &[
+            "last",
+            "home",
+            "uri:Test Region&128&64&25",
+            "uri:Rock %26 Roll&1.5&2&3",
+            "uri:grid.example.org:8002:Welcome&128&128&25",
+            "uri:grid.example.org:8002:&128&128&0",
+        ] {
+            let location = StartLocation::from_str(raw).unwrap();
+            assert_eq!(&location.to_string(), raw);
+        }
+    }
+
+    #[test]
+    fn parse_region() {
+        match StartLocation::from_str("uri:grid.example.org:8002:Rock %26 Roll").unwrap() {
+            StartLocation::Region { address, position } => {
+                assert_eq!(
+                    address.gatekeeper,
+                    Some(Gatekeeper {
+                        host: "grid.example.org".to_string(),
+                        port: 8002,
+                    })
+                );
+                assert_eq!(address.region, "Rock & Roll");
+                assert_eq!(position, default_position());
+            }
+            l => panic!("wrong location: {:?}", l),
+        }
+    }
+
+    #[test]
+    fn invalid_locations() {
+        assert_eq!(
+            StartLocation::from_str("Test Region"),
+            Err(StartLocationError::InvalidFormat("Test Region".to_string()))
+        );
+        assert_eq!(
+            StartLocation::from_str("uri:&128&128&0"),
+            Err(StartLocationError::EmptyRegion)
+        );
+        assert_eq!(
+            StartLocation::from_str("uri:Test&128&-1&0"),
+            Err(StartLocationError::InvalidCoordinate("-1".to_string()))
+        );
+        assert_eq!(
+            StartLocation::from_str("uri:Test&128&128"),
+            Err(StartLocationError::InvalidFormat(
+                "uri:Test&128&128".to_string()
+            ))
+        );
+        assert_eq!(
+            StartLocation::from_str("uri:grid.example.org:abc:Test"),
+            Err(StartLocationError::InvalidPort("abc".to_string()))
+        );
+    }
+}
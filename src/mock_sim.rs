@@ -0,0 +1,840 @@
+//! In-process fake simulator for offline testing.
+//!
+//! `MockSimulator` listens on a local UDP socket for the circuit and on a
+//! local HTTP port for the login and gatekeeper XML-RPC calls, the grid info
+//! and the seed capability. It follows a fixed script:
+//!
+//! - Every reliable packet is acknowledged.
+//! - `UseCircuitCode` is answered with the configured `RegionHandshake`.
+//! - `RegionHandshakeReply` is answered with full updates of the configured
+//!   `objects` if the client asked for all cacheable objects, otherwise with
+//!   `ObjectUpdateCached`. Objects requested with `RequestMultipleObjects`
+//!   are sent in full.
+//! - `CompleteAgentMovement` is answered with `AgentMovementComplete`,
+//!   followed by the configured `after_movement` messages, e.g. `LayerData`.
+//! - `StartPingCheck` is answered with `CompletePingCheck`, and if
+//!   configured the client is pinged regularly.
+//! - `MapNameRequest` for a hypergrid name, `host:port Region`, creates a
+//!   hyperlink, which is answered with its handle in `MapBlockReply`.
+//! - `TeleportLocationRequest` is answered with `TeleportStart` for the own
+//!   region, hyperlinks and the configured `teleport_regions`, and with
+//!   `TeleportFailed` for all other handles.
+//! - `LogoutRequest` is answered with `LogoutReply`, unless `logout_reply`
+//!   is disabled.
+//! - An event queue request with `done` set is answered right away and
+//!   recorded, see `MockSimulator::event_queue_released`. The first
+//!   `event_queue_failures` other event queue requests fail with a server
+//!   error.
+//!
+//! Further messages can be sent to the client with `MockSimulator::send`,
+//! and events through the event queue with `MockSimulator::send_event`. All
+//! messages received from the client are recorded.
+//!
+//! This module is only available with the `mock-sim` feature.
+
+use capabilities::Capability;
+use grid_map::region_handle::RegionHandle;
+use llsd;
+use llsd::data::{Scalar, Value};
+use login::Gatekeeper;
+use messages::all::{
+    AgentMovementComplete, AgentMovementComplete_AgentData, AgentMovementComplete_Data,
+    AgentMovementComplete_SimData, CompletePingCheck, CompletePingCheck_PingID, LogoutReply,
+    LogoutReply_AgentData, MapBlockReply, MapBlockReply_AgentData, MapBlockReply_Data,
+    ObjectUpdateCached, ObjectUpdateCached_ObjectData, ObjectUpdateCached_RegionData, PacketAck,
+    PacketAck_Packets, RegionHandshake, StartPingCheck, StartPingCheck_PingID, TeleportFailed,
+    TeleportFailed_Info, TeleportStart, TeleportStart_Info,
+};
+use messages::{MessageInstance, MessageType};
+use object_update::Primitive;
+use packet::Packet;
+use simulator::ConnectInfo;
+use types::{Ip4Addr, Uuid, Vector3};
+use url::Url;
+use util::AtomicU32Counter;
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often the server threads check whether they should exit.
+const POLL_INTERVAL_MS: u64 = 20;
+
+/// How long an event queue request is held open if there are no events.
+/// This is a lot shorter than for a real sim, as the HTTP server only handles
+/// one request at a time.
+const EVENT_QUEUE_TIMEOUT_MS: u64 = 200;
+
+/// Set in `RegionHandshakeReply` to ask for all cacheable objects.
+const REGION_HANDSHAKE_CACHE_EMPTY: u32 = 1;
+
+/// Where hyperlinks are placed on the map, in regions.
+const HYPERLINK_X: u16 = 9000;
+const HYPERLINK_Y: u16 = 9000;
+
+/// Describes the agent and region the mock simulator pretends to host.
+#[derive(Clone, Debug)]
+pub struct MockSimConfig {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    pub circuit_code: u32,
+
+    /// Sent in reply to `UseCircuitCode`.
+    pub region_handshake: RegionHandshake,
+
+    /// The region and position reported in `AgentMovementComplete`.
+    pub region_handle: RegionHandle,
+    pub agent_position: Vector3<f32>,
+
+    /// Sent reliably in reply to `CompleteAgentMovement`, in order.
+    pub after_movement: Vec<MessageInstance>,
+
+    /// If set the client is pinged with `StartPingCheck` at this interval.
+    pub ping_interval: Option<Duration>,
+
+    /// Whether `LogoutRequest` is answered.
+    pub logout_reply: bool,
+
+    /// How many event queue requests fail before events are delivered.
+    pub event_queue_failures: u32,
+
+    /// Further regions of the grid which can be teleported to.
+    pub teleport_regions: Vec<RegionHandle>,
+
+    /// The cacheable objects of the region, full updates containing one
+    /// object each.
+    pub objects: Vec<MessageInstance>,
+}
+
+impl Default for MockSimConfig {
+    fn default() -> Self {
+        MockSimConfig {
+            agent_id: "5e3f1ad4-7bd5-4c4a-a2cf-5c8a3b1b2b01".parse().unwrap(),
+            session_id: "0d4d6bd0-6c2e-4b7e-9f4b-3e8b2f7a1c02".parse().unwrap(),
+            circuit_code: 123_456,
+            region_handshake: default_region_handshake(),
+            region_handle: RegionHandle::from_xy(256_000, 256_000),
+            agent_position: Vector3::new(128., 128., 25.),
+            after_movement: Vec::new(),
+            ping_interval: None,
+            logout_reply: true,
+            event_queue_failures: 0,
+            teleport_regions: Vec::new(),
+            objects: Vec::new(),
+        }
+    }
+}
+
+/// The `RegionHandshake` of a small test region named "testland", as
+/// captured from OpenSim.
+pub fn default_region_handshake() -> RegionHandshake {
+    let raw = include_bytes!("data/tests/region_handshake.bin");
+    match Packet::read(raw).unwrap().message {
+        MessageInstance::RegionHandshake(msg) => msg,
+        _ => unreachable!(),
+    }
+}
+
+/// A fake simulator running in background threads of the current process.
+///
+/// The threads are stopped when this is dropped.
+pub struct MockSimulator {
+    config: MockSimConfig,
+    udp_addr: SocketAddr,
+    http_addr: SocketAddr,
+
+    received: Arc<Mutex<Vec<MessageInstance>>>,
+    outgoing: Mutex<mpsc::Sender<MessageInstance>>,
+    events: Arc<Mutex<Vec<(String, String)>>>,
+    event_queue_released: Arc<AtomicBool>,
+
+    shutdown: Arc<AtomicBool>,
+    threads: Vec<thread::JoinHandle<()>>,
+}
+
+impl MockSimulator {
+    /// Bind the sockets and start the server threads.
+    pub fn start(config: MockSimConfig) -> Result<Self, io::Error> {
+        let udp_socket = UdpSocket::bind("127.0.0.1:0")?;
+        udp_socket.set_read_timeout(Some(Duration::from_millis(POLL_INTERVAL_MS)))?;
+        let http_listener = TcpListener::bind("127.0.0.1:0")?;
+        http_listener.set_nonblocking(true)?;
+
+        let udp_addr = udp_socket.local_addr()?;
+        let http_addr = http_listener.local_addr()?;
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let (outgoing_tx, outgoing_rx) = mpsc::channel();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let event_queue_released = Arc::new(AtomicBool::new(false));
+
+        let udp_thread = {
+            let server = UdpServer {
+                socket: udp_socket,
+                client: None,
+                config: config.clone(),
+                received: Arc::clone(&received),
+                outgoing: outgoing_rx,
+                sequence_counter: AtomicU32Counter::new(1),
+                last_ping: Instant::now(),
+                next_ping_id: 0,
+                hyperlinks: Vec::new(),
+            };
+            let shutdown = Arc::clone(&shutdown);
+            thread::spawn(move || server.run(&shutdown))
+        };
+
+        let http_thread = {
+            let responses = HttpResponses {
+                config: config.clone(),
+                udp_addr: udp_addr,
+                http_addr: http_addr,
+                events: Arc::clone(&events),
+                event_id: AtomicU32Counter::new(1),
+                event_queue_released: Arc::clone(&event_queue_released),
+                event_queue_requests: AtomicU32Counter::new(0),
+            };
+            let shutdown = Arc::clone(&shutdown);
+            thread::spawn(move || {
+                while !shutdown.load(Ordering::SeqCst) {
+                    match http_listener.accept() {
+                        Ok((stream, _)) => {
+                            // A broken request only affects the client which sent it.
+                            let _ = responses.handle(stream);
+                        }
+                        Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                            thread::sleep(Duration::from_millis(POLL_INTERVAL_MS));
+                        }
+                        Err(e) => panic!("mock sim: accepting connection failed: {}", e),
+                    }
+                }
+            })
+        };
+
+        Ok(MockSimulator {
+            config: config,
+            udp_addr: udp_addr,
+            http_addr: http_addr,
+            received: received,
+            outgoing: Mutex::new(outgoing_tx),
+            events: events,
+            event_queue_released: event_queue_released,
+            shutdown: shutdown,
+            threads: vec![udp_thread, http_thread],
+        })
+    }
+
+    /// The URI to pass to `grid_info::get_grid_info`.
+    pub fn grid_uri(&self) -> String {
+        format!("http://{}/", self.http_addr)
+    }
+
+    /// The gatekeeper of the mock grid, for hypergrid addresses.
+    pub fn gatekeeper(&self) -> Gatekeeper {
+        Gatekeeper {
+            host: self.http_addr.ip().to_string(),
+            port: self.http_addr.port(),
+        }
+    }
+
+    /// The URI to pass to `login::login`.
+    pub fn login_uri(&self) -> String {
+        format!("http://{}/login", self.http_addr)
+    }
+
+    /// The URL of the seed capability.
+    pub fn seed_capability(&self) -> Url {
+        seed_capability(&self.http_addr)
+    }
+
+    /// The connect info a successful login would return, for tests which
+    /// want to skip the login.
+    pub fn connect_info(&self) -> ConnectInfo {
+        ConnectInfo {
+            capabilities_seed: self.seed_capability(),
+            agent_id: self.config.agent_id.clone(),
+            session_id: self.config.session_id.clone(),
+            circuit_code: self.config.circuit_code,
+            sim_ip: sim_ip(&self.udp_addr),
+            sim_port: self.udp_addr.port(),
+            requested_capabilities: Capability::default_requested(),
+            object_cache: None,
+        }
+    }
+
+    pub fn config(&self) -> &MockSimConfig {
+        &self.config
+    }
+
+    /// Send a message reliably to the client.
+    ///
+    /// Messages sent before the client connected are sent once it sends
+    /// `UseCircuitCode`.
+    pub fn send<M: Into<MessageInstance>>(&self, msg: M) {
+        self.outgoing.lock().unwrap().send(msg.into()).unwrap();
+    }
+
+    /// Deliver an event through the event queue.
+    ///
+    /// `body` is the LLSD XML of the event body, i.e. `<map>...</map>`.
+    pub fn send_event(&self, name: &str, body: &str) {
+        self.events
+            .lock()
+            .unwrap()
+            .push((name.to_string(), body.to_string()));
+    }
+
+    /// Whether the client released the event queue by sending a request
+    /// with `done` set.
+    pub fn event_queue_released(&self) -> bool {
+        self.event_queue_released.load(Ordering::SeqCst)
+    }
+
+    /// Returns all messages received from the client so far, except for
+    /// `PacketAck`.
+    pub fn received(&self) -> Vec<MessageInstance> {
+        self.received.lock().unwrap().clone()
+    }
+
+    /// Wait until a message of the given type was received from the client,
+    /// returning the first one.
+    ///
+    /// Returns `None` if there was none before the timeout.
+    pub fn wait_for(
+        &self,
+        message_type: MessageType,
+        timeout: Duration,
+    ) -> Option<MessageInstance> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let found = self
+                .received
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|msg| msg.message_type() == message_type)
+                .cloned();
+            if found.is_some() || Instant::now() >= deadline {
+                return found;
+            }
+            thread::sleep(Duration::from_millis(POLL_INTERVAL_MS));
+        }
+    }
+}
+
+impl Drop for MockSimulator {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn sim_ip(udp_addr: &SocketAddr) -> Ip4Addr {
+    match *udp_addr {
+        SocketAddr::V4(addr) => *addr.ip(),
+        SocketAddr::V6(_) => unreachable!(),
+    }
+}
+
+fn seed_capability(http_addr: &SocketAddr) -> Url {
+    Url::parse(&format!("http://{}/seed", http_addr)).unwrap()
+}
+
+struct UdpServer {
+    socket: UdpSocket,
+    /// Address of the client, known once it sent its first packet.
+    client: Option<SocketAddr>,
+    config: MockSimConfig,
+    received: Arc<Mutex<Vec<MessageInstance>>>,
+    outgoing: mpsc::Receiver<MessageInstance>,
+    sequence_counter: AtomicU32Counter,
+    last_ping: Instant,
+    next_ping_id: u8,
+    /// The hyperlinks created with `MapNameRequest`, by name.
+    hyperlinks: Vec<(String, RegionHandle)>,
+}
+
+impl UdpServer {
+    fn run(mut self, shutdown: &AtomicBool) {
+        let mut buf = [0u8; 4096];
+        while !shutdown.load(Ordering::SeqCst) {
+            match self.socket.recv_from(&mut buf) {
+                Ok((size, addr)) => {
+                    self.client = Some(addr);
+                    if let Ok(packet) = Packet::read(&buf[..size]) {
+                        self.handle_packet(packet);
+                    }
+                }
+                Err(ref e)
+                    if e.kind() == io::ErrorKind::WouldBlock
+                        || e.kind() == io::ErrorKind::TimedOut => {}
+                Err(e) => panic!("mock sim: reading from socket failed: {}", e),
+            }
+
+            if self.client.is_some() {
+                while let Ok(msg) = self.outgoing.try_recv() {
+                    self.send(msg, true);
+                }
+
+                if let Some(interval) = self.config.ping_interval {
+                    if self.last_ping.elapsed() >= interval {
+                        self.ping();
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_packet(&mut self, packet: Packet) {
+        if packet.is_reliable() {
+            let ack = PacketAck {
+                packets: vec![PacketAck_Packets {
+                    id: packet.sequence_number,
+                }],
+            };
+            self.send(ack, false);
+        }
+
+        match packet.message {
+            MessageInstance::PacketAck(_) => return,
+            MessageInstance::UseCircuitCode(_) => {
+                let handshake = self.config.region_handshake.clone();
+                self.send(handshake, true);
+            }
+            MessageInstance::RegionHandshakeReply(ref msg) => {
+                if msg.region_info.flags & REGION_HANDSHAKE_CACHE_EMPTY != 0 {
+                    for object in self.config.objects.clone() {
+                        self.send(object, true);
+                    }
+                } else {
+                    self.send_cached_objects();
+                }
+            }
+            MessageInstance::RequestMultipleObjects(ref msg) => {
+                for request in &msg.object_data {
+                    let object = self
+                        .config
+                        .objects
+                        .iter()
+                        .find(|object| object_id_crc(object).map(|(id, _)| id) == Some(request.id))
+                        .cloned();
+                    if let Some(object) = object {
+                        self.send(object, true);
+                    }
+                }
+            }
+            MessageInstance::CompleteAgentMovement(_) => {
+                let complete = AgentMovementComplete {
+                    agent_data: AgentMovementComplete_AgentData {
+                        agent_id: self.config.agent_id.clone(),
+                        session_id: self.config.session_id.clone(),
+                    },
+                    data: AgentMovementComplete_Data {
+                        position: self.config.agent_position,
+                        look_at: Vector3::new(1., 0., 0.),
+                        region_handle: self.config.region_handle.handle(),
+                        timestamp: 0,
+                    },
+                    sim_data: AgentMovementComplete_SimData {
+                        channel_version: b"mock_sim".to_vec(),
+                    },
+                };
+                self.send(complete, true);
+                for msg in self.config.after_movement.clone() {
+                    self.send(msg, true);
+                }
+            }
+            MessageInstance::StartPingCheck(ref msg) => {
+                let reply = CompletePingCheck {
+                    ping_id: CompletePingCheck_PingID {
+                        ping_id: msg.ping_id.ping_id,
+                    },
+                };
+                self.send(reply, false);
+            }
+            MessageInstance::MapNameRequest(ref msg) => {
+                let name = String::from_utf8_lossy(&msg.name_data.name)
+                    .trim_end_matches('\0')
+                    .to_string();
+                self.map_name_request(name);
+            }
+            MessageInstance::TeleportLocationRequest(ref msg) => {
+                let handle = RegionHandle::from_handle(msg.info.region_handle);
+                if self.can_teleport_to(&handle) {
+                    let start = TeleportStart {
+                        info: TeleportStart_Info { teleport_flags: 0 },
+                    };
+                    self.send(start, true);
+                } else {
+                    let failed = TeleportFailed {
+                        info: TeleportFailed_Info {
+                            agent_id: self.config.agent_id.clone(),
+                            reason: b"Unknown region\0".to_vec(),
+                        },
+                        alert_info: Vec::new(),
+                    };
+                    self.send(failed, true);
+                }
+            }
+            MessageInstance::LogoutRequest(_) if self.config.logout_reply => {
+                let reply = LogoutReply {
+                    agent_data: LogoutReply_AgentData {
+                        agent_id: self.config.agent_id.clone(),
+                        session_id: self.config.session_id.clone(),
+                    },
+                    inventory_data: Vec::new(),
+                };
+                self.send(reply, true);
+            }
+            _ => {}
+        }
+
+        self.received.lock().unwrap().push(packet.message);
+    }
+
+    /// Tell the client which objects it might have cached.
+    fn send_cached_objects(&self) {
+        let object_data: Vec<_> = self
+            .config
+            .objects
+            .iter()
+            .filter_map(object_id_crc)
+            .map(|(id, crc)| ObjectUpdateCached_ObjectData {
+                id: id,
+                crc: crc,
+                update_flags: 0,
+            })
+            .collect();
+        if object_data.is_empty() {
+            return;
+        }
+        let cached = ObjectUpdateCached {
+            region_data: ObjectUpdateCached_RegionData {
+                region_handle: self.config.region_handle.handle(),
+                time_dilation: 0xffff,
+            },
+            object_data: object_data,
+        };
+        self.send(cached, true);
+    }
+
+    /// Link hypergrid names, other regions than the own one are not found.
+    ///
+    /// Like OpenSim the results are terminated with a block named like the
+    /// request, which has no position.
+    fn map_name_request(&mut self, name: String) {
+        let mut blocks = Vec::new();
+        let sim_name = &self.config.region_handshake.region_info.sim_name;
+        let sim_name = String::from_utf8_lossy(sim_name);
+        if name.eq_ignore_ascii_case(sim_name.trim_end_matches('\0')) {
+            let (x, y) = self.config.region_handle.xy();
+            blocks.push(map_block(&name, (x / 256) as u16, (y / 256) as u16, 13));
+        } else if name.contains(':') {
+            let existing = self
+                .hyperlinks
+                .iter()
+                .find(|&&(ref link, _)| link == &name)
+                .map(|&(_, ref handle)| handle.clone());
+            let handle = match existing {
+                Some(handle) => handle,
+                None => {
+                    let x = HYPERLINK_X + self.hyperlinks.len() as u16;
+                    let handle =
+                        RegionHandle::from_xy(u32::from(x) * 256, u32::from(HYPERLINK_Y) * 256);
+                    self.hyperlinks.push((name.clone(), handle.clone()));
+                    handle
+                }
+            };
+            let (x, y) = handle.xy();
+            blocks.push(map_block(&name, (x / 256) as u16, (y / 256) as u16, 13));
+        }
+        blocks.push(map_block(&name, 0, 0, 255));
+
+        let reply = MapBlockReply {
+            agent_data: MapBlockReply_AgentData {
+                agent_id: self.config.agent_id.clone(),
+                flags: 2,
+            },
+            data: blocks,
+        };
+        self.send(reply, true);
+    }
+
+    fn can_teleport_to(&self, handle: &RegionHandle) -> bool {
+        *handle == self.config.region_handle
+            || self.config.teleport_regions.contains(handle)
+            || self.hyperlinks.iter().any(|&(_, ref link)| link == handle)
+    }
+
+    fn ping(&mut self) {
+        self.last_ping = Instant::now();
+        let ping = StartPingCheck {
+            ping_id: StartPingCheck_PingID {
+                ping_id: self.next_ping_id,
+                oldest_unacked: 0,
+            },
+        };
+        self.next_ping_id = self.next_ping_id.wrapping_add(1);
+        self.send(ping, false);
+    }
+
+    fn send<M: Into<MessageInstance>>(&self, msg: M, reliable: bool) {
+        let client = match self.client {
+            Some(addr) => addr,
+            None => return,
+        };
+
+        let msg = msg.into();
+        let zerocoded = msg.message_type().is_zerocoded();
+        let mut packet = Packet::new(msg, self.sequence_counter.next());
+        packet.set_reliable(reliable);
+        packet.set_zerocoded(zerocoded);
+
+        let mut buf = Vec::new();
+        packet.write_to(&mut buf).unwrap();
+        self.socket.send_to(&buf, client).unwrap();
+    }
+}
+
+/// The local id and CRC of the object in a full update.
+fn object_id_crc(msg: &MessageInstance) -> Option<(u32, u32)> {
+    let mut objects = match *msg {
+        MessageInstance::ObjectUpdate(ref msg) => Primitive::from_object_update(msg),
+        MessageInstance::ObjectUpdateCompressed(ref msg) => {
+            Primitive::from_object_update_compressed(msg)
+        }
+        _ => return None,
+    };
+    let object = objects.pop()?.ok()?;
+    Some((object.local_id, object.crc))
+}
+
+/// Whether an event queue request has `done` set.
+fn is_done_request(body: &[u8]) -> bool {
+    match llsd::xml::read_value(body) {
+        Ok(Value::Map(map)) => match map.get("done") {
+            Some(&Value::Scalar(Scalar::Boolean(done))) => done,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn map_block(name: &str, x: u16, y: u16, access: u8) -> MapBlockReply_Data {
+    MapBlockReply_Data {
+        x: x,
+        y: y,
+        name: name.as_bytes().to_vec(),
+        access: access,
+        region_flags: 0,
+        water_height: 20,
+        agents: 0,
+        map_image_id: Uuid::nil(),
+    }
+}
+
+/// Answers the HTTP requests of the client.
+struct HttpResponses {
+    config: MockSimConfig,
+    udp_addr: SocketAddr,
+    http_addr: SocketAddr,
+    /// Pending events as name and LLSD body.
+    events: Arc<Mutex<Vec<(String, String)>>>,
+    event_id: AtomicU32Counter,
+    event_queue_released: Arc<AtomicBool>,
+    event_queue_requests: AtomicU32Counter,
+}
+
+impl HttpResponses {
+    fn handle(&self, stream: TcpStream) -> Result<(), io::Error> {
+        stream.set_nonblocking(false)?;
+        let mut reader = BufReader::new(stream);
+
+        // Read the request line and the headers.
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line)?;
+            let line = line.trim();
+            if line.is_empty() {
+                break;
+            }
+            let mut parts = line.splitn(2, ':');
+            let name = parts.next().unwrap_or("").trim().to_lowercase();
+            if name == "content-length" {
+                content_length = parts.next().unwrap_or("").trim().parse().unwrap_or(0);
+            }
+        }
+        let mut body = vec![0u8; content_length];
+        reader.read_exact(&mut body)?;
+
+        let path = request_line.split_whitespace().nth(1).unwrap_or("");
+        let (status, content_type, body) = match path {
+            "/" => ("200 OK", "text/xml", self.gatekeeper_response(&body)),
+            "/login" => ("200 OK", "text/xml", self.login_response()),
+            "/get_grid_info" => ("200 OK", "text/xml", self.grid_info_response()),
+            "/seed" => ("200 OK", "application/xml", self.seed_response()),
+            "/eq" if is_done_request(&body) => {
+                self.event_queue_released.store(true, Ordering::SeqCst);
+                ("404 Not Found", "text/plain", "queue released".to_string())
+            }
+            "/eq" if self.event_queue_requests.next() < self.config.event_queue_failures => (
+                "500 Internal Server Error",
+                "text/plain",
+                "temporary failure".to_string(),
+            ),
+            "/eq" => match self.event_queue_response() {
+                Some(body) => ("200 OK", "application/xml", body),
+                None => ("502 Bad Gateway", "text/plain", "no events".to_string()),
+            },
+            _ => ("404 Not Found", "text/plain", "not found".to_string()),
+        };
+
+        let mut stream = reader.into_inner();
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            content_type,
+            body.len(),
+            body
+        )?;
+        stream.flush()
+    }
+
+    /// XML-RPC response of a successful `login_to_simulator` call.
+    fn login_response(&self) -> String {
+        fn member(name: &str, value: String) -> String {
+            format!(
+                "<member><name>{}</name><value>{}</value></member>",
+                name, value
+            )
+        }
+        fn string(value: &str) -> String {
+            format!("<string>{}</string>", value)
+        }
+        fn int(value: i64) -> String {
+            format!("<i4>{}</i4>", value)
+        }
+
+        let members = vec![
+            member("login", string("true")),
+            member("look_at", string("[r1,r0,r0]")),
+            member("circuit_code", int(self.config.circuit_code as i64)),
+            member("session_id", string(&self.config.session_id.to_string())),
+            member("agent_id", string(&self.config.agent_id.to_string())),
+            member(
+                "seed_capability",
+                string(seed_capability(&self.http_addr).as_str()),
+            ),
+            member("sim_ip", string(&sim_ip(&self.udp_addr).to_string())),
+            member("sim_port", int(self.udp_addr.port() as i64)),
+            member("region_x", int(256_000)),
+            member("region_y", int(256_000)),
+            member("first_name", string("&quot;Test&quot;")),
+            member("last_name", string("User")),
+            member("message", string("Welcome to the mock simulator.")),
+        ];
+        format!(
+            "<?xml version=\"1.0\"?><methodResponse><params><param><value><struct>{}</struct></value></param></params></methodResponse>",
+            members.concat()
+        )
+    }
+
+    /// XML-RPC response of the gatekeeper's `link_region` call, the region is
+    /// the one of the circuit.
+    fn gatekeeper_response(&self, request: &[u8]) -> String {
+        let request = String::from_utf8_lossy(request);
+        let region_id = self
+            .config
+            .region_handshake
+            .region_info2
+            .region_id
+            .to_string();
+        let mut members = vec![("result", "True".to_string()), ("uuid", region_id)];
+        if request.contains("<methodName>link_region</methodName>") {
+            members.push(("handle", ((256_000u64 << 32) | 256_000).to_string()));
+            members.push(("external_name", "mock:testland".to_string()));
+        } else {
+            members = vec![("result", "False".to_string())];
+        }
+
+        let members: Vec<_> = members
+            .iter()
+            .map(|&(name, ref value)| {
+                format!(
+                    "<member><name>{}</name><value><string>{}</string></value></member>",
+                    name, value
+                )
+            })
+            .collect();
+        format!(
+            "<?xml version=\"1.0\"?><methodResponse><params><param><value><struct>{}</struct></value></param></params></methodResponse>",
+            members.concat()
+        )
+    }
+
+    /// The grid info document, pointing to the login service.
+    fn grid_info_response(&self) -> String {
+        format!(
+            "<?xml version=\"1.0\"?><gridinfo>\
+             <login>http://{}/login</login>\
+             <gridname>Mock Grid</gridname>\
+             <gridnick>mock</gridnick>\
+             </gridinfo>",
+            self.http_addr
+        )
+    }
+
+    /// LLSD response of the seed capability.
+    fn seed_response(&self) -> String {
+        format!(
+            "<?xml version=\"1.0\" ?><llsd><map>\
+             <key>GetTexture</key><uri>http://{0}/texture</uri>\
+             <key>EventQueueGet</key><uri>http://{0}/eq</uri>\
+             </map></llsd>",
+            self.http_addr
+        )
+    }
+
+    /// Wait for events and return the LLSD response, `None` if there were
+    /// none before the timeout.
+    fn event_queue_response(&self) -> Option<String> {
+        let deadline = Instant::now() + Duration::from_millis(EVENT_QUEUE_TIMEOUT_MS);
+        let events = loop {
+            let events: Vec<_> = self.events.lock().unwrap().drain(..).collect();
+            if !events.is_empty() {
+                break events;
+            }
+            if Instant::now() >= deadline {
+                return None;
+            }
+            thread::sleep(Duration::from_millis(POLL_INTERVAL_MS));
+        };
+
+        let events: Vec<_> = events
+            .iter()
+            .map(|&(ref name, ref body)| {
+                format!(
+                    "<map><key>message</key><string>{}</string><key>body</key>{}</map>",
+                    name, body
+                )
+            })
+            .collect();
+        Some(format!(
+            "<?xml version=\"1.0\" ?><llsd><map>\
+             <key>id</key><integer>{}</integer>\
+             <key>events</key><array>{}</array>\
+             </map></llsd>",
+            self.event_id.next(),
+            events.concat()
+        ))
+    }
+}
@@ -0,0 +1,441 @@
+//! Connections to the sims of neighbour regions.
+//!
+//! Once the agent is in a region, its sim announces the neighbour regions
+//! with `EnableSimulator`, followed by the `EstablishAgentCommunication` event
+//! containing the seed capability of the child agent in the neighbour region.
+//! `Neighbours` then connects to the neighbour sim, so its terrain and objects
+//! can be received as well. The neighbour sim sends `DisableSimulator` when
+//! the child agent is removed, i.e. because the agent moved away, and the
+//! connection is closed.
+//!
+//! When the agent walks or flies across the border into a neighbour region,
+//! its sim sends `CrossedRegion`. The circuit to the neighbour sim then
+//! becomes the one of the root agent, see `NeighbourEvent::Crossed`.
+
+use circuit::message_handlers::{self, HandlerContext, Handlers};
+use circuit::Subscription;
+use event_queue::{EstablishAgentCommunication, Event};
+use failure::Error;
+use futures::sync::mpsc;
+use futures::{Async, Future, Poll, Stream};
+use grid_map::region_handle::RegionHandle;
+use logging::Log;
+use messages::all::CrossedRegion;
+use messages::{MessageInstance, MessageType};
+use simulator::{ConnectInfo, Simulator};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use tokio_core::reactor::Handle;
+use types::Vector3;
+use url::Url;
+
+/// Changes of the set of connected neighbours.
+#[derive(Debug)]
+pub enum NeighbourEvent {
+    /// The sim of the region was connected.
+    Connected(RegionHandle),
+    /// The connection to the sim of the region was closed.
+    Disconnected(RegionHandle),
+    /// Connecting to the sim of the region failed.
+    Failed(RegionHandle, Error),
+    /// The agent moved into a neighbour region.
+    Crossed(RegionCrossing),
+}
+
+/// The agent crossed from one region into another.
+///
+/// `sim` is the sim of the new region, the agent is a root agent there now
+/// and the services of `sim` are the ones to use from now on. The sim of the
+/// previous region keeps a child agent, it can be handed back with
+/// `Neighbours::add` to keep receiving what happens in that region, or be
+/// disconnected.
+pub struct RegionCrossing {
+    pub from: RegionHandle,
+    pub to: RegionHandle,
+    /// Position of the agent in the new region.
+    pub position: Vector3<f32>,
+    pub look_at: Vector3<f32>,
+    pub sim: Simulator,
+}
+
+impl fmt::Debug for RegionCrossing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RegionCrossing")
+            .field("from", &self.from)
+            .field("to", &self.to)
+            .field("position", &self.position)
+            .field("look_at", &self.look_at)
+            .finish()
+    }
+}
+
+/// Creates the message handlers of a neighbour circuit.
+pub type HandlersFn = Box<Fn(&RegionHandle) -> Handlers>;
+
+/// A crossing waiting for the sim of the new region.
+struct Crossing {
+    to: RegionHandle,
+    position: Vector3<f32>,
+    look_at: Vector3<f32>,
+    sim: Box<Future<Item = Simulator, Error = Error>>,
+}
+
+/// Manages the connections to the neighbour sims of the agent's region.
+///
+/// This is a `Stream` of `NeighbourEvent`s, it has to be polled on the
+/// reactor thread for the connections to be made.
+pub struct Neighbours {
+    /// The agent, circuit code and capabilities to connect with.
+    connect_info: ConnectInfo,
+    handlers: HandlersFn,
+    handle: Handle,
+    log: Log,
+
+    /// The region of the root agent.
+    region: RegionHandle,
+    /// `EnableSimulator` and `CrossedRegion` messages of all circuits, only
+    /// the ones of the root agent's circuit are acted upon.
+    messages: Vec<(RegionHandle, Subscription)>,
+    establish: mpsc::UnboundedReceiver<EstablishAgentCommunication>,
+    establish_tx: mpsc::UnboundedSender<EstablishAgentCommunication>,
+    disable: mpsc::UnboundedReceiver<RegionHandle>,
+    disable_tx: mpsc::UnboundedSender<RegionHandle>,
+
+    /// Regions announced by `EnableSimulator`, waiting for their seed
+    /// capability.
+    announced: HashMap<SocketAddr, RegionHandle>,
+    connecting: Vec<(RegionHandle, Box<Future<Item = Simulator, Error = Error>>)>,
+    connected: HashMap<RegionHandle, Simulator>,
+    crossing: Option<Crossing>,
+    events: VecDeque<NeighbourEvent>,
+}
+
+impl Neighbours {
+    /// Register the handlers on the handlers of the circuit of the root agent.
+    ///
+    /// `region` and `connect_info` are the ones of the root agent's sim,
+    /// `handlers` creates the handlers for the circuits to the neighbours.
+    pub fn register(
+        root_handlers: &mut Handlers,
+        region: RegionHandle,
+        connect_info: ConnectInfo,
+        handlers: HandlersFn,
+        handle: Handle,
+        log: Log,
+    ) -> Neighbours {
+        let (establish_tx, establish_rx) = mpsc::unbounded();
+        let (disable_tx, disable_rx) = mpsc::unbounded();
+
+        let mut neighbours = Neighbours {
+            connect_info: connect_info,
+            handlers: handlers,
+            handle: handle,
+            log: log,
+            region: region.clone(),
+            messages: Vec::new(),
+            establish: establish_rx,
+            establish_tx: establish_tx,
+            disable: disable_rx,
+            disable_tx: disable_tx,
+            announced: HashMap::new(),
+            connecting: Vec::new(),
+            connected: HashMap::new(),
+            crossing: None,
+            events: VecDeque::new(),
+        };
+        neighbours.register_circuit(root_handlers, region);
+        neighbours
+    }
+
+    /// Returns the region of the root agent.
+    pub fn region(&self) -> &RegionHandle {
+        &self.region
+    }
+
+    /// Returns the sim of a connected neighbour region.
+    pub fn get(&self, region: &RegionHandle) -> Option<&Simulator> {
+        self.connected.get(region)
+    }
+
+    /// Returns the handles of the connected neighbour regions.
+    pub fn regions(&self) -> Vec<RegionHandle> {
+        self.connected.keys().cloned().collect()
+    }
+
+    /// Keep a sim connected as neighbour, i.e. the one of the previous region
+    /// after `NeighbourEvent::Crossed`.
+    ///
+    /// The handlers of the sim's circuit must have been registered by this
+    /// `Neighbours`, which is the case for all sims it returns and the one
+    /// passed to `register`.
+    pub fn add(&mut self, region: RegionHandle, sim: Simulator) {
+        if let Some(old) = self.connected.insert(region, sim) {
+            let _ = old.disconnect();
+        }
+    }
+
+    /// Close all connections to neighbour sims.
+    pub fn disconnect_all(&mut self) {
+        self.announced.clear();
+        self.connecting.clear();
+        self.crossing = None;
+        let regions = self.regions();
+        for region in regions {
+            self.disconnect(region);
+        }
+    }
+
+    /// Register what is needed on the handlers of a circuit, so it keeps
+    /// working if it becomes the circuit of the root agent.
+    fn register_circuit(&mut self, handlers: &mut Handlers, region: RegionHandle) {
+        let establish_tx = Mutex::new(self.establish_tx.clone());
+        handlers.register_event(
+            "EstablishAgentCommunication",
+            Box::new(move |event: Event, _: &HandlerContext| match event {
+                Event::EstablishAgentCommunication(e) => {
+                    let _ = establish_tx.lock().unwrap().unbounded_send(e);
+                    Ok(())
+                }
+                event => Err(message_handlers::EventError {
+                    event: event,
+                    kind: message_handlers::ErrorKind::WrongHandler,
+                }),
+            }),
+        );
+
+        let disable_tx = Mutex::new(self.disable_tx.clone());
+        let disabled_region = region.clone();
+        handlers.register_type(
+            MessageType::DisableSimulator,
+            Box::new(move |_: MessageInstance, _: &HandlerContext| {
+                let _ = disable_tx
+                    .lock()
+                    .unwrap()
+                    .unbounded_send(disabled_region.clone());
+                Ok(())
+            }),
+        );
+
+        let messages = handlers.subscribe_filter(Box::new(|msg: &MessageInstance| match *msg {
+            MessageInstance::EnableSimulator(_) | MessageInstance::CrossedRegion(_) => true,
+            _ => false,
+        }));
+        self.messages.push((region, messages));
+    }
+
+    fn enable(&mut self, addr: SocketAddr, region: RegionHandle) {
+        if region == self.region || self.connected.contains_key(&region) {
+            return;
+        }
+        debug!(
+            self.log.slog_logger(),
+            "Neighbour {:?} announced at {}", region, addr
+        );
+        self.announced.insert(addr, region);
+    }
+
+    fn establish(&mut self, establish: EstablishAgentCommunication) {
+        let region = match self.announced.remove(&establish.sim_address) {
+            Some(region) => region,
+            None => {
+                debug!(
+                    self.log.slog_logger(),
+                    "EstablishAgentCommunication for unknown sim: {}", establish.sim_address
+                );
+                return;
+            }
+        };
+
+        let connect_info = ConnectInfo {
+            capabilities_seed: establish.seed_capability,
+            sim_ip: match establish.sim_address {
+                SocketAddr::V4(addr) => *addr.ip(),
+                SocketAddr::V6(_) => {
+                    let error = format_err!("IPv6 is not supported: {}", establish.sim_address);
+                    self.events.push_back(NeighbourEvent::Failed(region, error));
+                    return;
+                }
+            },
+            sim_port: establish.sim_address.port(),
+            ..self.connect_info.clone()
+        };
+
+        let mut handlers = (self.handlers)(&region);
+        self.register_circuit(&mut handlers, region.clone());
+
+        let connect = Simulator::connect_child(
+            connect_info,
+            handlers,
+            self.handle.clone(),
+            self.log.clone(),
+        );
+        self.connecting.push((region, Box::new(connect)));
+    }
+
+    fn cross(&mut self, msg: CrossedRegion) {
+        let to = RegionHandle::from_handle(msg.region_data.region_handle);
+        info!(
+            self.log.slog_logger(),
+            "Crossing from region {:?} into {:?}", self.region, to
+        );
+        self.announced.retain(|_, r| *r != to);
+        self.connecting.retain(|&(ref r, _)| *r != to);
+
+        let sim: Box<Future<Item = Simulator, Error = Error>> = match self.connected.remove(&to) {
+            Some(sim) => {
+                let promote = sim.promote_to_root();
+                Box::new(promote.map(move |()| sim))
+            }
+            // The neighbour was not connected yet, connect as root agent.
+            None => {
+                let seed = String::from_utf8_lossy(&msg.region_data.seed_capability);
+                let seed = match Url::parse(seed.trim_end_matches('\0')) {
+                    Ok(seed) => seed,
+                    Err(e) => {
+                        let error = format_err!("Invalid seed capability: {}", e);
+                        self.events.push_back(NeighbourEvent::Failed(to, error));
+                        return;
+                    }
+                };
+                let connect_info = ConnectInfo {
+                    capabilities_seed: seed,
+                    sim_ip: msg.region_data.sim_ip,
+                    sim_port: msg.region_data.sim_port,
+                    ..self.connect_info.clone()
+                };
+                let mut handlers = (self.handlers)(&to);
+                self.register_circuit(&mut handlers, to.clone());
+                Box::new(Simulator::connect(
+                    connect_info,
+                    handlers,
+                    self.handle.clone(),
+                    self.log.clone(),
+                ))
+            }
+        };
+        self.crossing = Some(Crossing {
+            to: to,
+            position: msg.info.position,
+            look_at: msg.info.look_at,
+            sim: sim,
+        });
+    }
+
+    fn disconnect(&mut self, region: RegionHandle) {
+        self.announced.retain(|_, r| *r != region);
+        self.connecting.retain(|&(ref r, _)| *r != region);
+        if let Some(sim) = self.connected.remove(&region) {
+            if let Err(e) = sim.disconnect() {
+                warn!(
+                    self.log.slog_logger(),
+                    "Closing connection to neighbour {:?} failed: {}", region, e
+                );
+            }
+            self.events.push_back(NeighbourEvent::Disconnected(region));
+        }
+    }
+
+    /// Handle the messages of all circuits, returns false once the circuit of
+    /// the root agent is closed.
+    fn poll_messages(&mut self) -> bool {
+        let mut root_open = true;
+        let mut i = 0;
+        while i < self.messages.len() {
+            let is_root = self.messages[i].0 == self.region;
+            match self.messages[i].1.poll() {
+                // Messages of child agents are dropped.
+                Ok(Async::Ready(Some(_))) if !is_root => {}
+                Ok(Async::Ready(Some(MessageInstance::EnableSimulator(msg)))) => {
+                    let info = msg.simulator_info;
+                    let addr = SocketAddr::new(info.ip.into(), info.port);
+                    self.enable(addr, RegionHandle::from_handle(info.handle));
+                }
+                Ok(Async::Ready(Some(MessageInstance::CrossedRegion(msg)))) => self.cross(msg),
+                Ok(Async::Ready(Some(_))) => {}
+                Ok(Async::Ready(None)) | Err(()) => {
+                    // The previous root is closed by the owner after crossing.
+                    if is_root && self.crossing.is_none() {
+                        root_open = false;
+                    }
+                    self.messages.remove(i);
+                }
+                Ok(Async::NotReady) => i += 1,
+            }
+        }
+        root_open
+    }
+
+    fn poll_connecting(&mut self) {
+        let mut i = 0;
+        while i < self.connecting.len() {
+            let result = self.connecting[i].1.poll();
+            match result {
+                Ok(Async::NotReady) => i += 1,
+                Ok(Async::Ready(sim)) => {
+                    let (region, _) = self.connecting.remove(i);
+                    self.connected.insert(region.clone(), sim);
+                    self.events.push_back(NeighbourEvent::Connected(region));
+                }
+                Err(e) => {
+                    let (region, _) = self.connecting.remove(i);
+                    self.events.push_back(NeighbourEvent::Failed(region, e));
+                }
+            }
+        }
+    }
+
+    fn poll_crossing(&mut self) {
+        let result = match self.crossing {
+            Some(ref mut crossing) => crossing.sim.poll(),
+            None => return,
+        };
+        match result {
+            Ok(Async::NotReady) => {}
+            Ok(Async::Ready(sim)) => {
+                let crossing = self.crossing.take().unwrap();
+                let from = ::std::mem::replace(&mut self.region, crossing.to.clone());
+                self.events
+                    .push_back(NeighbourEvent::Crossed(RegionCrossing {
+                        from: from,
+                        to: crossing.to,
+                        position: crossing.position,
+                        look_at: crossing.look_at,
+                        sim: sim,
+                    }));
+            }
+            Err(e) => {
+                let crossing = self.crossing.take().unwrap();
+                self.events
+                    .push_back(NeighbourEvent::Failed(crossing.to, e));
+            }
+        }
+    }
+}
+
+impl Stream for Neighbours {
+    type Item = NeighbourEvent;
+    type Error = ();
+
+    /// The stream ends when the circuit of the root agent is closed.
+    fn poll(&mut self) -> Poll<Option<NeighbourEvent>, ()> {
+        if !self.poll_messages() {
+            self.disconnect_all();
+            return Ok(Async::Ready(None));
+        }
+        while let Async::Ready(Some(establish)) = self.establish.poll()? {
+            self.establish(establish);
+        }
+        while let Async::Ready(Some(region)) = self.disable.poll()? {
+            self.disconnect(region);
+        }
+        self.poll_connecting();
+        self.poll_crossing();
+
+        match self.events.pop_front() {
+            Some(event) => Ok(Async::Ready(Some(event))),
+            None => Ok(Async::NotReady),
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//! Decoding of `ObjectUpdateCompressed`, which sims use for most prims.
+//!
+//! Instead of the fields of `ObjectUpdate` the update contains a packed blob
+//! of the same properties, most of which are only present if indicated by
+//! the compression flags.
+
+use messages::all::{ObjectUpdateCompressed, ObjectUpdateCompressed_ObjectData};
+use object_update::motion::{read_vector3_f32, rotation_from_vector};
+use object_update::primitive::RawShape;
+use object_update::{
+    read_texture_entry, read_uuid, ClickAction, ExtraParams, HoverText, Material, NameValue,
+    ObjectState, ObjectUpdateError, PCode, ParticleSystem, PrimFlags, Primitive, Shape, Sound,
+    TextureAnimation,
+};
+use std::io::Cursor;
+use types::{Vector3, Vector4};
+use util::bitsreader::{BytesReader, LittleEndian, ReadError};
+
+bitflags! {
+    /// Which of the optional properties are present in the data.
+    struct CompressedFlags: u32 {
+        const SCRATCH_PAD = 1 << 0;
+        const TREE = 1 << 1;
+        const HAS_TEXT = 1 << 2;
+        const HAS_PARTICLES = 1 << 3;
+        const HAS_SOUND = 1 << 4;
+        const HAS_PARENT = 1 << 5;
+        const TEXTURE_ANIMATION = 1 << 6;
+        const HAS_ANGULAR_VELOCITY = 1 << 7;
+        const HAS_NAME_VALUES = 1 << 8;
+        const MEDIA_URL = 1 << 9;
+        const HAS_PARTICLES_NEW = 1 << 10;
+    }
+}
+
+/// Size of the particle system in the legacy format.
+const PARTICLES_LEGACY_SIZE: usize = 86;
+
+impl Primitive {
+    /// Decode all objects of an `ObjectUpdateCompressed`.
+    pub fn from_object_update_compressed(
+        msg: &ObjectUpdateCompressed,
+    ) -> Vec<Result<Primitive, ObjectUpdateError>> {
+        msg.object_data
+            .iter()
+            .map(Primitive::from_compressed)
+            .collect()
+    }
+
+    pub fn from_compressed(
+        data: &ObjectUpdateCompressed_ObjectData,
+    ) -> Result<Primitive, ObjectUpdateError> {
+        let mut reader = Cursor::new(&data.data[..]);
+        let reader = &mut reader;
+
+        let full_id = read_uuid(reader)?;
+        let local_id = reader.read_bytes_u32::<LittleEndian>()?;
+        let pcode = reader.read_bytes_u8()?;
+        let state = reader.read_bytes_u8()?;
+        let crc = reader.read_bytes_u32::<LittleEndian>()?;
+        let material = reader.read_bytes_u8()?;
+        let click_action = reader.read_bytes_u8()?;
+        let scale = read_vector3_f32(reader)?;
+        let position = read_vector3_f32(reader)?;
+        let rotation = rotation_from_vector(read_vector3_f32(reader)?);
+        let flags = CompressedFlags::from_bits_truncate(reader.read_bytes_u32::<LittleEndian>()?);
+        let owner_id = read_uuid(reader)?;
+
+        let angular_velocity = if flags.contains(CompressedFlags::HAS_ANGULAR_VELOCITY) {
+            read_vector3_f32(reader)?
+        } else {
+            Vector3::new(0., 0., 0.)
+        };
+        let parent_id = if flags.contains(CompressedFlags::HAS_PARENT) {
+            reader.read_bytes_u32::<LittleEndian>()?
+        } else {
+            0
+        };
+        // The tree species or the scratch pad, like the `data` of an
+        // `ObjectUpdate`.
+        let generic_data = if flags.contains(CompressedFlags::TREE) {
+            vec![reader.read_bytes_u8()?]
+        } else if flags.contains(CompressedFlags::SCRATCH_PAD) {
+            let size = reader.read_bytes_u8()? as usize;
+            read_vec(reader, size)?
+        } else {
+            Vec::new()
+        };
+        let text = if flags.contains(CompressedFlags::HAS_TEXT) {
+            let text = read_cstring(reader)?;
+            let c = read_color(reader)?;
+            Some(HoverText {
+                text: text,
+                // The alpha is sent inverted.
+                color: Vector4::new(c.x, c.y, c.z, 255 - c.w),
+            })
+        } else {
+            None
+        };
+        let media_url = if flags.contains(CompressedFlags::MEDIA_URL) {
+            Some(read_cstring(reader)?)
+        } else {
+            None
+        };
+        let particle_system = if flags.contains(CompressedFlags::HAS_PARTICLES_NEW) {
+            ParticleSystem::read(&read_sized_particles(reader)?)?
+        } else if flags.contains(CompressedFlags::HAS_PARTICLES) {
+            ParticleSystem::read(&read_vec(reader, PARTICLES_LEGACY_SIZE)?)?
+        } else {
+            None
+        };
+        let extra_params = ExtraParams::read(&read_extra_params(reader)?)?;
+        let sound = if flags.contains(CompressedFlags::HAS_SOUND) {
+            Some(Sound {
+                sound_id: read_uuid(reader)?,
+                owner_id: owner_id,
+                gain: reader.read_bytes_f32::<LittleEndian>()?,
+                flags: reader.read_bytes_u8()?,
+                radius: reader.read_bytes_f32::<LittleEndian>()?,
+            })
+        } else {
+            None
+        };
+        let name_values = if flags.contains(CompressedFlags::HAS_NAME_VALUES) {
+            NameValue::parse(read_cstring(reader)?.as_bytes())
+        } else {
+            Vec::new()
+        };
+        let shape = Shape::from_raw(&RawShape::read_compressed(reader)?);
+        let texture_entry = {
+            let size = reader.read_bytes_u32::<LittleEndian>()? as usize;
+            if size == 0 {
+                None
+            } else {
+                Some(read_texture_entry(&read_vec(reader, size)?)?)
+            }
+        };
+        let texture_animation = if flags.contains(CompressedFlags::TEXTURE_ANIMATION) {
+            let size = reader.read_bytes_u32::<LittleEndian>()? as usize;
+            TextureAnimation::read(&read_vec(reader, size)?)?
+        } else {
+            None
+        };
+
+        Ok(Primitive {
+            local_id: local_id,
+            full_id: full_id,
+            parent_id: parent_id,
+            crc: crc,
+            pcode: PCode::from_u8(pcode),
+            state: state,
+            material: Material::from_u8(material),
+            click_action: ClickAction::from_u8(click_action),
+            scale: scale,
+            motion: ObjectState {
+                collision_plane: None,
+                position: position,
+                velocity: Vector3::new(0., 0., 0.),
+                acceleration: Vector3::new(0., 0., 0.),
+                rotation: rotation,
+                angular_velocity: angular_velocity,
+            },
+            flags: PrimFlags::from_bits_truncate(data.update_flags),
+            shape: shape,
+            texture_entry: texture_entry,
+            texture_animation: texture_animation,
+            name_values: name_values,
+            data: generic_data,
+            text: text,
+            media_url: media_url,
+            particle_system: particle_system,
+            extra_params: extra_params,
+            sound: sound,
+            joint: None,
+        })
+    }
+}
+
+fn read_color<R: BytesReader>(reader: &mut R) -> Result<Vector4<u8>, ReadError> {
+    Ok(Vector4::new(
+        reader.read_bytes_u8()?,
+        reader.read_bytes_u8()?,
+        reader.read_bytes_u8()?,
+        reader.read_bytes_u8()?,
+    ))
+}
+
+fn read_vec(reader: &mut Cursor<&[u8]>, size: usize) -> Result<Vec<u8>, ReadError> {
+    if size > reader.get_ref().len() - reader.position() as usize {
+        return Err(ReadError::UnexpectedEnd);
+    }
+    let mut data = vec![0u8; size];
+    reader.read_bytes_exact(&mut data)?;
+    Ok(data)
+}
+
+/// Read a nul terminated string.
+fn read_cstring<R: BytesReader>(reader: &mut R) -> Result<String, ReadError> {
+    let mut bytes = Vec::new();
+    loop {
+        match reader.read_bytes_u8()? {
+            0 => break,
+            byte => bytes.push(byte),
+        }
+    }
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Read the particle system with the sizes of its parts, see
+/// `ParticleSystem::read`.
+fn read_sized_particles(reader: &mut Cursor<&[u8]>) -> Result<Vec<u8>, ReadError> {
+    let start = reader.position() as usize;
+    for _ in 0..2 {
+        let size = reader.read_bytes_u32::<LittleEndian>()? as usize;
+        read_vec(reader, size)?;
+    }
+    let end = reader.position() as usize;
+    Ok(reader.get_ref()[start..end].to_vec())
+}
+
+/// Read the extra params, which are not prefixed with their total size.
+fn read_extra_params(reader: &mut Cursor<&[u8]>) -> Result<Vec<u8>, ReadError> {
+    let start = reader.position() as usize;
+    let count = reader.read_bytes_u8()?;
+    for _ in 0..count {
+        reader.read_bytes_u16::<LittleEndian>()?;
+        let size = reader.read_bytes_u32::<LittleEndian>()? as usize;
+        read_vec(reader, size)?;
+    }
+    let end = reader.position() as usize;
+    Ok(reader.get_ref()[start..end].to_vec())
+}
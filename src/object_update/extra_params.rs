@@ -0,0 +1,235 @@
+use object_update::{read_uuid, ObjectUpdateError};
+use std::io::Cursor;
+use types::{Uuid, Vector3, Vector4};
+use util::bitsreader::{BytesReader, LittleEndian, ReadError};
+
+const PARAMS_FLEXIBLE: u16 = 0x10;
+const PARAMS_LIGHT: u16 = 0x20;
+const PARAMS_SCULPT: u16 = 0x30;
+const PARAMS_LIGHT_IMAGE: u16 = 0x40;
+
+/// Additional properties of a prim, each of them is optional.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExtraParams {
+    pub flexible: Option<FlexibleParams>,
+    pub light: Option<LightParams>,
+    pub sculpt: Option<SculptParams>,
+    pub light_image: Option<LightImageParams>,
+    /// Parameters not decoded by this crate as `(type, data)`.
+    pub other: Vec<(u16, Vec<u8>)>,
+}
+
+/// The physics of flexible prims.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlexibleParams {
+    pub softness: u8,
+    pub tension: f32,
+    pub drag: f32,
+    pub gravity: f32,
+    pub wind: f32,
+    pub force: Vector3<f32>,
+}
+
+/// A prim emitting light.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LightParams {
+    /// RGB color, the alpha is the intensity.
+    pub color: Vector4<u8>,
+    pub radius: f32,
+    pub cutoff: f32,
+    pub falloff: f32,
+}
+
+/// The light projected by a prim.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LightImageParams {
+    pub texture_id: Uuid,
+    /// Field of view, focus and ambiance.
+    pub params: Vector3<f32>,
+}
+
+/// The shape of a sculpted prim or mesh.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SculptParams {
+    /// The sculpt map or the mesh asset.
+    pub texture_id: Uuid,
+    pub sculpt_type: SculptType,
+    pub invert: bool,
+    pub mirror: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SculptType {
+    None,
+    Sphere,
+    Torus,
+    Plane,
+    Cylinder,
+    Mesh,
+    Unknown(u8),
+}
+
+impl SculptType {
+    fn from_u8(value: u8) -> SculptType {
+        match value {
+            0 => SculptType::None,
+            1 => SculptType::Sphere,
+            2 => SculptType::Torus,
+            3 => SculptType::Plane,
+            4 => SculptType::Cylinder,
+            5 => SculptType::Mesh,
+            other => SculptType::Unknown(other),
+        }
+    }
+}
+
+impl ExtraParams {
+    /// Read the `extra_params` of an update.
+    ///
+    /// They start with the number of parameters, each parameter is its type,
+    /// size and data.
+    pub fn read(data: &[u8]) -> Result<ExtraParams, ObjectUpdateError> {
+        let mut params = ExtraParams::default();
+        if data.is_empty() {
+            return Ok(params);
+        }
+
+        let mut reader = Cursor::new(data);
+        let count = reader.read_bytes_u8()?;
+        for _ in 0..count {
+            let param_type = reader.read_bytes_u16::<LittleEndian>()?;
+            let size = reader.read_bytes_u32::<LittleEndian>()? as usize;
+            if size > data.len() - reader.position() as usize {
+                return Err(ReadError::UnexpectedEnd.into());
+            }
+            let mut param = vec![0u8; size];
+            reader.read_bytes_exact(&mut param)?;
+
+            let invalid = |_: ReadError| ObjectUpdateError::InvalidExtraParam(param_type, size);
+            let mut param_reader = Cursor::new(&param[..]);
+            match param_type {
+                PARAMS_FLEXIBLE => {
+                    params.flexible = Some(read_flexible(&mut param_reader).map_err(invalid)?)
+                }
+                PARAMS_LIGHT => {
+                    params.light = Some(read_light(&mut param_reader).map_err(invalid)?)
+                }
+                PARAMS_SCULPT => {
+                    params.sculpt = Some(read_sculpt(&mut param_reader).map_err(invalid)?)
+                }
+                PARAMS_LIGHT_IMAGE => {
+                    params.light_image = Some(read_light_image(&mut param_reader).map_err(invalid)?)
+                }
+                _ => params.other.push((param_type, param.clone())),
+            }
+        }
+        Ok(params)
+    }
+}
+
+fn read_flexible<R: BytesReader>(reader: &mut R) -> Result<FlexibleParams, ReadError> {
+    // The two bits of the softness are stored in the high bits of the
+    // tension and drag.
+    let tension = reader.read_bytes_u8()?;
+    let drag = reader.read_bytes_u8()?;
+    Ok(FlexibleParams {
+        softness: ((tension & 0x80) >> 6) | ((drag & 0x80) >> 7),
+        tension: (tension & 0x7f) as f32 / 10.,
+        drag: (drag & 0x7f) as f32 / 10.,
+        gravity: reader.read_bytes_u8()? as f32 / 10. - 10.,
+        wind: reader.read_bytes_u8()? as f32 / 10.,
+        force: Vector3::new(
+            reader.read_bytes_f32::<LittleEndian>()?,
+            reader.read_bytes_f32::<LittleEndian>()?,
+            reader.read_bytes_f32::<LittleEndian>()?,
+        ),
+    })
+}
+
+fn read_light<R: BytesReader>(reader: &mut R) -> Result<LightParams, ReadError> {
+    Ok(LightParams {
+        color: Vector4::new(
+            reader.read_bytes_u8()?,
+            reader.read_bytes_u8()?,
+            reader.read_bytes_u8()?,
+            reader.read_bytes_u8()?,
+        ),
+        radius: reader.read_bytes_f32::<LittleEndian>()?,
+        cutoff: reader.read_bytes_f32::<LittleEndian>()?,
+        falloff: reader.read_bytes_f32::<LittleEndian>()?,
+    })
+}
+
+fn read_sculpt<R: BytesReader>(reader: &mut R) -> Result<SculptParams, ReadError> {
+    let texture_id = read_uuid(reader)?;
+    let sculpt_type = reader.read_bytes_u8()?;
+    Ok(SculptParams {
+        texture_id: texture_id,
+        sculpt_type: SculptType::from_u8(sculpt_type & 0x07),
+        invert: sculpt_type & 0x40 != 0,
+        mirror: sculpt_type & 0x80 != 0,
+    })
+}
+
+fn read_light_image<R: BytesReader>(reader: &mut R) -> Result<LightImageParams, ReadError> {
+    Ok(LightImageParams {
+        texture_id: read_uuid(reader)?,
+        params: Vector3::new(
+            reader.read_bytes_f32::<LittleEndian>()?,
+            reader.read_bytes_f32::<LittleEndian>()?,
+            reader.read_bytes_f32::<LittleEndian>()?,
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_light_and_sculpt() {
+        let sculpt_map = Uuid::parse_str("be293869-d0d9-0a69-5989-ad27f1946fd4").unwrap();
+
+        let mut data = vec![3];
+        data.extend_from_slice(&[0x20, 0, 16, 0, 0, 0]);
+        data.extend_from_slice(&[255, 128, 0, 255]);
+        data.extend_from_slice(&[0, 0, 0x20, 0x41]); // 10.
+        data.extend_from_slice(&[0, 0, 0, 0]);
+        data.extend_from_slice(&[0, 0, 0x80, 0x3f]); // 1.
+        data.extend_from_slice(&[0x30, 0, 17, 0, 0, 0]);
+        data.extend_from_slice(sculpt_map.as_bytes());
+        data.push(0x40 | 5);
+        data.extend_from_slice(&[0x70, 0, 2, 0, 0, 0, 1, 2]);
+
+        let params = ExtraParams::read(&data).unwrap();
+        assert_eq!(
+            params.light,
+            Some(LightParams {
+                color: Vector4::new(255, 128, 0, 255),
+                radius: 10.,
+                cutoff: 0.,
+                falloff: 1.,
+            })
+        );
+        assert_eq!(
+            params.sculpt,
+            Some(SculptParams {
+                texture_id: sculpt_map,
+                sculpt_type: SculptType::Mesh,
+                invert: true,
+                mirror: false,
+            })
+        );
+        assert_eq!(params.flexible, None);
+        assert_eq!(params.other, vec![(0x70, vec![1, 2])]);
+    }
+
+    #[test]
+    fn read_truncated() {
+        assert_eq!(ExtraParams::read(&[]).unwrap(), ExtraParams::default());
+        match ExtraParams::read(&[1, 0x20, 0, 4, 0, 0, 0, 1, 2, 3, 4]) {
+            Err(ObjectUpdateError::InvalidExtraParam(0x20, 4)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Decoding of the object updates sent by the sim.
+//!
+//! Everything in a region, prims, avatars and trees, is an object, which the
+//! sim describes with an `ObjectUpdate` for each object, see `Primitive`.
+//! Most prims are sent with the more compact `ObjectUpdateCompressed`
+//! instead, which is decoded into the same model. Afterwards movement is
+//! sent with `ImprovedTerseObjectUpdate`, see `TerseUpdate`.
+
+mod compressed;
+mod extra_params;
+mod motion;
+mod particles;
+mod primitive;
+mod terse;
+mod texture_entry;
+
+pub use self::extra_params::{
+    ExtraParams, FlexibleParams, LightImageParams, LightParams, SculptParams, SculptType,
+};
+pub use self::motion::ObjectState;
+pub use self::particles::ParticleSystem;
+pub use self::primitive::{
+    ClickAction, HoleType, HoverText, Joint, Material, NameValue, PCode, PathCurve, PathParams,
+    PrimFlags, Primitive, ProfileCurve, ProfileParams, Shape, Sound,
+};
+pub use self::terse::TerseUpdate;
+pub use self::texture_entry::{read_texture_entry, FaceProperties, TextureAnimation, TextureEntry};
+
+use types::Uuid;
+use util::bitsreader::{BytesReader, LittleEndian, ReadError};
+
+#[derive(Debug, Fail)]
+pub enum ObjectUpdateError {
+    #[fail(display = "Reading the input failed: {}", 0)]
+    BitReader(#[cause] ReadError),
+
+    #[fail(display = "Invalid size of the object data: {}", 0)]
+    InvalidObjectDataSize(usize),
+
+    #[fail(display = "Invalid extra parameter {:#x} of size {}", 0, 1)]
+    InvalidExtraParam(u16, usize),
+
+    #[fail(display = "Invalid particle system of size {}", 0)]
+    InvalidParticleSystem(usize),
+}
+
+impl From<ReadError> for ObjectUpdateError {
+    fn from(e: ReadError) -> Self {
+        ObjectUpdateError::BitReader(e)
+    }
+}
+
+/// Map a quantized value back to the range `[lower, upper]`.
+///
+/// Values closer to zero than the quantization step are snapped to zero, so
+/// that zero survives the round trip.
+#[inline]
+fn u16_to_float(value: u16, lower: f32, upper: f32) -> f32 {
+    debug_assert!(lower < upper);
+    let delta = upper - lower;
+    let fvalue = (value as f32) / 65535. * delta + lower;
+
+    if fvalue.abs() < delta / 65535. {
+        0.
+    } else {
+        fvalue
+    }
+}
+
+/// Quantize a value of the range `[lower, upper]` to a `u16`.
+#[inline]
+fn float_to_u16(value: f32, lower: f32, upper: f32) -> u16 {
+    debug_assert!(lower < upper);
+    let value = value.max(lower).min(upper);
+    ((value - lower) / (upper - lower) * 65535.).floor() as u16
+}
+
+/// Like `u16_to_float` for values quantized to a single byte.
+#[inline]
+fn u8_to_float(value: u8, lower: f32, upper: f32) -> f32 {
+    debug_assert!(lower < upper);
+    let delta = upper - lower;
+    let fvalue = (value as f32) / 255. * delta + lower;
+
+    if fvalue.abs() < delta / 255. {
+        0.
+    } else {
+        fvalue
+    }
+}
+
+fn read_uuid<R: BytesReader>(reader: &mut R) -> Result<Uuid, ReadError> {
+    let mut bytes = [0u8; 16];
+    reader.read_bytes_exact(&mut bytes)?;
+    Ok(Uuid::from_bytes(bytes))
+}
+
+/// Decode a string field of a message, which may be nul terminated.
+fn read_string(raw: &[u8]) -> String {
+    String::from_utf8_lossy(raw)
+        .trim_end_matches('\0')
+        .to_string()
+}
+
+/// Read a value quantized to the range `[-range_r, range_r]`.
+#[inline]
+fn read_u16f<R: BytesReader>(reader: &mut R, range_r: f32) -> Result<f32, ReadError> {
+    Ok(u16_to_float(
+        reader.read_bytes_u16::<LittleEndian>()?,
+        -range_r,
+        range_r,
+    ))
+}
+
+/// Inverse of `read_u16f`.
+#[inline]
+fn write_u16f(buf: &mut Vec<u8>, value: f32, range_r: f32) {
+    let value = float_to_u16(value, -range_r, range_r);
+    buf.extend_from_slice(&[value as u8, (value >> 8) as u8]);
+}
@@ -0,0 +1,207 @@
+use object_update::{u16_to_float, u8_to_float, ObjectUpdateError};
+use std::io::Cursor;
+use types::{Quaternion, Vector3, Vector4};
+use util::bitsreader::{BytesReader, LittleEndian, ReadError};
+
+/// Position and movement of an object.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjectState {
+    /// The plane avatars are standing on.
+    pub collision_plane: Option<Vector4<f32>>,
+    /// Region local position, or relative to the parent for children.
+    pub position: Vector3<f32>,
+    pub velocity: Vector3<f32>,
+    pub acceleration: Vector3<f32>,
+    pub rotation: Quaternion<f32>,
+    pub angular_velocity: Vector3<f32>,
+}
+
+impl ObjectState {
+    /// Decode the `object_data` of an `ObjectUpdate`.
+    ///
+    /// Depending on its size the motion is encoded as `f32`, `u16` or `u8`
+    /// values, optionally preceded by the collision plane.
+    pub fn read(data: &[u8]) -> Result<ObjectState, ObjectUpdateError> {
+        let mut reader = Cursor::new(data);
+        let reader = &mut reader;
+        match data.len() {
+            76 => {
+                let collision_plane = read_vector4_f32(reader)?;
+                read_f32_state(reader, Some(collision_plane))
+            }
+            60 => read_f32_state(reader, None),
+            48 => {
+                let collision_plane = read_vector4_f32(reader)?;
+                read_u16_state(reader, Some(collision_plane))
+            }
+            32 => read_u16_state(reader, None),
+            16 => read_u8_state(reader),
+            len => Err(ObjectUpdateError::InvalidObjectDataSize(len)),
+        }
+    }
+}
+
+fn read_f32_state<R: BytesReader>(
+    reader: &mut R,
+    collision_plane: Option<Vector4<f32>>,
+) -> Result<ObjectState, ObjectUpdateError> {
+    Ok(ObjectState {
+        collision_plane: collision_plane,
+        position: read_vector3_f32(reader)?,
+        velocity: read_vector3_f32(reader)?,
+        acceleration: read_vector3_f32(reader)?,
+        rotation: rotation_from_vector(read_vector3_f32(reader)?),
+        angular_velocity: read_vector3_f32(reader)?,
+    })
+}
+
+fn read_u16_state<R: BytesReader>(
+    reader: &mut R,
+    collision_plane: Option<Vector4<f32>>,
+) -> Result<ObjectState, ObjectUpdateError> {
+    Ok(ObjectState {
+        collision_plane: collision_plane,
+        position: read_vector3_u16(reader, -0.5 * 256., 1.5 * 256.)?,
+        velocity: read_vector3_u16(reader, -256., 256.)?,
+        acceleration: read_vector3_u16(reader, -256., 256.)?,
+        rotation: read_quaternion_u16(reader)?,
+        angular_velocity: read_vector3_u16(reader, -256., 256.)?,
+    })
+}
+
+fn read_u8_state<R: BytesReader>(reader: &mut R) -> Result<ObjectState, ObjectUpdateError> {
+    let mut read = |lower, upper| -> Result<f32, ReadError> {
+        Ok(u8_to_float(reader.read_bytes_u8()?, lower, upper))
+    };
+    let position = Vector3::new(read(-256., 256.)?, read(-256., 256.)?, read(-256., 256.)?);
+    let velocity = Vector3::new(read(-256., 256.)?, read(-256., 256.)?, read(-256., 256.)?);
+    let acceleration = Vector3::new(read(-256., 256.)?, read(-256., 256.)?, read(-256., 256.)?);
+    let (x, y, z, w) = (
+        read(-1., 1.)?,
+        read(-1., 1.)?,
+        read(-1., 1.)?,
+        read(-1., 1.)?,
+    );
+    let angular_velocity = Vector3::new(read(-256., 256.)?, read(-256., 256.)?, read(-256., 256.)?);
+    Ok(ObjectState {
+        collision_plane: None,
+        position: position,
+        velocity: velocity,
+        acceleration: acceleration,
+        rotation: Quaternion::new(w, x, y, z),
+        angular_velocity: angular_velocity,
+    })
+}
+
+/// Complete the rotation of which only the vector part was sent, which is
+/// enough since it is normalized.
+pub(super) fn rotation_from_vector(v: Vector3<f32>) -> Quaternion<f32> {
+    let w = (1. - v.norm_squared()).max(0.).sqrt();
+    Quaternion::new(w, v.x, v.y, v.z)
+}
+
+pub(super) fn read_vector3_f32<R: BytesReader>(reader: &mut R) -> Result<Vector3<f32>, ReadError> {
+    Ok(Vector3::new(
+        reader.read_bytes_f32::<LittleEndian>()?,
+        reader.read_bytes_f32::<LittleEndian>()?,
+        reader.read_bytes_f32::<LittleEndian>()?,
+    ))
+}
+
+pub(super) fn read_vector4_f32<R: BytesReader>(reader: &mut R) -> Result<Vector4<f32>, ReadError> {
+    Ok(Vector4::new(
+        reader.read_bytes_f32::<LittleEndian>()?,
+        reader.read_bytes_f32::<LittleEndian>()?,
+        reader.read_bytes_f32::<LittleEndian>()?,
+        reader.read_bytes_f32::<LittleEndian>()?,
+    ))
+}
+
+fn read_vector3_u16<R: BytesReader>(
+    reader: &mut R,
+    lower: f32,
+    upper: f32,
+) -> Result<Vector3<f32>, ReadError> {
+    Ok(Vector3::new(
+        u16_to_float(reader.read_bytes_u16::<LittleEndian>()?, lower, upper),
+        u16_to_float(reader.read_bytes_u16::<LittleEndian>()?, lower, upper),
+        u16_to_float(reader.read_bytes_u16::<LittleEndian>()?, lower, upper),
+    ))
+}
+
+/// Read a rotation quantized to four `u16`, in the order x, y, z, w.
+fn read_quaternion_u16<R: BytesReader>(reader: &mut R) -> Result<Quaternion<f32>, ReadError> {
+    let v = read_vector3_u16(reader, -1., 1.)?;
+    let w = u16_to_float(reader.read_bytes_u16::<LittleEndian>()?, -1., 1.);
+    Ok(Quaternion::new(w, v.x, v.y, v.z))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_f32s(data: &mut Vec<u8>, values: &[f32]) {
+        for value in values {
+            let bits = value.to_bits();
+            data.extend_from_slice(&[
+                bits as u8,
+                (bits >> 8) as u8,
+                (bits >> 16) as u8,
+                (bits >> 24) as u8,
+            ]);
+        }
+    }
+
+    #[test]
+    fn read_f32_motion() {
+        let mut data = Vec::new();
+        write_f32s(&mut data, &[0., 0., 1., -24.5]);
+        write_f32s(&mut data, &[128., 64., 25.5]);
+        write_f32s(&mut data, &[1., 0., 0.]);
+        write_f32s(&mut data, &[0., 0., -9.8]);
+        write_f32s(&mut data, &[0., 0., 0.6]);
+        write_f32s(&mut data, &[0., 0., 0.5]);
+        assert_eq!(data.len(), 76);
+
+        let state = ObjectState::read(&data).unwrap();
+        assert_eq!(state.collision_plane, Some(Vector4::new(0., 0., 1., -24.5)));
+        assert_eq!(state.position, Vector3::new(128., 64., 25.5));
+        assert_eq!(state.velocity, Vector3::new(1., 0., 0.));
+        assert_eq!(state.acceleration, Vector3::new(0., 0., -9.8));
+        assert!((state.rotation.coords.w - 0.8).abs() < 1e-6);
+        assert_eq!(state.angular_velocity, Vector3::new(0., 0., 0.5));
+
+        let state = ObjectState::read(&data[16..]).unwrap();
+        assert_eq!(state.collision_plane, None);
+        assert_eq!(state.position, Vector3::new(128., 64., 25.5));
+    }
+
+    #[test]
+    fn read_u16_motion() {
+        let mut data = Vec::new();
+        // Position 128, 0, 384.
+        data.extend_from_slice(&[0x00, 0x80, 0xff, 0x3f, 0xff, 0xff]);
+        // No velocity and acceleration.
+        data.extend_from_slice(&[0xff, 0x7f, 0xff, 0x7f, 0xff, 0x7f]);
+        data.extend_from_slice(&[0xff, 0x7f, 0xff, 0x7f, 0xff, 0x7f]);
+        // Identity rotation.
+        data.extend_from_slice(&[0xff, 0x7f, 0xff, 0x7f, 0xff, 0x7f, 0xff, 0xff]);
+        data.extend_from_slice(&[0xff, 0x7f, 0xff, 0x7f, 0xff, 0x7f]);
+        assert_eq!(data.len(), 32);
+
+        let state = ObjectState::read(&data).unwrap();
+        assert!((state.position - Vector3::new(128., 0., 384.)).norm() < 0.01);
+        assert_eq!(state.velocity, Vector3::new(0., 0., 0.));
+        assert_eq!(state.acceleration, Vector3::new(0., 0., 0.));
+        assert_eq!(state.rotation, Quaternion::new(1., 0., 0., 0.));
+        assert_eq!(state.angular_velocity, Vector3::new(0., 0., 0.));
+    }
+
+    #[test]
+    fn invalid_size() {
+        match ObjectState::read(&[0u8; 20]) {
+            Err(ObjectUpdateError::InvalidObjectDataSize(20)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use object_update::{read_uuid, ObjectUpdateError};
+use std::io::Cursor;
+use types::{Uuid, Vector2, Vector3, Vector4};
+use util::bitsreader::{BytesReader, LittleEndian, ReadError};
+
+/// Size of the particle system block as sent by older sims, without the
+/// sizes of the system and particle parts.
+const LEGACY_BLOCK_SIZE: usize = 86;
+const SYSTEM_DATA_SIZE: usize = 68;
+const PARTICLE_DATA_SIZE: usize = 18;
+
+/// A particle source.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParticleSystem {
+    pub crc: u32,
+    pub flags: u32,
+    /// DROP, EXPLODE, ANGLE and ANGLE_CONE.
+    pub pattern: u8,
+    /// How long the source emits particles, 0 for forever.
+    pub max_age: f32,
+    pub start_age: f32,
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+    pub burst_rate: f32,
+    pub burst_radius: f32,
+    pub burst_speed_min: f32,
+    pub burst_speed_max: f32,
+    pub burst_part_count: u8,
+    pub angular_velocity: Vector3<f32>,
+    pub acceleration: Vector3<f32>,
+    pub texture_id: Uuid,
+    pub target_id: Uuid,
+
+    pub part_flags: u32,
+    pub part_max_age: f32,
+    pub part_start_color: Vector4<u8>,
+    pub part_end_color: Vector4<u8>,
+    pub part_start_scale: Vector2<f32>,
+    pub part_end_scale: Vector2<f32>,
+}
+
+impl ParticleSystem {
+    /// Read the `ps_block` of an update, which is empty if the object has
+    /// no particle system.
+    pub fn read(data: &[u8]) -> Result<Option<ParticleSystem>, ObjectUpdateError> {
+        if data.is_empty() {
+            return Ok(None);
+        }
+        let invalid = |_: ReadError| ObjectUpdateError::InvalidParticleSystem(data.len());
+
+        let mut reader = Cursor::new(data);
+        if data.len() == LEGACY_BLOCK_SIZE {
+            return read_system(&mut reader).map(Some).map_err(invalid);
+        }
+
+        // Newer sims prefix both parts with their size, the particle part
+        // may be followed by glow and blend parameters.
+        let system_size = reader.read_bytes_u32::<LittleEndian>().map_err(invalid)? as usize;
+        if system_size != SYSTEM_DATA_SIZE || data.len() < 8 + SYSTEM_DATA_SIZE {
+            return Err(invalid(ReadError::UnexpectedEnd));
+        }
+        let system_data = &data[4..4 + SYSTEM_DATA_SIZE];
+        let mut part_reader = Cursor::new(&data[4 + SYSTEM_DATA_SIZE..]);
+        let part_size = part_reader
+            .read_bytes_u32::<LittleEndian>()
+            .map_err(invalid)? as usize;
+        if part_size < PARTICLE_DATA_SIZE || part_size + 8 + SYSTEM_DATA_SIZE > data.len() {
+            return Err(invalid(ReadError::UnexpectedEnd));
+        }
+
+        let mut joined = system_data.to_vec();
+        joined.extend_from_slice(
+            &data[8 + SYSTEM_DATA_SIZE..8 + SYSTEM_DATA_SIZE + PARTICLE_DATA_SIZE],
+        );
+        read_system(&mut Cursor::new(&joined[..]))
+            .map(Some)
+            .map_err(invalid)
+    }
+}
+
+/// Read an unsigned fixed point number.
+fn read_fixed_u16<R: BytesReader>(reader: &mut R, frac_bits: u32) -> Result<f32, ReadError> {
+    Ok(reader.read_bytes_u16::<LittleEndian>()? as f32 / (1 << frac_bits) as f32)
+}
+
+fn read_fixed_u8<R: BytesReader>(reader: &mut R, frac_bits: u32) -> Result<f32, ReadError> {
+    Ok(reader.read_bytes_u8()? as f32 / (1 << frac_bits) as f32)
+}
+
+/// Read a signed fixed point number with 8 integer and 7 fractional bits,
+/// stored with an offset instead of two's complement.
+fn read_fixed_s8_7<R: BytesReader>(reader: &mut R) -> Result<f32, ReadError> {
+    Ok(read_fixed_u16(reader, 7)? - 256.)
+}
+
+fn read_fixed_vector<R: BytesReader>(reader: &mut R) -> Result<Vector3<f32>, ReadError> {
+    Ok(Vector3::new(
+        read_fixed_s8_7(reader)?,
+        read_fixed_s8_7(reader)?,
+        read_fixed_s8_7(reader)?,
+    ))
+}
+
+fn read_color<R: BytesReader>(reader: &mut R) -> Result<Vector4<u8>, ReadError> {
+    Ok(Vector4::new(
+        reader.read_bytes_u8()?,
+        reader.read_bytes_u8()?,
+        reader.read_bytes_u8()?,
+        reader.read_bytes_u8()?,
+    ))
+}
+
+fn read_system<R: BytesReader>(reader: &mut R) -> Result<ParticleSystem, ReadError> {
+    Ok(ParticleSystem {
+        crc: reader.read_bytes_u32::<LittleEndian>()?,
+        flags: reader.read_bytes_u32::<LittleEndian>()?,
+        pattern: reader.read_bytes_u8()?,
+        max_age: read_fixed_u16(reader, 8)?,
+        start_age: read_fixed_u16(reader, 8)?,
+        inner_angle: read_fixed_u8(reader, 5)?,
+        outer_angle: read_fixed_u8(reader, 5)?,
+        burst_rate: read_fixed_u16(reader, 8)?,
+        burst_radius: read_fixed_u16(reader, 8)?,
+        burst_speed_min: read_fixed_u16(reader, 8)?,
+        burst_speed_max: read_fixed_u16(reader, 8)?,
+        burst_part_count: reader.read_bytes_u8()?,
+        angular_velocity: read_fixed_vector(reader)?,
+        acceleration: read_fixed_vector(reader)?,
+        texture_id: read_uuid(reader)?,
+        target_id: read_uuid(reader)?,
+
+        part_flags: reader.read_bytes_u32::<LittleEndian>()?,
+        part_max_age: read_fixed_u16(reader, 8)?,
+        part_start_color: read_color(reader)?,
+        part_end_color: read_color(reader)?,
+        part_start_scale: Vector2::new(read_fixed_u8(reader, 5)?, read_fixed_u8(reader, 5)?),
+        part_end_scale: Vector2::new(read_fixed_u8(reader, 5)?, read_fixed_u8(reader, 5)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy_block() -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&[1, 0, 0, 0]); // crc
+        data.extend_from_slice(&[0, 0, 0, 0]); // flags
+        data.push(0x02); // EXPLODE
+        data.extend_from_slice(&[0, 0x05, 0, 0]); // max age 5, start age 0
+        data.extend_from_slice(&[0, 0x20]); // angles 0 and 1
+        data.extend_from_slice(&[0x80, 0, 0, 0x01, 0, 0x02, 0, 0x04]); // burst
+        data.push(10);
+        // No angular velocity, falling particles.
+        data.extend_from_slice(&[0, 0x80, 0, 0x80, 0, 0x80]);
+        data.extend_from_slice(&[0, 0x80, 0, 0x80, 0, 0x7f]);
+        data.extend_from_slice(&[0; 32]); // texture and target
+        data.extend_from_slice(&[0x03, 0, 0, 0]); // part flags
+        data.extend_from_slice(&[0x80, 0x02]); // max age 2.5
+        data.extend_from_slice(&[255, 0, 0, 255, 0, 0, 255, 0]);
+        data.extend_from_slice(&[0x20, 0x20, 0x08, 0x08]);
+        data
+    }
+
+    #[test]
+    fn read_legacy() {
+        let data = legacy_block();
+        assert_eq!(data.len(), LEGACY_BLOCK_SIZE);
+
+        let system = ParticleSystem::read(&data).unwrap().unwrap();
+        assert_eq!(system.crc, 1);
+        assert_eq!(system.pattern, 2);
+        assert_eq!(system.max_age, 5.);
+        assert_eq!(system.outer_angle, 1.);
+        assert_eq!(system.burst_rate, 0.5);
+        assert_eq!(system.burst_radius, 1.);
+        assert_eq!(system.burst_speed_min, 2.);
+        assert_eq!(system.burst_speed_max, 4.);
+        assert_eq!(system.burst_part_count, 10);
+        assert_eq!(system.angular_velocity, Vector3::new(0., 0., 0.));
+        assert_eq!(system.acceleration, Vector3::new(0., 0., -2.));
+        assert_eq!(system.part_flags, 3);
+        assert_eq!(system.part_max_age, 2.5);
+        assert_eq!(system.part_start_color, Vector4::new(255, 0, 0, 255));
+        assert_eq!(system.part_end_scale, Vector2::new(0.25, 0.25));
+    }
+
+    #[test]
+    fn read_sized() {
+        let legacy = legacy_block();
+        let mut data = vec![68, 0, 0, 0];
+        data.extend_from_slice(&legacy[..68]);
+        data.extend_from_slice(&[20, 0, 0, 0]);
+        data.extend_from_slice(&legacy[68..]);
+        // Glow.
+        data.extend_from_slice(&[0, 0]);
+
+        let system = ParticleSystem::read(&data).unwrap().unwrap();
+        assert_eq!(system, ParticleSystem::read(&legacy).unwrap().unwrap());
+        assert!(ParticleSystem::read(&[]).unwrap().is_none());
+        assert!(ParticleSystem::read(&data[..80]).is_err());
+    }
+}
@@ -0,0 +1,666 @@
+use messages::all::{ObjectUpdate, ObjectUpdate_ObjectData};
+use object_update::{
+    read_string, read_texture_entry, ExtraParams, ObjectState, ObjectUpdateError, ParticleSystem,
+    TextureAnimation, TextureEntry,
+};
+use types::{Uuid, Vector3, Vector4};
+use util::bitsreader::{BytesReader, LittleEndian, ReadError};
+
+/// The kind of an object.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PCode {
+    Primitive,
+    Avatar,
+    Grass,
+    NewTree,
+    ParticleSystem,
+    Tree,
+    Unknown(u8),
+}
+
+impl PCode {
+    pub fn from_u8(value: u8) -> PCode {
+        match value {
+            9 => PCode::Primitive,
+            47 => PCode::Avatar,
+            95 => PCode::Grass,
+            111 => PCode::NewTree,
+            143 => PCode::ParticleSystem,
+            255 => PCode::Tree,
+            other => PCode::Unknown(other),
+        }
+    }
+}
+
+/// What an object is made of, which determines its sounds and friction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Material {
+    Stone,
+    Metal,
+    Glass,
+    Wood,
+    Flesh,
+    Plastic,
+    Rubber,
+    Light,
+    Unknown(u8),
+}
+
+impl Material {
+    pub fn from_u8(value: u8) -> Material {
+        match value & 0x0f {
+            0 => Material::Stone,
+            1 => Material::Metal,
+            2 => Material::Glass,
+            3 => Material::Wood,
+            4 => Material::Flesh,
+            5 => Material::Plastic,
+            6 => Material::Rubber,
+            7 => Material::Light,
+            other => Material::Unknown(other),
+        }
+    }
+}
+
+/// What happens when the object is clicked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClickAction {
+    Touch,
+    Sit,
+    Buy,
+    Pay,
+    Open,
+    Play,
+    OpenMedia,
+    Zoom,
+    Disabled,
+    Unknown(u8),
+}
+
+impl ClickAction {
+    pub fn from_u8(value: u8) -> ClickAction {
+        match value {
+            0 => ClickAction::Touch,
+            1 => ClickAction::Sit,
+            2 => ClickAction::Buy,
+            3 => ClickAction::Pay,
+            4 => ClickAction::Open,
+            5 => ClickAction::Play,
+            6 => ClickAction::OpenMedia,
+            7 => ClickAction::Zoom,
+            8 => ClickAction::Disabled,
+            other => ClickAction::Unknown(other),
+        }
+    }
+}
+
+bitflags! {
+    /// The `update_flags` of an object, describing its state and the
+    /// permissions of the agent.
+    pub struct PrimFlags: u32 {
+        const USE_PHYSICS = 1 << 0;
+        const CREATE_SELECTED = 1 << 1;
+        const OBJECT_MODIFY = 1 << 2;
+        const OBJECT_COPY = 1 << 3;
+        const OBJECT_ANY_OWNER = 1 << 4;
+        const OBJECT_YOU_OWNER = 1 << 5;
+        const SCRIPTED = 1 << 6;
+        const HANDLE_TOUCH = 1 << 7;
+        const OBJECT_MOVE = 1 << 8;
+        const TAKES_MONEY = 1 << 9;
+        const PHANTOM = 1 << 10;
+        const INVENTORY_EMPTY = 1 << 11;
+        const JOINT_HINGE = 1 << 12;
+        const JOINT_P2P = 1 << 13;
+        const JOINT_LP2P = 1 << 14;
+        const INCLUDE_IN_SEARCH = 1 << 15;
+        const ALLOW_INVENTORY_DROP = 1 << 16;
+        const OBJECT_TRANSFER = 1 << 17;
+        const OBJECT_GROUP_OWNED = 1 << 18;
+        const CAMERA_DECOUPLED = 1 << 20;
+        const ANIM_SOURCE = 1 << 21;
+        const CAMERA_SOURCE = 1 << 22;
+        const OBJECT_OWNER_MODIFY = 1 << 28;
+        const TEMPORARY_ON_REZ = 1 << 29;
+        const TEMPORARY = 1 << 30;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathCurve {
+    Line,
+    Circle,
+    Circle2,
+    Test,
+    Flexible,
+    Unknown(u8),
+}
+
+impl PathCurve {
+    pub fn from_u8(value: u8) -> PathCurve {
+        match value {
+            0x10 => PathCurve::Line,
+            0x20 => PathCurve::Circle,
+            0x30 => PathCurve::Circle2,
+            0x40 => PathCurve::Test,
+            0x80 => PathCurve::Flexible,
+            other => PathCurve::Unknown(other),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProfileCurve {
+    Circle,
+    Square,
+    IsoTriangle,
+    EquilateralTriangle,
+    RightTriangle,
+    HalfCircle,
+    Unknown(u8),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HoleType {
+    Same,
+    Circle,
+    Square,
+    Triangle,
+    Unknown(u8),
+}
+
+/// The path which is swept by the profile to form the prim.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathParams {
+    pub curve: PathCurve,
+    pub begin: f32,
+    pub end: f32,
+    pub scale_x: f32,
+    pub scale_y: f32,
+    pub shear_x: f32,
+    pub shear_y: f32,
+    pub twist: f32,
+    pub twist_begin: f32,
+    pub radius_offset: f32,
+    pub taper_x: f32,
+    pub taper_y: f32,
+    pub revolutions: f32,
+    pub skew: f32,
+}
+
+/// The cross section of the prim.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProfileParams {
+    pub curve: ProfileCurve,
+    pub hole: HoleType,
+    pub begin: f32,
+    pub end: f32,
+    pub hollow: f32,
+}
+
+/// The parameters of a prim's shape, dequantized from the message.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shape {
+    pub path: PathParams,
+    pub profile: ProfileParams,
+}
+
+// Quantization steps of the shape parameters.
+const CUT_QUANTA: f32 = 0.00002;
+const SCALE_QUANTA: f32 = 0.01;
+const SHEAR_QUANTA: f32 = 0.01;
+const TAPER_QUANTA: f32 = 0.01;
+const REV_QUANTA: f32 = 0.015;
+const HOLLOW_QUANTA: f32 = 0.00002;
+
+/// The shape parameters as sent by the sim.
+pub(super) struct RawShape {
+    pub path_curve: u8,
+    pub profile_curve: u8,
+    pub path_begin: u16,
+    pub path_end: u16,
+    pub path_scale_x: u8,
+    pub path_scale_y: u8,
+    pub path_shear_x: u8,
+    pub path_shear_y: u8,
+    pub path_twist: i8,
+    pub path_twist_begin: i8,
+    pub path_radius_offset: i8,
+    pub path_taper_x: i8,
+    pub path_taper_y: i8,
+    pub path_revolutions: u8,
+    pub path_skew: i8,
+    pub profile_begin: u16,
+    pub profile_end: u16,
+    pub profile_hollow: u16,
+}
+
+impl RawShape {
+    fn from_object_data(data: &ObjectUpdate_ObjectData) -> RawShape {
+        RawShape {
+            path_curve: data.path_curve,
+            profile_curve: data.profile_curve,
+            path_begin: data.path_begin,
+            path_end: data.path_end,
+            path_scale_x: data.path_scale_x,
+            path_scale_y: data.path_scale_y,
+            path_shear_x: data.path_shear_x,
+            path_shear_y: data.path_shear_y,
+            path_twist: data.path_twist,
+            path_twist_begin: data.path_twist_begin,
+            path_radius_offset: data.path_radius_offset,
+            path_taper_x: data.path_taper_x,
+            path_taper_y: data.path_taper_y,
+            path_revolutions: data.path_revolutions,
+            path_skew: data.path_skew,
+            profile_begin: data.profile_begin,
+            profile_end: data.profile_end,
+            profile_hollow: data.profile_hollow,
+        }
+    }
+
+    /// Read the parameters as packed in `ObjectUpdateCompressed`, where the
+    /// profile curve follows the path parameters instead of the path curve.
+    pub fn read_compressed<R: BytesReader>(reader: &mut R) -> Result<RawShape, ReadError> {
+        let path_curve = reader.read_bytes_u8()?;
+        let path_begin = reader.read_bytes_u16::<LittleEndian>()?;
+        let path_end = reader.read_bytes_u16::<LittleEndian>()?;
+        let path_scale_x = reader.read_bytes_u8()?;
+        let path_scale_y = reader.read_bytes_u8()?;
+        let path_shear_x = reader.read_bytes_u8()?;
+        let path_shear_y = reader.read_bytes_u8()?;
+        let path_twist = reader.read_bytes_i8()?;
+        let path_twist_begin = reader.read_bytes_i8()?;
+        let path_radius_offset = reader.read_bytes_i8()?;
+        let path_taper_x = reader.read_bytes_i8()?;
+        let path_taper_y = reader.read_bytes_i8()?;
+        let path_revolutions = reader.read_bytes_u8()?;
+        let path_skew = reader.read_bytes_i8()?;
+        Ok(RawShape {
+            path_curve: path_curve,
+            profile_curve: reader.read_bytes_u8()?,
+            path_begin: path_begin,
+            path_end: path_end,
+            path_scale_x: path_scale_x,
+            path_scale_y: path_scale_y,
+            path_shear_x: path_shear_x,
+            path_shear_y: path_shear_y,
+            path_twist: path_twist,
+            path_twist_begin: path_twist_begin,
+            path_radius_offset: path_radius_offset,
+            path_taper_x: path_taper_x,
+            path_taper_y: path_taper_y,
+            path_revolutions: path_revolutions,
+            path_skew: path_skew,
+            profile_begin: reader.read_bytes_u16::<LittleEndian>()?,
+            profile_end: reader.read_bytes_u16::<LittleEndian>()?,
+            profile_hollow: reader.read_bytes_u16::<LittleEndian>()?,
+        })
+    }
+}
+
+impl Shape {
+    pub(super) fn from_raw(data: &RawShape) -> Shape {
+        Shape {
+            path: PathParams {
+                curve: PathCurve::from_u8(data.path_curve),
+                begin: data.path_begin as f32 * CUT_QUANTA,
+                end: (50000 - data.path_end as i32) as f32 * CUT_QUANTA,
+                scale_x: (200 - data.path_scale_x as i32) as f32 * SCALE_QUANTA,
+                scale_y: (200 - data.path_scale_y as i32) as f32 * SCALE_QUANTA,
+                shear_x: data.path_shear_x as i8 as f32 * SHEAR_QUANTA,
+                shear_y: data.path_shear_y as i8 as f32 * SHEAR_QUANTA,
+                twist: data.path_twist as f32 * SCALE_QUANTA,
+                twist_begin: data.path_twist_begin as f32 * SCALE_QUANTA,
+                radius_offset: data.path_radius_offset as f32 * SCALE_QUANTA,
+                taper_x: data.path_taper_x as f32 * TAPER_QUANTA,
+                taper_y: data.path_taper_y as f32 * TAPER_QUANTA,
+                revolutions: data.path_revolutions as f32 * REV_QUANTA + 1.,
+                skew: data.path_skew as f32 * SCALE_QUANTA,
+            },
+            profile: ProfileParams {
+                curve: match data.profile_curve & 0x0f {
+                    0 => ProfileCurve::Circle,
+                    1 => ProfileCurve::Square,
+                    2 => ProfileCurve::IsoTriangle,
+                    3 => ProfileCurve::EquilateralTriangle,
+                    4 => ProfileCurve::RightTriangle,
+                    5 => ProfileCurve::HalfCircle,
+                    other => ProfileCurve::Unknown(other),
+                },
+                hole: match data.profile_curve & 0xf0 {
+                    0x00 => HoleType::Same,
+                    0x10 => HoleType::Circle,
+                    0x20 => HoleType::Square,
+                    0x30 => HoleType::Triangle,
+                    other => HoleType::Unknown(other),
+                },
+                begin: data.profile_begin as f32 * CUT_QUANTA,
+                end: (50000 - data.profile_end as i32) as f32 * CUT_QUANTA,
+                hollow: data.profile_hollow as f32 * HOLLOW_QUANTA,
+            },
+        }
+    }
+}
+
+/// A named value attached to an object, e.g. the name of an avatar.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NameValue {
+    pub name: String,
+    /// STRING, F32, S32, VEC3, U32, ASSET or U64.
+    pub value_type: String,
+    /// R (read only), RW (read write) or CB (callback).
+    pub class: String,
+    /// S (sim), DS (data server), SV (sim and viewer) or DSV.
+    pub send_to: String,
+    pub value: String,
+}
+
+impl NameValue {
+    /// Parse the `name_value` of an update, one value per line in the format
+    /// `<name> <type> <class> <send to> <value>`.
+    pub fn parse(data: &[u8]) -> Vec<NameValue> {
+        read_string(data)
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(5, ' ');
+                Some(NameValue {
+                    name: parts.next()?.to_string(),
+                    value_type: parts.next()?.to_string(),
+                    class: parts.next()?.to_string(),
+                    send_to: parts.next()?.to_string(),
+                    value: parts.next().unwrap_or("").to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Text floating over an object.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HoverText {
+    pub text: String,
+    /// RGBA color.
+    pub color: Vector4<u8>,
+}
+
+/// A looped sound played by an object.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sound {
+    pub sound_id: Uuid,
+    pub owner_id: Uuid,
+    pub gain: f32,
+    pub flags: u8,
+    pub radius: f32,
+}
+
+/// Legacy physical joint between objects.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Joint {
+    /// 1 for a hinge, 2 for point to point.
+    pub joint_type: u8,
+    pub pivot: Vector3<f32>,
+    pub axis_or_anchor: Vector3<f32>,
+}
+
+/// An object as described by an `ObjectUpdate` or `ObjectUpdateCompressed`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Primitive {
+    /// The id of the object in the region.
+    pub local_id: u32,
+    pub full_id: Uuid,
+    /// The local id of the root of the link set, 0 for roots.
+    pub parent_id: u32,
+    /// Checksum of the object, used for caching.
+    pub crc: u32,
+    pub pcode: PCode,
+    /// For attachments this contains the attachment point.
+    pub state: u8,
+    pub material: Material,
+    pub click_action: ClickAction,
+    pub scale: Vector3<f32>,
+    pub motion: ObjectState,
+    pub flags: PrimFlags,
+    pub shape: Shape,
+    pub texture_entry: Option<TextureEntry>,
+    pub texture_animation: Option<TextureAnimation>,
+    pub name_values: Vec<NameValue>,
+    /// Additional data depending on the `pcode`, e.g. the tree species.
+    pub data: Vec<u8>,
+    pub text: Option<HoverText>,
+    pub media_url: Option<String>,
+    pub particle_system: Option<ParticleSystem>,
+    pub extra_params: ExtraParams,
+    pub sound: Option<Sound>,
+    pub joint: Option<Joint>,
+}
+
+impl Primitive {
+    /// Decode all objects of an `ObjectUpdate`.
+    pub fn from_object_update(msg: &ObjectUpdate) -> Vec<Result<Primitive, ObjectUpdateError>> {
+        msg.object_data
+            .iter()
+            .map(Primitive::from_object_data)
+            .collect()
+    }
+
+    pub fn from_object_data(
+        data: &ObjectUpdate_ObjectData,
+    ) -> Result<Primitive, ObjectUpdateError> {
+        let text = read_string(&data.text);
+        let media_url = read_string(&data.media_url);
+        let texture_entry = if data.texture_entry.is_empty() {
+            None
+        } else {
+            Some(read_texture_entry(&data.texture_entry)?)
+        };
+
+        Ok(Primitive {
+            local_id: data.id,
+            full_id: data.full_id.clone(),
+            parent_id: data.parent_id,
+            crc: data.crc,
+            pcode: PCode::from_u8(data.p_code),
+            state: data.state,
+            material: Material::from_u8(data.material),
+            click_action: ClickAction::from_u8(data.click_action),
+            scale: data.scale,
+            motion: ObjectState::read(&data.object_data)?,
+            flags: PrimFlags::from_bits_truncate(data.update_flags),
+            shape: Shape::from_raw(&RawShape::from_object_data(data)),
+            texture_entry: texture_entry,
+            texture_animation: TextureAnimation::read(&data.texture_anim)?,
+            name_values: NameValue::parse(&data.name_value),
+            data: data.data.clone(),
+            text: if text.is_empty() {
+                None
+            } else {
+                // The alpha is sent inverted.
+                let c = data.text_color;
+                Some(HoverText {
+                    text: text,
+                    color: Vector4::new(c[0], c[1], c[2], 255 - c[3]),
+                })
+            },
+            media_url: if media_url.is_empty() {
+                None
+            } else {
+                Some(media_url)
+            },
+            particle_system: ParticleSystem::read(&data.ps_block)?,
+            extra_params: ExtraParams::read(&data.extra_params)?,
+            sound: if data.sound.is_nil() {
+                None
+            } else {
+                Some(Sound {
+                    sound_id: data.sound.clone(),
+                    owner_id: data.owner_id.clone(),
+                    gain: data.gain,
+                    flags: data.flags,
+                    radius: data.radius,
+                })
+            },
+            joint: if data.joint_type == 0 {
+                None
+            } else {
+                Some(Joint {
+                    joint_type: data.joint_type,
+                    pivot: data.joint_pivot,
+                    axis_or_anchor: data.joint_axis_or_anchor,
+                })
+            },
+        })
+    }
+
+    /// The value of the name value with the given name.
+    pub fn name_value(&self, name: &str) -> Option<&str> {
+        self.name_values
+            .iter()
+            .find(|nv| nv.name == name)
+            .map(|nv| &nv.value[..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object_update::FaceProperties;
+
+    /// A default box as rezzed from the build menu, with some hover text.
+    fn plywood_box() -> ObjectUpdate_ObjectData {
+        let plywood = Uuid::parse_str("89556747-24cb-43ed-920b-47caed15465f").unwrap();
+        let mut texture_entry = plywood.as_bytes().to_vec();
+        texture_entry.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+        texture_entry.extend_from_slice(&[0, 0, 0x80, 0x3f, 0, 0, 0, 0x80, 0x3f, 0]);
+        texture_entry.extend_from_slice(&[0; 15]);
+
+        let mut object_data = vec![0u8; 60];
+        object_data[0..12].copy_from_slice(&[0, 0, 0x00, 0x43, 0, 0, 0x00, 0x43, 0, 0, 0xc8, 0x41]);
+
+        ObjectUpdate_ObjectData {
+            id: 1_234_567,
+            state: 0,
+            full_id: Uuid::parse_str("0c2ef5b7-3b28-4c0b-b4f7-29d0ccb1b3d1").unwrap(),
+            crc: 42,
+            p_code: 9,
+            material: 3,
+            click_action: 1,
+            scale: Vector3::new(0.5, 0.5, 0.5),
+            object_data: object_data,
+            parent_id: 0,
+            update_flags: 0x0000_0124,
+            path_curve: 0x10,
+            profile_curve: 0x01,
+            path_begin: 0,
+            path_end: 0,
+            path_scale_x: 100,
+            path_scale_y: 100,
+            path_shear_x: 0,
+            path_shear_y: 0xf6,
+            path_twist: 0,
+            path_twist_begin: 0,
+            path_radius_offset: 0,
+            path_taper_x: 0,
+            path_taper_y: 0,
+            path_revolutions: 0,
+            path_skew: 0,
+            profile_begin: 0,
+            profile_end: 0,
+            profile_hollow: 25000,
+            texture_entry: texture_entry,
+            texture_anim: Vec::new(),
+            name_value: Vec::new(),
+            data: Vec::new(),
+            text: b"Sit here\0".to_vec(),
+            text_color: [255, 255, 0, 0],
+            media_url: Vec::new(),
+            ps_block: Vec::new(),
+            extra_params: vec![0],
+            sound: Uuid::nil(),
+            owner_id: Uuid::nil(),
+            gain: 0.,
+            flags: 0,
+            radius: 0.,
+            joint_type: 0,
+            joint_pivot: Vector3::new(0., 0., 0.),
+            joint_axis_or_anchor: Vector3::new(0., 0., 0.),
+        }
+    }
+
+    #[test]
+    fn read_box() {
+        let prim = Primitive::from_object_data(&plywood_box()).unwrap();
+        assert_eq!(prim.local_id, 1_234_567);
+        assert_eq!(prim.pcode, PCode::Primitive);
+        assert_eq!(prim.material, Material::Wood);
+        assert_eq!(prim.click_action, ClickAction::Sit);
+        assert_eq!(
+            prim.flags,
+            PrimFlags::OBJECT_MODIFY | PrimFlags::OBJECT_YOU_OWNER | PrimFlags::OBJECT_MOVE
+        );
+        assert_eq!(prim.motion.position, Vector3::new(128., 128., 25.));
+
+        let shape = &prim.shape;
+        assert_eq!(shape.path.curve, PathCurve::Line);
+        assert_eq!(shape.path.begin, 0.);
+        assert_eq!(shape.path.end, 1.);
+        assert_eq!(shape.path.scale_x, 1.);
+        assert!((shape.path.shear_y + 0.1).abs() < 1e-6);
+        assert_eq!(shape.path.revolutions, 1.);
+        assert_eq!(shape.profile.curve, ProfileCurve::Square);
+        assert_eq!(shape.profile.hole, HoleType::Same);
+        assert_eq!(shape.profile.end, 1.);
+        assert!((shape.profile.hollow - 0.5).abs() < 1e-6);
+
+        let texture_entry = prim.texture_entry.unwrap();
+        assert!(texture_entry.faces.is_empty());
+        let face: &FaceProperties = texture_entry.face(3);
+        assert_eq!(
+            face.texture_id,
+            Uuid::parse_str("89556747-24cb-43ed-920b-47caed15465f").unwrap()
+        );
+        assert_eq!(face.repeat_v, 1.);
+
+        assert_eq!(
+            prim.text,
+            Some(HoverText {
+                text: "Sit here".to_string(),
+                color: Vector4::new(255, 255, 0, 255),
+            })
+        );
+        assert_eq!(prim.media_url, None);
+        assert_eq!(prim.particle_system, None);
+        assert_eq!(prim.extra_params, ExtraParams::default());
+        assert_eq!(prim.sound, None);
+        assert_eq!(prim.joint, None);
+    }
+
+    #[test]
+    fn read_avatar() {
+        let mut data = plywood_box();
+        data.p_code = 47;
+        data.object_data = vec![0u8; 76];
+        data.name_value =
+            b"FirstName STRING RW SV Test\nLastName STRING RW SV User\nTitle STRING RW SV \0"
+                .to_vec();
+
+        let prim = Primitive::from_object_data(&data).unwrap();
+        assert_eq!(prim.pcode, PCode::Avatar);
+        assert_eq!(
+            prim.motion.collision_plane,
+            Some(Vector4::new(0., 0., 0., 0.))
+        );
+        assert_eq!(prim.name_values.len(), 3);
+        assert_eq!(prim.name_value("FirstName"), Some("Test"));
+        assert_eq!(prim.name_value("LastName"), Some("User"));
+        assert_eq!(prim.name_value("Title"), Some(""));
+        assert_eq!(prim.name_values[0].send_to, "SV");
+    }
+
+    #[test]
+    fn invalid_object_data() {
+        let mut data = plywood_box();
+        data.object_data.truncate(50);
+        assert!(Primitive::from_object_data(&data).is_err());
+    }
+}
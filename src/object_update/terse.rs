@@ -0,0 +1,244 @@
+//! Decoding of `ImprovedTerseObjectUpdate`, which the sim sends whenever an
+//! avatar or physical object moves.
+
+use messages::all::{ImprovedTerseObjectUpdate, ImprovedTerseObjectUpdate_ObjectData};
+use object_update::motion::{read_vector3_f32, read_vector4_f32};
+use object_update::{
+    read_texture_entry, read_u16f, write_u16f, ObjectUpdateError, Primitive, TextureEntry,
+};
+use std::io::Cursor;
+use types::{Quaternion, Vector3, Vector4};
+use util::bitsreader::{BytesReader, LittleEndian, ReadError};
+
+// The ranges of the quantized values.
+const VELOCITY_RANGE: f32 = 128.;
+const ACCELERATION_RANGE: f32 = 64.;
+const ROTATION_RANGE: f32 = 1.;
+const ANGULAR_VELOCITY_RANGE: f32 = 64.;
+
+/// The new motion of an already known object.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TerseUpdate {
+    pub local_id: u32,
+    pub state: u8,
+    /// Only sent for avatars.
+    pub collision_plane: Option<Vector4<f32>>,
+    pub position: Vector3<f32>,
+    pub velocity: Vector3<f32>,
+    pub acceleration: Vector3<f32>,
+    pub rotation: Quaternion<f32>,
+    pub angular_velocity: Vector3<f32>,
+    pub texture_entry: Option<TextureEntry>,
+}
+
+impl TerseUpdate {
+    /// Decode all objects of an `ImprovedTerseObjectUpdate`.
+    pub fn from_message(
+        msg: &ImprovedTerseObjectUpdate,
+    ) -> Vec<Result<TerseUpdate, ObjectUpdateError>> {
+        msg.object_data.iter().map(TerseUpdate::read).collect()
+    }
+
+    pub fn read(
+        data: &ImprovedTerseObjectUpdate_ObjectData,
+    ) -> Result<TerseUpdate, ObjectUpdateError> {
+        let mut reader = Cursor::new(&data.data[..]);
+        let reader = &mut reader;
+
+        let local_id = reader.read_bytes_u32::<LittleEndian>()?;
+        let state = reader.read_bytes_u8()?;
+        let collision_plane = if reader.read_bytes_bool()? {
+            Some(read_vector4_f32(reader)?)
+        } else {
+            None
+        };
+        let position = read_vector3_f32(reader)?;
+        let velocity = read_vector3_u16f(reader, VELOCITY_RANGE)?;
+        let acceleration = read_vector3_u16f(reader, ACCELERATION_RANGE)?;
+        // Sent in the order x, y, z, w.
+        let rotation = {
+            let v = read_vector3_u16f(reader, ROTATION_RANGE)?;
+            let w = read_u16f(reader, ROTATION_RANGE)?;
+            Quaternion::new(w, v.x, v.y, v.z)
+        };
+        let angular_velocity = read_vector3_u16f(reader, ANGULAR_VELOCITY_RANGE)?;
+
+        // The texture entry is prefixed with its size.
+        let texture_entry = if data.texture_entry.len() > 4 {
+            Some(read_texture_entry(&data.texture_entry[4..])?)
+        } else {
+            None
+        };
+
+        Ok(TerseUpdate {
+            local_id: local_id,
+            state: state,
+            collision_plane: collision_plane,
+            position: position,
+            velocity: velocity,
+            acceleration: acceleration,
+            rotation: rotation,
+            angular_velocity: angular_velocity,
+            texture_entry: texture_entry,
+        })
+    }
+
+    /// Encode the motion as the `data` of an `ImprovedTerseObjectUpdate`,
+    /// the inverse of `read`.
+    ///
+    /// The texture entry is not encoded.
+    pub fn write(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(60);
+        write_u32(&mut buf, self.local_id);
+        buf.push(self.state);
+        match self.collision_plane {
+            Some(ref plane) => {
+                buf.push(1);
+                write_f32s(&mut buf, plane.as_slice());
+            }
+            None => buf.push(0),
+        }
+        write_f32s(&mut buf, self.position.as_slice());
+        for v in self.velocity.iter() {
+            write_u16f(&mut buf, *v, VELOCITY_RANGE);
+        }
+        for v in self.acceleration.iter() {
+            write_u16f(&mut buf, *v, ACCELERATION_RANGE);
+        }
+        // `coords` is in the order x, y, z, w.
+        for v in self.rotation.coords.iter() {
+            write_u16f(&mut buf, *v, ROTATION_RANGE);
+        }
+        for v in self.angular_velocity.iter() {
+            write_u16f(&mut buf, *v, ANGULAR_VELOCITY_RANGE);
+        }
+        buf
+    }
+
+    /// Apply the update to the object it is for.
+    pub fn apply(&self, prim: &mut Primitive) {
+        debug_assert_eq!(self.local_id, prim.local_id);
+
+        prim.state = self.state;
+        let motion = &mut prim.motion;
+        if self.collision_plane.is_some() {
+            motion.collision_plane = self.collision_plane;
+        }
+        motion.position = self.position;
+        motion.velocity = self.velocity;
+        motion.acceleration = self.acceleration;
+        motion.rotation = self.rotation;
+        motion.angular_velocity = self.angular_velocity;
+        if let Some(ref texture_entry) = self.texture_entry {
+            prim.texture_entry = Some(texture_entry.clone());
+        }
+    }
+}
+
+fn read_vector3_u16f<R: BytesReader>(
+    reader: &mut R,
+    range_r: f32,
+) -> Result<Vector3<f32>, ReadError> {
+    Ok(Vector3::new(
+        read_u16f(reader, range_r)?,
+        read_u16f(reader, range_r)?,
+        read_u16f(reader, range_r)?,
+    ))
+}
+
+fn write_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&[
+        value as u8,
+        (value >> 8) as u8,
+        (value >> 16) as u8,
+        (value >> 24) as u8,
+    ]);
+}
+
+fn write_f32s(buf: &mut Vec<u8>, values: &[f32]) {
+    for value in values {
+        write_u32(buf, value.to_bits());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update() -> TerseUpdate {
+        TerseUpdate {
+            local_id: 1_234_567,
+            state: 0,
+            collision_plane: Some(Vector4::new(0., 0., 1., -21.)),
+            position: Vector3::new(128.5, 64.25, 22.),
+            velocity: Vector3::new(3.2, -1.5, 0.),
+            acceleration: Vector3::new(0., 0., -9.8),
+            rotation: Quaternion::new(0.8, 0., 0., 0.6),
+            angular_velocity: Vector3::new(0., 0., 0.),
+            texture_entry: None,
+        }
+    }
+
+    fn round_trip(update: &TerseUpdate) -> TerseUpdate {
+        TerseUpdate::read(&ImprovedTerseObjectUpdate_ObjectData {
+            data: update.write(),
+            texture_entry: Vec::new(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn avatar_round_trip() {
+        let update = update();
+        let data = update.write();
+        assert_eq!(data.len(), 60);
+
+        let decoded = round_trip(&update);
+        assert_eq!(decoded.local_id, update.local_id);
+        assert_eq!(decoded.collision_plane, update.collision_plane);
+        assert_eq!(decoded.position, update.position);
+        assert!((decoded.velocity - update.velocity).norm() < 0.01);
+        assert!((decoded.acceleration - update.acceleration).norm() < 0.01);
+        assert!((decoded.rotation.coords - update.rotation.coords).norm() < 0.001);
+        // Zero survives the quantization.
+        assert_eq!(decoded.velocity.z, 0.);
+        assert_eq!(decoded.rotation.coords.x, 0.);
+        assert_eq!(decoded.angular_velocity, Vector3::new(0., 0., 0.));
+    }
+
+    #[test]
+    fn object_round_trip() {
+        let mut update = update();
+        update.collision_plane = None;
+        update.angular_velocity = Vector3::new(0., 0., 1.5);
+        assert_eq!(update.write().len(), 44);
+
+        let decoded = round_trip(&update);
+        assert_eq!(decoded.collision_plane, None);
+        assert_eq!(decoded.position, update.position);
+        assert!((decoded.angular_velocity - update.angular_velocity).norm() < 0.01);
+    }
+
+    #[test]
+    fn captured_update() {
+        // Captured from an OpenSim sim.
+        let data = vec![
+            138, 231, 80, 74, 0, 1, 105, 69, 167, 61, 0, 0, 0, 0, 10, 37, 127, 63, 181, 100, 177,
+            65, 71, 93, 41, 67, 200, 44, 255, 66, 200, 50, 26, 65, 217, 125, 254, 127, 138, 129,
+            255, 127, 255, 127, 255, 127, 255, 127, 255, 127, 255, 127, 255, 255, 255, 127, 255,
+            127, 255, 127,
+        ];
+        let update = TerseUpdate::read(&ImprovedTerseObjectUpdate_ObjectData {
+            data: data,
+            texture_entry: Vec::new(),
+        })
+        .unwrap();
+        assert_eq!(update.local_id, 0x4a50_e78a);
+        assert!(update.collision_plane.is_some());
+        assert!((update.position - Vector3::new(169.364, 127.587, 9.637)).norm() < 0.001);
+        assert!((update.velocity - Vector3::new(-2.150, -0.006, 1.541)).norm() < 0.001);
+        assert_eq!(update.acceleration, Vector3::new(0., 0., 0.));
+        assert_eq!(update.rotation, Quaternion::new(1., 0., 0., 0.));
+        assert_eq!(update.angular_velocity, Vector3::new(0., 0., 0.));
+    }
+}
@@ -0,0 +1,314 @@
+use object_update::read_uuid;
+use std::io::Cursor;
+use types::{Uuid, Vector4};
+use util::bitsreader::{BytesReader, LittleEndian, ReadError};
+
+/// The textures and surface properties of the faces of an object.
+///
+/// Each property is encoded as a default value followed by values for
+/// sets of faces, so only faces differing from the default are listed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextureEntry {
+    /// The properties of all faces which are not in `faces`.
+    pub default: FaceProperties,
+    /// The properties of faces `0..faces.len()`.
+    pub faces: Vec<FaceProperties>,
+}
+
+impl TextureEntry {
+    /// The properties of the face with the given index.
+    pub fn face(&self, index: usize) -> &FaceProperties {
+        self.faces.get(index).unwrap_or(&self.default)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FaceProperties {
+    /// The texture ID for this face.
+    pub texture_id: Uuid,
+    /// RGBA color value.
+    pub color: Vector4<u8>,
+    pub repeat_u: f32,
+    pub repeat_v: f32,
+    pub offset_u: f32,
+    pub offset_v: f32,
+    /// Rotation of the texture in radians.
+    pub rotation: f32,
+    /// Bump map, shininess and full bright, see the accessors.
+    pub material: u8,
+    /// Media flag and texture mapping.
+    pub media: u8,
+    pub glow: f32,
+    /// The render material, nil if there is none.
+    pub material_id: Uuid,
+}
+
+impl FaceProperties {
+    pub fn bump(&self) -> u8 {
+        self.material & 0x1f
+    }
+
+    pub fn fullbright(&self) -> bool {
+        self.material & 0x20 != 0
+    }
+
+    pub fn shiny(&self) -> u8 {
+        self.material >> 6
+    }
+}
+
+struct PartialFaceProperties {
+    texture_id: Option<Uuid>,
+    color: Option<Vector4<u8>>,
+    repeat_u: Option<f32>,
+    repeat_v: Option<f32>,
+    offset_u: Option<f32>,
+    offset_v: Option<f32>,
+    rotation: Option<f32>,
+    material: Option<u8>,
+    media: Option<u8>,
+    glow: Option<f32>,
+    material_id: Option<Uuid>,
+}
+
+impl PartialFaceProperties {
+    fn new() -> Self {
+        PartialFaceProperties {
+            texture_id: None,
+            color: None,
+            repeat_u: None,
+            repeat_v: None,
+            offset_u: None,
+            offset_v: None,
+            rotation: None,
+            material: None,
+            media: None,
+            glow: None,
+            material_id: None,
+        }
+    }
+
+    fn full(self) -> Option<FaceProperties> {
+        Some(FaceProperties {
+            texture_id: self.texture_id?,
+            color: self.color?,
+            repeat_u: self.repeat_u?,
+            repeat_v: self.repeat_v?,
+            offset_u: self.offset_u?,
+            offset_v: self.offset_v?,
+            rotation: self.rotation?,
+            material: self.material?,
+            media: self.media?,
+            glow: self.glow?,
+            material_id: self.material_id?,
+        })
+    }
+
+    fn complete(self, full: &FaceProperties) -> FaceProperties {
+        FaceProperties {
+            texture_id: self.texture_id.unwrap_or_else(|| full.texture_id.clone()),
+            color: self.color.unwrap_or_else(|| full.color.clone()),
+            repeat_u: self.repeat_u.unwrap_or(full.repeat_u),
+            repeat_v: self.repeat_v.unwrap_or(full.repeat_v),
+            offset_u: self.offset_u.unwrap_or(full.offset_u),
+            offset_v: self.offset_v.unwrap_or(full.offset_v),
+            rotation: self.rotation.unwrap_or(full.rotation),
+            material: self.material.unwrap_or(full.material),
+            media: self.media.unwrap_or(full.media),
+            glow: self.glow.unwrap_or(full.glow),
+            material_id: self.material_id.unwrap_or_else(|| full.material_id.clone()),
+        }
+    }
+}
+
+/// Animation of the texture of an object.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextureAnimation {
+    /// ON, LOOP, REVERSE, PING_PONG, SMOOTH, ROTATE and SCALE flags.
+    pub mode: u8,
+    /// The animated face, -1 for all faces.
+    pub face: i8,
+    pub size_x: u8,
+    pub size_y: u8,
+    pub start: f32,
+    pub length: f32,
+    pub rate: f32,
+}
+
+impl TextureAnimation {
+    /// Read the `texture_anim` of an update, which is empty if the texture
+    /// is not animated.
+    pub fn read(data: &[u8]) -> Result<Option<TextureAnimation>, ReadError> {
+        if data.is_empty() {
+            return Ok(None);
+        }
+        let mut reader = Cursor::new(data);
+        Ok(Some(TextureAnimation {
+            mode: reader.read_bytes_u8()?,
+            face: reader.read_bytes_i8()?,
+            size_x: reader.read_bytes_u8()?,
+            size_y: reader.read_bytes_u8()?,
+            start: reader.read_bytes_f32::<LittleEndian>()?,
+            length: reader.read_bytes_f32::<LittleEndian>()?,
+            rate: reader.read_bytes_f32::<LittleEndian>()?,
+        }))
+    }
+}
+
+fn read_offset<R: BytesReader>(reader: &mut R) -> Result<f32, ReadError> {
+    Ok(reader.read_bytes_i16::<LittleEndian>()? as f32 / 32767.)
+}
+
+fn read_rotation<R: BytesReader>(reader: &mut R) -> Result<f32, ReadError> {
+    Ok(reader.read_bytes_i16::<LittleEndian>()? as f32 / 32768. * 2. * ::std::f32::consts::PI)
+}
+
+fn read_glow<R: BytesReader>(reader: &mut R) -> Result<f32, ReadError> {
+    Ok(reader.read_bytes_u8()? as f32 / 255.)
+}
+
+/// Colors are sent inverted, so that the default white is all zeros.
+fn read_color<R: BytesReader>(reader: &mut R) -> Result<Vector4<u8>, ReadError> {
+    Ok(Vector4::new(
+        255 - reader.read_bytes_u8()?,
+        255 - reader.read_bytes_u8()?,
+        255 - reader.read_bytes_u8()?,
+        255 - reader.read_bytes_u8()?,
+    ))
+}
+
+pub fn read_texture_entry(data: &[u8]) -> Result<TextureEntry, ReadError> {
+    let mut reader = Cursor::new(data);
+    let reader = &mut reader;
+
+    // Fill these by reading the various property arrays.
+    let mut default = PartialFaceProperties::new();
+    let mut partial: Vec<PartialFaceProperties> = Vec::new();
+
+    macro_rules! decode_prop_vec {
+        ($f_name:ident = $read:expr) => {
+            default.$f_name = Some($read);
+            loop {
+                // The list of faces also ends with the data.
+                if reader.position() as usize >= data.len() {
+                    break;
+                }
+                let face_bits = read_face_bitfield(reader)?;
+                if face_bits == 0 {
+                    break;
+                }
+
+                let value = $read;
+                for i in 0..64 {
+                    if face_bits & (1 << i) == 0 {
+                        continue;
+                    }
+                    while i >= partial.len() {
+                        partial.push(PartialFaceProperties::new());
+                    }
+                    partial[i].$f_name = Some(value.clone());
+                }
+            }
+        };
+    }
+
+    decode_prop_vec!(texture_id = read_uuid(reader)?);
+    decode_prop_vec!(color = read_color(reader)?);
+    decode_prop_vec!(repeat_u = reader.read_bytes_f32::<LittleEndian>()?);
+    decode_prop_vec!(repeat_v = reader.read_bytes_f32::<LittleEndian>()?);
+    decode_prop_vec!(offset_u = read_offset(reader)?);
+    decode_prop_vec!(offset_v = read_offset(reader)?);
+    decode_prop_vec!(rotation = read_rotation(reader)?);
+    decode_prop_vec!(material = reader.read_bytes_u8()?);
+    decode_prop_vec!(media = reader.read_bytes_u8()?);
+    decode_prop_vec!(glow = read_glow(reader)?);
+    // Older sims don't send the materials.
+    if (reader.position() as usize) < data.len() {
+        decode_prop_vec!(material_id = read_uuid(reader)?);
+    } else {
+        default.material_id = Some(Uuid::nil());
+    }
+
+    // Note: At this point this should not be able to fail, since each default value
+    //       should have been extracted or there should have been an early return of
+    //       this function.
+    let default = default.full().unwrap();
+    Ok(TextureEntry {
+        faces: partial
+            .into_iter()
+            .map(|item| item.complete(&default))
+            .collect(),
+        default: default,
+    })
+}
+
+/// Read the set of faces a value applies to, bit `i` is face `i`.
+///
+/// The bits are sent in groups of seven, most significant first, the high
+/// bit of each byte tells whether another group follows.
+fn read_face_bitfield<R: BytesReader>(reader: &mut R) -> Result<u64, ReadError> {
+    let mut face_bits = 0u64;
+
+    loop {
+        let byte = reader.read_bytes_u8()? as u64;
+        face_bits = (face_bits << 7) | (byte & 0x7f);
+
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+
+    Ok(face_bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn face_bitfield() {
+        assert_eq!(
+            read_face_bitfield(&mut Cursor::new(&[0x05])).unwrap(),
+            0b101
+        );
+        assert_eq!(
+            read_face_bitfield(&mut Cursor::new(&[0x81, 0x00])).unwrap(),
+            1 << 7
+        );
+    }
+
+    #[test]
+    fn read_plywood_box() {
+        let plywood = Uuid::parse_str("89556747-24cb-43ed-920b-47caed15465f").unwrap();
+        let blank = Uuid::parse_str("5748decc-f629-461c-9a36-a35a221fe21f").unwrap();
+
+        let mut data = Vec::new();
+        // Plywood everywhere, except for face 1 and 3.
+        data.extend_from_slice(plywood.as_bytes());
+        data.push(0b1010);
+        data.extend_from_slice(blank.as_bytes());
+        data.push(0);
+        // White, face 1 is red.
+        data.extend_from_slice(&[0, 0, 0, 0, 0b10, 0, 255, 255, 0, 0]);
+        // Repeats of 1.
+        data.extend_from_slice(&[0, 0, 0x80, 0x3f, 0]);
+        data.extend_from_slice(&[0, 0, 0x80, 0x3f, 0]);
+        // Offsets, rotation, material, media and glow are zero.
+        data.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        data.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+
+        let entry = read_texture_entry(&data).unwrap();
+        assert_eq!(entry.default.texture_id, plywood);
+        assert_eq!(entry.default.color, Vector4::new(255, 255, 255, 255));
+        assert_eq!(entry.default.repeat_u, 1.);
+        assert_eq!(entry.default.material_id, Uuid::nil());
+        assert_eq!(entry.faces.len(), 4);
+        assert_eq!(entry.face(0).texture_id, plywood);
+        assert_eq!(entry.face(1).texture_id, blank);
+        assert_eq!(entry.face(1).color, Vector4::new(255, 0, 0, 255));
+        assert_eq!(entry.face(2), &entry.default);
+        assert_eq!(entry.face(3).texture_id, blank);
+        assert_eq!(entry.face(3).color, Vector4::new(255, 255, 255, 255));
+        assert_eq!(entry.face(5), &entry.default);
+    }
+}
@@ -54,6 +54,11 @@ impl Packet {
     /// Write the packet (including both body and header) to a buffer
     /// in its current form.
     ///
+    /// If the `ZEROCODED` flag is set the message body will be zerocoded,
+    /// unless this would not make the packet any smaller, in which case the
+    /// body is written as is and the flag is cleared in the written header.
+    /// Appended acks are never zerocoded.
+    ///
     /// # Protocol documentation
     /// * http://lib.openmetaverse.co/wiki/Protocol_(network)
     /// * http://wiki.secondlife.com/wiki/Packet_Layout
@@ -62,13 +67,24 @@ impl Packet {
         debug_assert!(
             !(self.flags.contains(PacketFlags::APPENDED_ACKS) && self.appended_acks.is_empty())
         );
-        // TODO: Zero coded writing not implemented yet.
-        assert!(!self.flags.contains(PacketFlags::ZEROCODED));
 
-        buffer.write_u8(self.flags.bits())?;
+        let mut flags = self.flags;
+        let mut body = Vec::new();
+        self.message.write_to(&mut body)?;
+        if flags.contains(PacketFlags::ZEROCODED) {
+            let mut encoded = Vec::with_capacity(body.len());
+            write_zerocoded(&mut encoded, &body)?;
+            if encoded.len() < body.len() {
+                body = encoded;
+            } else {
+                flags.remove(PacketFlags::ZEROCODED);
+            }
+        }
+
+        buffer.write_u8(flags.bits())?;
         buffer.write_u32::<BigEndian>(self.sequence_number)?;
         buffer.write(&[0])?;
-        self.message.write_to(buffer)?;
+        buffer.write_all(&body)?;
         for ack in &self.appended_acks {
             buffer.write_u32::<BigEndian>(*ack)?;
         }
@@ -84,9 +100,32 @@ impl Packet {
     /// other noise
     /// from previous reads etc from the slice!
     pub fn read<'a>(buf: &'a [u8]) -> Result<Packet, ReadPacketError> {
-        let mut reader = PacketReader::new(buf);
+        if buf.is_empty() {
+            return Err(ReadPacketError::IoError(::std::io::Error::new(
+                ::std::io::ErrorKind::UnexpectedEof,
+                "Empty packet.",
+            )));
+        }
+        let flags = PacketFlags::from_bits_truncate(buf[0]);
+
+        // Appended acks are never zerocoded, so they have to be split off
+        // from the body before it is decoded.
+        let (body, acks_buf) = if flags.contains(PacketFlags::APPENDED_ACKS) {
+            let n_acks = buf[buf.len() - 1] as usize;
+            let acks_len = n_acks * 4 + 1;
+            if acks_len > buf.len() {
+                return Err(ReadPacketError::IoError(::std::io::Error::new(
+                    ::std::io::ErrorKind::UnexpectedEof,
+                    "Appended acks exceed the packet size.",
+                )));
+            }
+            buf.split_at(buf.len() - acks_len)
+        } else {
+            (buf, &buf[buf.len()..])
+        };
 
-        let flags = PacketFlags::from_bits(reader.read_u8()?).unwrap();
+        let mut reader = PacketReader::new(body);
+        reader.skip_bytes(1)?;
         let sequence_num = reader.read_u32::<BigEndian>()?;
 
         // Skip extra header if present, since we don't expect it.
@@ -104,11 +143,12 @@ impl Packet {
 
         // Read appended ACKs if there are supposed to be any.
         let mut acks = Vec::new();
-        if flags.contains(PacketFlags::APPENDED_ACKS) {
-            let n_acks = reader.peek_last_byte() as usize;
+        if !acks_buf.is_empty() {
+            let mut acks_reader = &acks_buf[..acks_buf.len() - 1];
+            let n_acks = acks_reader.len() / 4;
             acks.reserve(n_acks);
             for _ in 0..n_acks {
-                acks.push(reader.read_u32::<BigEndian>()?);
+                acks.push(acks_reader.read_u32::<BigEndian>()?);
             }
         }
 
@@ -130,6 +170,15 @@ impl Packet {
         self.flags.remove(flags);
     }
 
+    /// Set the zerocoded flag for a packet.
+    pub fn set_zerocoded(&mut self, value: bool) {
+        if value {
+            self.enable_flags(PacketFlags::ZEROCODED);
+        } else {
+            self.disable_flags(PacketFlags::ZEROCODED);
+        }
+    }
+
     /// Set the reliable flag for a packet.
     pub fn set_reliable(&mut self, value: bool) {
         if value {
@@ -171,6 +220,34 @@ impl From<::messages::ReadError> for ReadPacketError {
     }
 }
 
+/// Write `data` zerocoded to the buffer.
+///
+/// Each run of zero bytes is replaced by a single zero byte followed by the
+/// length of the run. Runs longer than 255 bytes are split into multiple such
+/// pairs.
+fn write_zerocoded<W: Write>(buffer: &mut W, data: &[u8]) -> Result<(), ::std::io::Error> {
+    let mut zeros: u8 = 0;
+    for &byte in data {
+        if byte == 0 {
+            if zeros == 255 {
+                buffer.write_all(&[0, zeros])?;
+                zeros = 0;
+            }
+            zeros += 1;
+        } else {
+            if zeros > 0 {
+                buffer.write_all(&[0, zeros])?;
+                zeros = 0;
+            }
+            buffer.write_u8(byte)?;
+        }
+    }
+    if zeros > 0 {
+        buffer.write_all(&[0, zeros])?;
+    }
+    Ok(())
+}
+
 /// Used internally to read the content of packages.
 /// Provides transparent reading of zerocoded content.
 /// Memory: O(1)
@@ -197,11 +274,6 @@ impl<'a> PacketReader<'a> {
         }
     }
 
-    /// Return the content of the very last byte of the message.
-    fn peek_last_byte(&self) -> u8 {
-        self.buf[self.buf.len() - 1]
-    }
-
     #[inline]
     fn has_index(&self, index: usize) -> bool {
         (self.buf.len() - index) > 0
@@ -360,6 +432,120 @@ mod tests {
         assert_eq!(buffer, [0, 0, 1]);
     }
 
+    #[test]
+    fn write_zerocoded_runs() {
+        let mut out = Vec::new();
+        write_zerocoded(&mut out, &[1, 0, 0, 2, 0]).unwrap();
+        assert_eq!(out, vec![1, 0, 2, 2, 0, 1]);
+
+        // Runs longer than 255 zero bytes have to be split.
+        let mut out = Vec::new();
+        write_zerocoded(&mut out, &[0u8; 600]).unwrap();
+        assert_eq!(out, vec![0, 255, 0, 255, 0, 90]);
+
+        let mut out = Vec::new();
+        write_zerocoded(&mut out, &[0u8; 255]).unwrap();
+        assert_eq!(out, vec![0, 255]);
+    }
+
+    #[test]
+    fn zerocoded_long_run_roundtrip() {
+        let mut data = vec![7u8];
+        data.extend_from_slice(&[0u8; 600]);
+        data.push(9);
+
+        let mut encoded = Vec::new();
+        write_zerocoded(&mut encoded, &data).unwrap();
+
+        let mut reader = PacketReader::new(&encoded);
+        reader.zerocoding_enabled = true;
+        let mut decoded = vec![1u8; data.len()];
+        reader.read_exact(&mut decoded).unwrap();
+        assert_eq!(decoded, data);
+    }
+
+    fn test_packet() -> Packet {
+        use messages::all::{UseCircuitCode, UseCircuitCode_CircuitCode};
+        use types::Uuid;
+
+        let message = UseCircuitCode {
+            circuit_code: UseCircuitCode_CircuitCode {
+                code: 0x0100_0000,
+                session_id: Uuid::nil(),
+                id: "10b2de5f-2030-4ac4-ab53-9a8f082af748".parse().unwrap(),
+            },
+        };
+        Packet::new(message, 42)
+    }
+
+    fn assert_same_message(a: &Packet, b: &Packet) {
+        let mut raw_a = Vec::new();
+        let mut raw_b = Vec::new();
+        a.message.write_to(&mut raw_a).unwrap();
+        b.message.write_to(&mut raw_b).unwrap();
+        assert_eq!(raw_a, raw_b);
+    }
+
+    #[test]
+    fn write_zerocoded_packet_roundtrip() {
+        let mut packet = test_packet();
+        packet.set_zerocoded(true);
+        packet.set_reliable(true);
+
+        let mut plain = Vec::new();
+        test_packet().write_to(&mut plain).unwrap();
+        let mut buf = Vec::new();
+        packet.write_to(&mut buf).unwrap();
+        assert!(buf.len() < plain.len());
+
+        let read = Packet::read(&buf).unwrap();
+        assert!(read.has_flag(PacketFlags::ZEROCODED));
+        assert!(read.is_reliable());
+        assert_eq!(read.sequence_number, 42);
+        assert!(read.appended_acks.is_empty());
+        assert_same_message(&packet, &read);
+    }
+
+    #[test]
+    fn write_zerocoded_appended_acks_unencoded() {
+        let mut packet = test_packet();
+        packet.set_zerocoded(true);
+        packet.enable_flags(PacketFlags::APPENDED_ACKS);
+        packet.appended_acks = vec![0x0000_0001, 0x0000_0100];
+
+        let mut buf = Vec::new();
+        packet.write_to(&mut buf).unwrap();
+        assert_eq!(&buf[buf.len() - 9..], &[0, 0, 0, 1, 0, 0, 1, 0, 2][..]);
+
+        let read = Packet::read(&buf).unwrap();
+        assert!(read.has_flag(PacketFlags::ZEROCODED));
+        assert_eq!(read.appended_acks, vec![0x0000_0001, 0x0000_0100]);
+        assert_same_message(&packet, &read);
+    }
+
+    #[test]
+    fn write_zerocoded_fallback_when_larger() {
+        use messages::all::{StartPingCheck, StartPingCheck_PingID};
+
+        // A message without zero bytes can't be shrunk by zerocoding.
+        let mut packet = Packet::new(
+            StartPingCheck {
+                ping_id: StartPingCheck_PingID {
+                    ping_id: 3,
+                    oldest_unacked: 0x0101_0101,
+                },
+            },
+            1,
+        );
+        packet.set_zerocoded(true);
+
+        let mut buf = Vec::new();
+        packet.write_to(&mut buf).unwrap();
+        let read = Packet::read(&buf).unwrap();
+        assert!(!read.has_flag(PacketFlags::ZEROCODED));
+        assert_same_message(&packet, &read);
+    }
+
     #[test]
     fn reader_skip() {
         let data: [u8; 6] = [0, 1, 2, 3, 4, 5];
@@ -0,0 +1,111 @@
+//! Tracking of the agent's own position.
+//!
+//! The sim tells the agent where it is with `AgentMovementComplete` after
+//! `CompleteAgentMovement`, i.e. after connecting, teleporting or crossing
+//! into the region. Afterwards the avatar is an object like any other, so its
+//! position is taken from the scene of the `ObjectService`.
+
+use circuit::message_handlers::{self, HandlerContext};
+use futures::sync::oneshot;
+use grid_map::region_handle::RegionHandle;
+use logging::Log;
+use messages::all::AgentMovementComplete;
+use messages::{MessageInstance, MessageType};
+use services::objects::ObjectService;
+use std::sync::{Arc, Mutex};
+use types::{Uuid, Vector3};
+
+/// Where the agent is.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AgentPosition {
+    pub region_handle: RegionHandle,
+    /// Region local position of the avatar.
+    pub position: Vector3<f32>,
+    /// The direction the agent is looking at, as of the last
+    /// `AgentMovementComplete`.
+    pub look_at: Vector3<f32>,
+}
+
+struct State {
+    position: Option<AgentPosition>,
+    /// Waiting for the next `AgentMovementComplete`.
+    waiting: Vec<oneshot::Sender<AgentPosition>>,
+}
+
+pub struct AgentService {
+    agent_id: Uuid,
+    state: Arc<Mutex<State>>,
+    objects: ObjectService,
+}
+
+impl AgentService {
+    pub(crate) fn register(
+        handlers: &mut message_handlers::Handlers,
+        agent_id: Uuid,
+        objects: &ObjectService,
+        _log: &Log,
+    ) -> Self {
+        let state = Arc::new(Mutex::new(State {
+            position: None,
+            waiting: Vec::new(),
+        }));
+
+        let handler_state = Arc::clone(&state);
+        handlers.register_type(
+            MessageType::AgentMovementComplete,
+            Box::new(move |msg: MessageInstance, _: &HandlerContext| match msg {
+                MessageInstance::AgentMovementComplete(msg) => {
+                    movement_complete(&mut handler_state.lock().unwrap(), msg);
+                    Ok(())
+                }
+                msg => Err(wrong_handler(msg)),
+            }),
+        );
+
+        AgentService {
+            agent_id: agent_id,
+            state: state,
+            objects: objects.clone(),
+        }
+    }
+
+    /// The last known position of the agent, `None` until the sim sent it.
+    pub fn position(&self) -> Option<AgentPosition> {
+        // Without `AgentMovementComplete` we don't know where we are looking.
+        let mut position = self.state.lock().unwrap().position.clone()?;
+        let scene = self.objects.scene();
+        let avatar = scene
+            .get_by_full_id(&self.agent_id)
+            .and_then(|avatar| scene.region_position(avatar.local_id));
+        if let Some(avatar) = avatar {
+            position.position = avatar;
+        }
+        Some(position)
+    }
+
+    /// Resolves with the position of the next `AgentMovementComplete`.
+    pub(crate) fn movement_complete(&self) -> oneshot::Receiver<AgentPosition> {
+        let (sender, receiver) = oneshot::channel();
+        self.state.lock().unwrap().waiting.push(sender);
+        receiver
+    }
+}
+
+fn wrong_handler(msg: MessageInstance) -> message_handlers::Error {
+    message_handlers::Error {
+        msg: msg,
+        kind: message_handlers::ErrorKind::WrongHandler,
+    }
+}
+
+fn movement_complete(state: &mut State, msg: AgentMovementComplete) {
+    let position = AgentPosition {
+        region_handle: RegionHandle::from_handle(msg.data.region_handle),
+        position: msg.data.position,
+        look_at: msg.data.look_at,
+    };
+    state.position = Some(position.clone());
+    for sender in state.waiting.drain(..) {
+        let _ = sender.send(position.clone());
+    }
+}
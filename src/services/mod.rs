@@ -48,5 +48,8 @@ pub struct CircuitData {
     pub message_sender: MessageSender,
 }
 
+pub mod agent;
+pub mod object_cache;
+pub mod objects;
 pub mod region_handle;
 pub mod terrain;
@@ -0,0 +1,307 @@
+//! On-disk cache of the objects of regions.
+//!
+//! Instead of full updates sims send `ObjectUpdateCached` with the local id
+//! and CRC of the objects the viewer might have cached. Only objects which
+//! are not cached with the same CRC have to be requested with
+//! `RequestMultipleObjects`.
+//!
+//! The objects of a region are stored in one file named after the `cache_id`
+//! of the region, which the sim changes whenever the cached objects become
+//! invalid, e.g. because the local ids changed after a restart.
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use messages::MessageInstance;
+use object_update::Primitive;
+use packet::Packet;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
+use types::Uuid;
+
+/// Identifies cache files, followed by the version of the format.
+const MAGIC: &[u8; 4] = b"OSOC";
+const VERSION: u32 = 1;
+
+#[derive(Debug, Fail)]
+pub enum ObjectCacheError {
+    #[fail(display = "I/O error: {}", 0)]
+    IoError(#[cause] io::Error),
+    #[fail(display = "Not an object cache: {:?}", 0)]
+    InvalidFile(PathBuf),
+    #[fail(display = "Unsupported object cache version: {}", 0)]
+    UnsupportedVersion(u32),
+}
+
+impl From<io::Error> for ObjectCacheError {
+    fn from(e: io::Error) -> Self {
+        ObjectCacheError::IoError(e)
+    }
+}
+
+/// The directory containing the cached objects of all regions.
+#[derive(Clone, Debug)]
+pub struct ObjectCache {
+    dir: PathBuf,
+}
+
+impl ObjectCache {
+    /// The directory is created once the first region is saved.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        ObjectCache { dir: dir.into() }
+    }
+
+    /// Load the objects cached for the region with the given `cache_id`.
+    ///
+    /// If nothing was cached for the region yet, the cache is empty.
+    pub fn load_region(&self, cache_id: &Uuid) -> Result<RegionCache, ObjectCacheError> {
+        let mut cache = self.empty_region(cache_id);
+        let file = match File::open(&cache.path) {
+            Ok(file) => file,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(cache),
+            Err(e) => return Err(e.into()),
+        };
+        let mut reader = BufReader::new(file);
+
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != *MAGIC {
+            return Err(ObjectCacheError::InvalidFile(cache.path.clone()));
+        }
+        let version = reader.read_u32::<LittleEndian>()?;
+        if version != VERSION {
+            return Err(ObjectCacheError::UnsupportedVersion(version));
+        }
+
+        let count = reader.read_u32::<LittleEndian>()?;
+        for _ in 0..count {
+            let local_id = reader.read_u32::<LittleEndian>()?;
+            let crc = reader.read_u32::<LittleEndian>()?;
+            let size = reader.read_u32::<LittleEndian>()?;
+            let mut packet = Vec::new();
+            reader.by_ref().take(size as u64).read_to_end(&mut packet)?;
+            if packet.len() != size as usize {
+                return Err(ObjectCacheError::InvalidFile(cache.path.clone()));
+            }
+            cache.objects.insert(
+                local_id,
+                CachedObject {
+                    crc: crc,
+                    packet: packet,
+                },
+            );
+        }
+        Ok(cache)
+    }
+
+    /// Delete the objects cached for the region, e.g. because the file
+    /// could not be loaded, and return the now empty cache.
+    pub fn discard_region(&self, cache_id: &Uuid) -> Result<RegionCache, ObjectCacheError> {
+        let cache = self.empty_region(cache_id);
+        match fs::remove_file(&cache.path) {
+            Ok(()) => Ok(cache),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(cache),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn empty_region(&self, cache_id: &Uuid) -> RegionCache {
+        RegionCache {
+            path: self.dir.join(format!("{}.objects", cache_id)),
+            objects: HashMap::new(),
+            changed: false,
+        }
+    }
+}
+
+/// An object as it was received, a packet with an `ObjectUpdate` or
+/// `ObjectUpdateCompressed` containing only this object.
+struct CachedObject {
+    crc: u32,
+    packet: Vec<u8>,
+}
+
+/// The cached objects of one region.
+///
+/// Changes are saved when the cache is dropped, unless `save` was called.
+pub struct RegionCache {
+    path: PathBuf,
+    objects: HashMap<u32, CachedObject>,
+    /// Whether there are changes which were not saved yet.
+    changed: bool,
+}
+
+impl RegionCache {
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Whether the object is cached, independent of its CRC.
+    pub fn contains(&self, local_id: u32) -> bool {
+        self.objects.contains_key(&local_id)
+    }
+
+    /// The cached object, if it is cached with the given CRC.
+    pub fn get(&self, local_id: u32, crc: u32) -> Option<Primitive> {
+        let object = self.objects.get(&local_id)?;
+        if object.crc != crc {
+            return None;
+        }
+        let objects = match Packet::read(&object.packet).ok()?.message {
+            MessageInstance::ObjectUpdate(msg) => Primitive::from_object_update(&msg),
+            MessageInstance::ObjectUpdateCompressed(msg) => {
+                Primitive::from_object_update_compressed(&msg)
+            }
+            _ => return None,
+        };
+        objects.into_iter().next()?.ok()
+    }
+
+    /// Cache the object, `msg` must contain only this object.
+    pub fn insert<M: Into<MessageInstance>>(&mut self, local_id: u32, crc: u32, msg: M) {
+        let mut packet = Packet::new(msg, 0);
+        packet.set_zerocoded(true);
+        let mut data = Vec::new();
+        if packet.write_to(&mut data).is_ok() {
+            self.objects.insert(
+                local_id,
+                CachedObject {
+                    crc: crc,
+                    packet: data,
+                },
+            );
+            self.changed = true;
+        }
+    }
+
+    pub fn remove(&mut self, local_id: u32) {
+        if self.objects.remove(&local_id).is_some() {
+            self.changed = true;
+        }
+    }
+
+    /// Write the cache to disk, if it changed since it was loaded.
+    pub fn save(&mut self) -> Result<(), ObjectCacheError> {
+        if !self.changed {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        // Replace the old file only once the new one is complete.
+        let tmp_path = self.path.with_extension("tmp");
+        {
+            let mut writer = BufWriter::new(File::create(&tmp_path)?);
+            writer.write_all(MAGIC)?;
+            writer.write_u32::<LittleEndian>(VERSION)?;
+            writer.write_u32::<LittleEndian>(self.objects.len() as u32)?;
+            for (local_id, object) in &self.objects {
+                writer.write_u32::<LittleEndian>(*local_id)?;
+                writer.write_u32::<LittleEndian>(object.crc)?;
+                writer.write_u32::<LittleEndian>(object.packet.len() as u32)?;
+                writer.write_all(&object.packet)?;
+            }
+            writer.flush()?;
+        }
+        fs::rename(&tmp_path, &self.path)?;
+
+        self.changed = false;
+        Ok(())
+    }
+}
+
+impl Drop for RegionCache {
+    fn drop(&mut self) {
+        let _ = self.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use messages::all::{
+        ObjectUpdateCompressed, ObjectUpdateCompressed_ObjectData,
+        ObjectUpdateCompressed_RegionData,
+    };
+    use std::env;
+
+    fn cache(name: &str) -> ObjectCache {
+        let dir = env::temp_dir()
+            .join("opensim_networking_tests")
+            .join("object_cache")
+            .join(name);
+        let _ = fs::remove_dir_all(&dir);
+        ObjectCache::new(dir)
+    }
+
+    /// An update of a single tree.
+    fn tree() -> ObjectUpdateCompressed {
+        ObjectUpdateCompressed {
+            region_data: ObjectUpdateCompressed_RegionData {
+                region_handle: 0,
+                time_dilation: 0xffff,
+            },
+            object_data: vec![ObjectUpdateCompressed_ObjectData {
+                update_flags: 0,
+                data: include_bytes!("../../tests/data/object_update_compressed_tree.bin").to_vec(),
+            }],
+        }
+    }
+
+    #[test]
+    fn save_and_load() {
+        let cache = cache("save_and_load");
+        let cache_id = Uuid::from_bytes([1; 16]);
+        let tree = Primitive::from_object_update_compressed(&tree())
+            .pop()
+            .unwrap()
+            .unwrap();
+
+        {
+            let mut region = cache.load_region(&cache_id).unwrap();
+            assert!(region.is_empty());
+            region.insert(tree.local_id, tree.crc, tree());
+            region.save().unwrap();
+        }
+
+        let mut region = cache.load_region(&cache_id).unwrap();
+        assert_eq!(region.len(), 1);
+        assert_eq!(region.get(tree.local_id, tree.crc), Some(tree.clone()));
+        // The object changed since it was cached.
+        assert_eq!(region.get(tree.local_id, tree.crc + 1), None);
+        assert!(region.contains(tree.local_id));
+
+        // Changes are saved when the cache is dropped.
+        region.remove(tree.local_id);
+        drop(region);
+        assert!(cache.load_region(&cache_id).unwrap().is_empty());
+
+        // Other regions have their own cache.
+        let other = cache.load_region(&Uuid::from_bytes([2; 16])).unwrap();
+        assert!(other.is_empty());
+    }
+
+    #[test]
+    fn invalid_file() {
+        let cache = cache("invalid_file");
+        let cache_id = Uuid::from_bytes([1; 16]);
+        fs::create_dir_all(&cache.dir).unwrap();
+        fs::write(cache.dir.join(format!("{}.objects", cache_id)), b"invalid").unwrap();
+        match cache.load_region(&cache_id) {
+            Err(ObjectCacheError::InvalidFile(_)) => {}
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => panic!("invalid file was loaded"),
+        }
+
+        // The region starts over with an empty cache.
+        let region = cache.discard_region(&cache_id).unwrap();
+        assert!(region.is_empty());
+        drop(region);
+        assert!(cache.load_region(&cache_id).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,740 @@
+//! The objects of the region, i.e. prims, trees, grass and avatars.
+//!
+//! The sim describes objects with full updates, `ObjectUpdate` and
+//! `ObjectUpdateCompressed`, confirms objects the viewer has cached with
+//! `ObjectUpdateCached`, moves them with `ImprovedTerseObjectUpdate` and
+//! removes them with `KillObject`. The `ObjectService` keeps the resulting
+//! scene and reports all changes to its subscribers.
+//!
+//! Objects which are neither in the scene nor in the `object_cache` are
+//! requested from the sim with `RequestMultipleObjects`.
+
+use circuit::message_handlers::{self, HandlerContext};
+use futures::sync::mpsc;
+use logging::{Log, Logger};
+use messages::all::{
+    ImprovedTerseObjectUpdate, KillObject, ObjectUpdate, ObjectUpdateCached,
+    ObjectUpdateCompressed, RequestMultipleObjects, RequestMultipleObjects_AgentData,
+    RequestMultipleObjects_ObjectData,
+};
+use messages::{MessageInstance, MessageType};
+use object_update::{PCode, PrimFlags, Primitive, TerseUpdate};
+use services::object_cache::{ObjectCacheError, RegionCache};
+use std::collections::{hash_map, BTreeSet, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+use types::{UnitQuaternion, Uuid, Vector3};
+
+/// The object is not cached.
+const CACHE_MISS_TYPE_FULL: u8 = 0;
+/// The object is cached, but its CRC differs.
+const CACHE_MISS_TYPE_CRC: u8 = 1;
+
+/// The maximum number of objects in one `RequestMultipleObjects`.
+const MAX_REQUESTED_OBJECTS: usize = 255;
+
+/// A change of the scene.
+#[derive(Clone, Debug)]
+pub enum ObjectEvent {
+    /// An object the scene did not contain yet.
+    Added(Primitive),
+    /// A full update of an object already in the scene.
+    Updated(Primitive),
+    /// An object in the scene moved.
+    Moved(TerseUpdate),
+    /// The object was removed, children of a removed link set are reported
+    /// individually.
+    Removed(Primitive),
+}
+
+/// All objects of the region known to the viewer.
+///
+/// Objects are indexed by their local id, which is only valid in this
+/// region, and their full id.
+#[derive(Debug, Default)]
+pub struct Scene {
+    objects: HashMap<u32, Primitive>,
+    local_ids: HashMap<Uuid, u32>,
+    /// The children of each parent, which might not be known yet.
+    children: HashMap<u32, BTreeSet<u32>>,
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Scene::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Iterate over all objects, in no particular order.
+    pub fn iter(&self) -> hash_map::Values<u32, Primitive> {
+        self.objects.values()
+    }
+
+    pub fn get(&self, local_id: u32) -> Option<&Primitive> {
+        self.objects.get(&local_id)
+    }
+
+    pub fn get_by_full_id(&self, full_id: &Uuid) -> Option<&Primitive> {
+        self.local_ids
+            .get(full_id)
+            .and_then(|local_id| self.objects.get(local_id))
+    }
+
+    /// The objects directly linked to the object, including seated avatars.
+    pub fn children(&self, local_id: u32) -> Vec<&Primitive> {
+        match self.children.get(&local_id) {
+            Some(children) => children
+                .iter()
+                .filter_map(|id| self.objects.get(id))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// The root of the link set the object is part of, which is the object
+    /// itself for roots.
+    ///
+    /// Returns `None` if the object or one of its parents is not known, or
+    /// if the parents form a cycle.
+    pub fn root(&self, local_id: u32) -> Option<&Primitive> {
+        let mut object = self.objects.get(&local_id)?;
+        // Without a cycle the root is reached before visiting every object.
+        for _ in 0..self.objects.len() {
+            if object.parent_id == 0 {
+                return Some(object);
+            }
+            object = self.objects.get(&object.parent_id)?;
+        }
+        None
+    }
+
+    /// The root of the link set the object is part of followed by all of its
+    /// children, empty if there is no root as explained for `root`.
+    pub fn link_set(&self, local_id: u32) -> Vec<&Primitive> {
+        let mut link_set = Vec::new();
+        if let Some(root) = self.root(local_id) {
+            link_set.push(root);
+            let mut i = 0;
+            while i < link_set.len() {
+                let children = self.children(link_set[i].local_id);
+                link_set.extend(children);
+                i += 1;
+            }
+        }
+        link_set
+    }
+
+    pub fn avatars(&self) -> Vec<&Primitive> {
+        self.objects
+            .values()
+            .filter(|object| object.pcode == PCode::Avatar)
+            .collect()
+    }
+
+    /// The region local position of the object.
+    ///
+    /// The position of children is relative to their parent, so this returns
+    /// `None` if one of the parents is not known or the parents form a cycle.
+    pub fn region_position(&self, local_id: u32) -> Option<Vector3<f32>> {
+        let mut object = self.objects.get(&local_id)?;
+        let mut position = object.motion.position;
+        for _ in 0..self.objects.len() {
+            if object.parent_id == 0 {
+                return Some(position);
+            }
+            object = self.objects.get(&object.parent_id)?;
+            let rotation = UnitQuaternion::from_quaternion(object.motion.rotation);
+            position = object.motion.position + rotation * position;
+        }
+        None
+    }
+
+    /// Add the object, or replace it if it is already known.
+    pub(crate) fn insert(&mut self, object: Primitive) -> ObjectEvent {
+        let local_id = object.local_id;
+        self.local_ids.insert(object.full_id.clone(), local_id);
+        self.children
+            .entry(object.parent_id)
+            .or_insert_with(BTreeSet::new)
+            .insert(local_id);
+
+        match self.objects.insert(local_id, object.clone()) {
+            Some(old) => {
+                // The object was linked or unlinked.
+                if old.parent_id != object.parent_id {
+                    self.remove_child(old.parent_id, local_id);
+                }
+                ObjectEvent::Updated(object)
+            }
+            None => ObjectEvent::Added(object),
+        }
+    }
+
+    /// Move the object, returns `None` if it is not known.
+    pub(crate) fn apply(&mut self, update: TerseUpdate) -> Option<ObjectEvent> {
+        let object = self.objects.get_mut(&update.local_id)?;
+        update.apply(object);
+        Some(ObjectEvent::Moved(update))
+    }
+
+    /// Handle an object the sim expects to be cached.
+    ///
+    /// Returns whether the object is known with the same checksum, in which
+    /// case only its flags are updated.
+    pub(crate) fn update_cached(&mut self, local_id: u32, crc: u32, update_flags: u32) -> bool {
+        match self.objects.get_mut(&local_id) {
+            Some(ref mut object) if object.crc == crc => {
+                object.flags = PrimFlags::from_bits_truncate(update_flags);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove the object and its children.
+    ///
+    /// Seated avatars are not removed but unlinked, the sim will tell where
+    /// they are now.
+    pub(crate) fn remove(&mut self, local_id: u32) -> Vec<ObjectEvent> {
+        let mut events = Vec::new();
+        let object = match self.objects.remove(&local_id) {
+            Some(object) => object,
+            None => return events,
+        };
+        if self.local_ids.get(&object.full_id) == Some(&local_id) {
+            self.local_ids.remove(&object.full_id);
+        }
+        self.remove_child(object.parent_id, local_id);
+
+        for child in self.children.remove(&local_id).unwrap_or_default() {
+            let is_avatar = self
+                .objects
+                .get(&child)
+                .map_or(false, |child| child.pcode == PCode::Avatar);
+            if is_avatar {
+                if let Some(avatar) = self.objects.get_mut(&child) {
+                    avatar.parent_id = 0;
+                }
+                self.children
+                    .entry(0)
+                    .or_insert_with(BTreeSet::new)
+                    .insert(child);
+            } else {
+                events.extend(self.remove(child));
+            }
+        }
+        events.insert(0, ObjectEvent::Removed(object));
+        events
+    }
+
+    fn remove_child(&mut self, parent_id: u32, local_id: u32) {
+        let empty = match self.children.get_mut(&parent_id) {
+            Some(children) => {
+                children.remove(&local_id);
+                children.is_empty()
+            }
+            None => false,
+        };
+        if empty {
+            self.children.remove(&parent_id);
+        }
+    }
+}
+
+struct Inner {
+    agent_id: Uuid,
+    session_id: Uuid,
+    scene: Mutex<Scene>,
+    /// The objects cached for the region, if caching is enabled.
+    cache: Mutex<Option<RegionCache>>,
+    subscribers: Mutex<Vec<mpsc::UnboundedSender<ObjectEvent>>>,
+}
+
+impl Inner {
+    fn notify(&self, events: Vec<ObjectEvent>) {
+        if events.is_empty() {
+            return;
+        }
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|subscriber| {
+            events
+                .iter()
+                .all(|event| subscriber.unbounded_send(event.clone()).is_ok())
+        });
+    }
+
+    fn handle(
+        &self,
+        msg: MessageInstance,
+        context: &HandlerContext,
+        logger: &Logger,
+    ) -> Result<(), message_handlers::Error> {
+        let mut misses = Vec::new();
+        let events = {
+            let mut scene = self.scene.lock().unwrap();
+            let mut cache = self.cache.lock().unwrap();
+            match msg {
+                MessageInstance::ObjectUpdate(msg) => {
+                    object_update(&mut scene, cache.as_mut(), &msg, logger)
+                }
+                MessageInstance::ObjectUpdateCompressed(msg) => {
+                    object_update_compressed(&mut scene, cache.as_mut(), &msg, logger)
+                }
+                MessageInstance::ObjectUpdateCached(msg) => {
+                    object_update_cached(&mut scene, cache.as_ref(), &msg, &mut misses)
+                }
+                MessageInstance::ImprovedTerseObjectUpdate(msg) => {
+                    terse_update(&mut scene, &msg, logger)
+                }
+                MessageInstance::KillObject(msg) => kill_object(&mut scene, cache.as_mut(), &msg),
+                msg => {
+                    return Err(message_handlers::Error {
+                        msg: msg,
+                        kind: message_handlers::ErrorKind::WrongHandler,
+                    })
+                }
+            }
+        };
+
+        if !misses.is_empty() {
+            debug!(
+                logger,
+                "Requesting {} objects missing in the cache",
+                misses.len()
+            );
+        }
+        for chunk in misses.chunks(MAX_REQUESTED_OBJECTS) {
+            let request = RequestMultipleObjects {
+                agent_data: RequestMultipleObjects_AgentData {
+                    agent_id: self.agent_id.clone(),
+                    session_id: self.session_id.clone(),
+                },
+                object_data: chunk.to_vec(),
+            };
+            let _ = context.message_sender.send(request, true);
+        }
+
+        self.notify(events);
+        Ok(())
+    }
+}
+
+/// Keeps track of all objects in the region.
+#[derive(Clone)]
+pub struct ObjectService {
+    inner: Arc<Inner>,
+}
+
+impl ObjectService {
+    pub(crate) fn register(
+        handlers: &mut message_handlers::Handlers,
+        agent_id: Uuid,
+        session_id: Uuid,
+        log: &Log,
+    ) -> Self {
+        let inner = Arc::new(Inner {
+            agent_id: agent_id,
+            session_id: session_id,
+            scene: Mutex::new(Scene::new()),
+            cache: Mutex::new(None),
+            subscribers: Mutex::new(Vec::new()),
+        });
+        let logger = Logger::root(log.clone(), o!("service" => "ObjectService"));
+
+        let message_types = [
+            MessageType::ObjectUpdate,
+            MessageType::ObjectUpdateCompressed,
+            MessageType::ObjectUpdateCached,
+            MessageType::ImprovedTerseObjectUpdate,
+            MessageType::KillObject,
+        ];
+        for m_type in message_types.iter() {
+            let inner = Arc::clone(&inner);
+            let logger = logger.clone();
+            handlers.register_type(
+                m_type.clone(),
+                Box::new(move |msg: MessageInstance, context: &HandlerContext| {
+                    inner.handle(msg, context, &logger)
+                }),
+            );
+        }
+
+        ObjectService { inner: inner }
+    }
+
+    /// Lock the scene to query it.
+    ///
+    /// Incoming updates are blocked while the lock is held.
+    pub fn scene(&self) -> MutexGuard<Scene> {
+        self.inner.scene.lock().unwrap()
+    }
+
+    /// A copy of the object with the given local id.
+    pub fn get(&self, local_id: u32) -> Option<Primitive> {
+        self.scene().get(local_id).cloned()
+    }
+
+    /// A copy of the object with the given full id.
+    pub fn get_by_full_id(&self, full_id: &Uuid) -> Option<Primitive> {
+        self.scene().get_by_full_id(full_id).cloned()
+    }
+
+    /// Returns a stream of all changes of the scene from now on.
+    ///
+    /// Dropping the stream cancels the subscription.
+    pub fn subscribe(&self) -> mpsc::UnboundedReceiver<ObjectEvent> {
+        let (sender, receiver) = mpsc::unbounded();
+        self.inner.subscribers.lock().unwrap().push(sender);
+        receiver
+    }
+
+    /// Use the cached objects of the region, which has to happen before the
+    /// sim sends `ObjectUpdateCached`.
+    pub(crate) fn set_cache(&self, cache: RegionCache) {
+        *self.inner.cache.lock().unwrap() = Some(cache);
+    }
+
+    /// Write the cached objects to disk.
+    ///
+    /// This also happens when the service is dropped, but errors are lost
+    /// then.
+    pub fn save_cache(&self) -> Result<(), ObjectCacheError> {
+        match *self.inner.cache.lock().unwrap() {
+            Some(ref mut cache) => cache.save(),
+            None => Ok(()),
+        }
+    }
+}
+
+fn object_update(
+    scene: &mut Scene,
+    mut cache: Option<&mut RegionCache>,
+    msg: &ObjectUpdate,
+    logger: &Logger,
+) -> Vec<ObjectEvent> {
+    let mut events = Vec::new();
+    for data in &msg.object_data {
+        let object = match Primitive::from_object_data(data) {
+            Ok(object) => object,
+            Err(e) => {
+                debug!(logger, "Invalid ObjectUpdate: {}", e);
+                continue;
+            }
+        };
+        if let Some(ref mut cache) = cache {
+            if is_cacheable(&object) {
+                let msg = ObjectUpdate {
+                    region_data: msg.region_data.clone(),
+                    object_data: vec![data.clone()],
+                };
+                cache.insert(object.local_id, object.crc, msg);
+            }
+        }
+        events.push(scene.insert(object));
+    }
+    events
+}
+
+fn object_update_compressed(
+    scene: &mut Scene,
+    mut cache: Option<&mut RegionCache>,
+    msg: &ObjectUpdateCompressed,
+    logger: &Logger,
+) -> Vec<ObjectEvent> {
+    let mut events = Vec::new();
+    for data in &msg.object_data {
+        let object = match Primitive::from_compressed(data) {
+            Ok(object) => object,
+            Err(e) => {
+                debug!(logger, "Invalid ObjectUpdateCompressed: {}", e);
+                continue;
+            }
+        };
+        if let Some(ref mut cache) = cache {
+            if is_cacheable(&object) {
+                let msg = ObjectUpdateCompressed {
+                    region_data: msg.region_data.clone(),
+                    object_data: vec![data.clone()],
+                };
+                cache.insert(object.local_id, object.crc, msg);
+            }
+        }
+        events.push(scene.insert(object));
+    }
+    events
+}
+
+/// Avatars are never announced with `ObjectUpdateCached`.
+fn is_cacheable(object: &Primitive) -> bool {
+    object.pcode != PCode::Avatar
+}
+
+/// Take the objects from the scene or the cache, the others are added to
+/// `misses` to be requested from the sim.
+fn object_update_cached(
+    scene: &mut Scene,
+    cache: Option<&RegionCache>,
+    msg: &ObjectUpdateCached,
+    misses: &mut Vec<RequestMultipleObjects_ObjectData>,
+) -> Vec<ObjectEvent> {
+    let mut events = Vec::new();
+    for data in &msg.object_data {
+        if scene.update_cached(data.id, data.crc, data.update_flags) {
+            continue;
+        }
+        match cache.and_then(|cache| cache.get(data.id, data.crc)) {
+            Some(mut object) => {
+                object.flags = PrimFlags::from_bits_truncate(data.update_flags);
+                events.push(scene.insert(object));
+            }
+            None => {
+                let changed = cache.map_or(false, |cache| cache.contains(data.id));
+                misses.push(RequestMultipleObjects_ObjectData {
+                    cache_miss_type: if changed {
+                        CACHE_MISS_TYPE_CRC
+                    } else {
+                        CACHE_MISS_TYPE_FULL
+                    },
+                    id: data.id,
+                });
+            }
+        }
+    }
+    events
+}
+
+fn terse_update(
+    scene: &mut Scene,
+    msg: &ImprovedTerseObjectUpdate,
+    logger: &Logger,
+) -> Vec<ObjectEvent> {
+    let mut events = Vec::new();
+    for update in TerseUpdate::from_message(msg) {
+        match update {
+            Ok(update) => events.extend(scene.apply(update)),
+            Err(e) => debug!(logger, "Invalid ImprovedTerseObjectUpdate: {}", e),
+        }
+    }
+    events
+}
+
+fn kill_object(
+    scene: &mut Scene,
+    mut cache: Option<&mut RegionCache>,
+    msg: &KillObject,
+) -> Vec<ObjectEvent> {
+    let mut events = Vec::new();
+    for data in &msg.object_data {
+        let removed = scene.remove(data.id);
+        if let Some(ref mut cache) = cache {
+            for event in &removed {
+                if let ObjectEvent::Removed(ref object) = *event {
+                    cache.remove(object.local_id);
+                }
+            }
+            // The object might only be cached.
+            cache.remove(data.id);
+        }
+        events.extend(removed);
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object_update::{
+        ClickAction, ExtraParams, HoleType, Material, ObjectState, PathCurve, PathParams,
+        ProfileCurve, ProfileParams, Shape,
+    };
+    use types::{Quaternion, Vector4};
+
+    fn object(local_id: u32, parent_id: u32, pcode: PCode) -> Primitive {
+        Primitive {
+            local_id: local_id,
+            full_id: Uuid::from_bytes([local_id as u8; 16]),
+            parent_id: parent_id,
+            crc: 1,
+            pcode: pcode,
+            state: 0,
+            material: Material::Wood,
+            click_action: ClickAction::Touch,
+            scale: Vector3::new(1., 1., 1.),
+            motion: ObjectState {
+                collision_plane: None,
+                position: Vector3::new(1., 0., 0.),
+                velocity: Vector3::new(0., 0., 0.),
+                acceleration: Vector3::new(0., 0., 0.),
+                rotation: Quaternion::new(1., 0., 0., 0.),
+                angular_velocity: Vector3::new(0., 0., 0.),
+            },
+            flags: PrimFlags::empty(),
+            shape: Shape {
+                path: PathParams {
+                    curve: PathCurve::Line,
+                    begin: 0.,
+                    end: 1.,
+                    scale_x: 1.,
+                    scale_y: 1.,
+                    shear_x: 0.,
+                    shear_y: 0.,
+                    twist: 0.,
+                    twist_begin: 0.,
+                    radius_offset: 0.,
+                    taper_x: 0.,
+                    taper_y: 0.,
+                    revolutions: 1.,
+                    skew: 0.,
+                },
+                profile: ProfileParams {
+                    curve: ProfileCurve::Square,
+                    hole: HoleType::Same,
+                    begin: 0.,
+                    end: 1.,
+                    hollow: 0.,
+                },
+            },
+            texture_entry: None,
+            texture_animation: None,
+            name_values: Vec::new(),
+            data: Vec::new(),
+            text: None,
+            media_url: None,
+            particle_system: None,
+            extra_params: ExtraParams::default(),
+            sound: None,
+            joint: None,
+        }
+    }
+
+    fn ids(objects: Vec<&Primitive>) -> Vec<u32> {
+        objects.iter().map(|object| object.local_id).collect()
+    }
+
+    /// A root with a child prim, a grandchild and a seated avatar.
+    fn scene() -> Scene {
+        let mut scene = Scene::new();
+        // Children might arrive before their parents.
+        scene.insert(object(2, 1, PCode::Primitive));
+        scene.insert(object(3, 2, PCode::Primitive));
+        scene.insert(object(4, 1, PCode::Avatar));
+        match scene.insert(object(1, 0, PCode::Primitive)) {
+            ObjectEvent::Added(ref object) => assert_eq!(object.local_id, 1),
+            event => panic!("unexpected event: {:?}", event),
+        }
+        scene
+    }
+
+    #[test]
+    fn link_sets() {
+        let scene = scene();
+        assert_eq!(scene.len(), 4);
+        assert_eq!(ids(scene.children(1)), vec![2, 4]);
+        assert_eq!(scene.root(3).map(|root| root.local_id), Some(1));
+        assert_eq!(ids(scene.link_set(2)), vec![1, 2, 4, 3]);
+        assert_eq!(ids(scene.avatars()), vec![4]);
+        assert_eq!(
+            scene
+                .get_by_full_id(&Uuid::from_bytes([3; 16]))
+                .map(|object| object.local_id),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn region_position() {
+        let mut scene = scene();
+        let mut root = object(1, 0, PCode::Primitive);
+        root.motion.position = Vector3::new(128., 128., 20.);
+        // Rotated by 90 degrees around z.
+        root.motion.rotation = Quaternion::new(1., 0., 0., 1.);
+        match scene.insert(root) {
+            ObjectEvent::Updated(_) => {}
+            event => panic!("unexpected event: {:?}", event),
+        }
+
+        let position = scene.region_position(3).unwrap();
+        assert!((position - Vector3::new(128., 130., 20.)).norm() < 1e-5);
+        assert_eq!(scene.region_position(5), None);
+    }
+
+    #[test]
+    fn parent_cycle() {
+        let mut scene = scene();
+        scene.insert(object(5, 6, PCode::Primitive));
+        scene.insert(object(6, 5, PCode::Primitive));
+        scene.insert(object(7, 7, PCode::Primitive));
+        for &local_id in &[5, 6, 7] {
+            assert!(scene.root(local_id).is_none());
+            assert!(scene.link_set(local_id).is_empty());
+            assert_eq!(scene.region_position(local_id), None);
+        }
+        assert_eq!(ids(scene.link_set(3)), vec![1, 2, 4, 3]);
+    }
+
+    #[test]
+    fn relink() {
+        let mut scene = scene();
+        scene.insert(object(3, 1, PCode::Primitive));
+        assert_eq!(ids(scene.children(1)), vec![2, 3, 4]);
+        assert!(scene.children(2).is_empty());
+    }
+
+    #[test]
+    fn terse_and_cached_updates() {
+        let mut scene = scene();
+        let update = TerseUpdate {
+            local_id: 4,
+            state: 0,
+            collision_plane: Some(Vector4::new(0., 0., 1., 0.)),
+            position: Vector3::new(0., 0., 0.5),
+            velocity: Vector3::new(0., 0., 0.),
+            acceleration: Vector3::new(0., 0., 0.),
+            rotation: Quaternion::new(1., 0., 0., 0.),
+            angular_velocity: Vector3::new(0., 0., 0.),
+            texture_entry: None,
+        };
+        assert!(scene.apply(update.clone()).is_some());
+        assert_eq!(
+            scene.get(4).unwrap().motion.position,
+            Vector3::new(0., 0., 0.5)
+        );
+        assert!(scene
+            .apply(TerseUpdate {
+                local_id: 5,
+                ..update
+            })
+            .is_none());
+
+        assert!(scene.update_cached(2, 1, PrimFlags::OBJECT_MOVE.bits()));
+        assert_eq!(scene.get(2).unwrap().flags, PrimFlags::OBJECT_MOVE);
+        assert!(!scene.update_cached(2, 2, 0));
+        assert!(!scene.update_cached(5, 1, 0));
+    }
+
+    #[test]
+    fn remove_link_set() {
+        let mut scene = scene();
+        let removed: Vec<u32> = scene
+            .remove(1)
+            .iter()
+            .map(|event| match *event {
+                ObjectEvent::Removed(ref object) => object.local_id,
+                ref event => panic!("unexpected event: {:?}", event),
+            })
+            .collect();
+        assert_eq!(removed, vec![1, 2, 3]);
+
+        // The seated avatar stays.
+        assert_eq!(scene.len(), 1);
+        assert_eq!(scene.get(4).unwrap().parent_id, 0);
+        assert_eq!(scene.get_by_full_id(&Uuid::from_bytes([1; 16])), None);
+        assert!(scene.remove(1).is_empty());
+    }
+}
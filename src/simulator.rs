@@ -1,23 +1,39 @@
-use capabilities::{Capabilities, CapabilitiesError};
-use circuit::{message_handlers, Circuit, CircuitConfig, SendMessage};
+use capabilities::{Capabilities, CapabilitiesError, Capability};
+use circuit::{
+    message_handlers, Circuit, CircuitConfig, CircuitEvent, CircuitStats, MessageSender,
+    SendMessage, Subscription, Throttle,
+};
+use crossbeam_channel;
 use data::RegionInfo;
+use event_queue;
 use failure::Error;
+use futures::future::Either;
 use futures::prelude::{await, *};
+use grid_map::region_handle::RegionHandle;
 use hyper::Uri;
+use hypergrid;
 use logging::Log;
-use login::LoginResponse;
+use login::{LoginResponse, RegionAddress};
 use messages::all::{
-    CompleteAgentMovement, CompleteAgentMovement_AgentData, UseCircuitCode,
-    UseCircuitCode_CircuitCode,
+    CompleteAgentMovement, CompleteAgentMovement_AgentData, LogoutRequest, LogoutRequest_AgentData,
+    MapNameRequest, MapNameRequest_AgentData, MapNameRequest_NameData, RegionHandshakeReply,
+    RegionHandshakeReply_AgentData, RegionHandshakeReply_RegionInfo, TeleportFinish,
+    TeleportLocationRequest, TeleportLocationRequest_AgentData, TeleportLocationRequest_Info,
+    UseCircuitCode, UseCircuitCode_CircuitCode,
 };
-use messages::MessageInstance;
+use messages::{MessageInstance, MessageType};
+use services::agent::AgentPosition;
+use services::object_cache::ObjectCache;
+use services::objects::ObjectService;
 use services::{self, CircuitData, CircuitDataHandle, Service};
-use std::sync::Mutex;
-use systems::agent_update::{AgentState, Modality};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use systems::agent_update::AgentState;
 use textures::{GetTexture, TextureService};
 use tokio_core::reactor::{self, Handle};
-use types::{Duration, Ip4Addr, UnitQuaternion, Uuid, Vector3};
+use types::{Duration, Ip4Addr, Uuid, Vector3};
 use url::Url;
+use util::{self, AtomicU32Counter};
 
 // TODO: Reconsider how useful this is.
 // It might actually just be making something rather convenient which should
@@ -38,6 +54,12 @@ pub struct ConnectInfo {
     pub circuit_code: u32,
     pub sim_ip: Ip4Addr,
     pub sim_port: u16,
+
+    /// The capabilities to request from the sim.
+    pub requested_capabilities: Vec<Capability>,
+
+    /// Where the objects of regions are cached, `None` disables caching.
+    pub object_cache: Option<ObjectCache>,
 }
 
 impl From<LoginResponse> for ConnectInfo {
@@ -49,11 +71,39 @@ impl From<LoginResponse> for ConnectInfo {
             circuit_code: l.circuit_code,
             sim_ip: l.sim_ip,
             sim_port: l.sim_port,
+            requested_capabilities: Capability::default_requested(),
+            object_cache: None,
         }
     }
 }
 
+impl ConnectInfo {
+    /// The connect info for the destination sim of a teleport, which arrives
+    /// with `TeleportFinish`.
+    ///
+    /// This also works for hypergrid teleports, then the sim is part of a
+    /// foreign grid.
+    pub fn for_teleport(&self, finish: &TeleportFinish) -> Result<ConnectInfo, ::url::ParseError> {
+        // The seed capability might be nul terminated.
+        let seed = String::from_utf8_lossy(&finish.info.seed_capability);
+        let seed = seed.trim_end_matches('\0');
+
+        Ok(ConnectInfo {
+            capabilities_seed: Url::parse(seed)?,
+            agent_id: self.agent_id.clone(),
+            session_id: self.session_id.clone(),
+            circuit_code: self.circuit_code,
+            sim_ip: finish.info.sim_ip,
+            sim_port: finish.info.sim_port,
+            requested_capabilities: self.requested_capabilities.clone(),
+            object_cache: self.object_cache.clone(),
+        })
+    }
+}
+
 pub struct Services {
+    pub agent: services::agent::AgentService,
+    pub objects: services::objects::ObjectService,
     pub region_handle: services::region_handle::LookupService,
     pub terrain: services::terrain::TerrainService,
 }
@@ -61,13 +111,26 @@ pub struct Services {
 /// This struct manages all connections from the viewer to a (single) simulator
 /// instance.
 pub struct Simulator {
-    caps: Mutex<Capabilities>,
+    caps: Arc<Mutex<Capabilities>>,
     circuit: Mutex<Circuit>,
-    texture_service: Mutex<TextureService>,
+    texture_service: Arc<Mutex<TextureService>>,
     services: Services,
+    /// The data the services use to talk to the sim.
+    circuit_data: CircuitDataHandle,
 
     handle: Handle,
     locator: SimLocator,
+    connect_info: ConnectInfo,
+    agent_id: Uuid,
+    session_id: Uuid,
+    circuit_code: u32,
+    log: Log,
+
+    /// Generation counter of the `AgentThrottle` messages we sent.
+    throttle_counter: AtomicU32Counter,
+
+    /// Set to stop polling the event queue.
+    event_queue_shutdown: Arc<AtomicBool>,
 
     // TODO: (future) can this be updated remotely somehow, i.e. by the estate manager?
     // If yes we should register appropriate message handlers which update this data,
@@ -91,12 +154,49 @@ pub enum ConnectError {
     Msg(String),
 }
 
+#[derive(Debug, Fail)]
+pub enum LogoutError {
+    #[fail(display = "Closing the circuit failed: {}", 0)]
+    CloseCircuit(#[cause] ::circuit::CloseCircuitError),
+    #[fail(display = "The circuit is not available anymore.")]
+    CircuitPoisoned,
+}
+
 impl Simulator {
+    /// Connect to the sim the agent logged into and move the agent into its
+    /// region.
+    ///
+    /// The returned future can be waited on without running the reactor of
+    /// `handle`, but the event queue is only polled while it runs.
     pub fn connect(
         connect_info: ConnectInfo,
         handlers: message_handlers::Handlers,
         handle: Handle,
         log: Log,
+    ) -> impl Future<Item = Simulator, Error = Error> {
+        Self::connect_agent(connect_info, handlers, handle, log, false)
+    }
+
+    /// Connect to a neighbour sim hosting a child agent, after it was
+    /// announced with `EnableSimulator`.
+    ///
+    /// In contrast to `connect` the agent is not moved into the region, the
+    /// child agent only receives what happens in it.
+    pub fn connect_child(
+        connect_info: ConnectInfo,
+        handlers: message_handlers::Handlers,
+        handle: Handle,
+        log: Log,
+    ) -> impl Future<Item = Simulator, Error = Error> {
+        Self::connect_agent(connect_info, handlers, handle, log, true)
+    }
+
+    fn connect_agent(
+        connect_info: ConnectInfo,
+        handlers: message_handlers::Handlers,
+        handle: Handle,
+        log: Log,
+        child: bool,
     ) -> impl Future<Item = Simulator, Error = Error> {
         async_block! {
             let capabilities = await!(Self::setup_capabilities(
@@ -106,12 +206,30 @@ impl Simulator {
 
             let mut handlers = handlers;
             let circuit_data_handle = CircuitDataHandle::new();
+            let objects = services::objects::ObjectService::register(&mut handlers, connect_info.agent_id.clone(), connect_info.session_id.clone(), &log);
             let services = Services {
+                agent: services::agent::AgentService::register(&mut handlers, connect_info.agent_id.clone(), &objects, &log),
+                objects: objects.clone(),
                 region_handle: services::region_handle::LookupService::register_service(&mut handlers, circuit_data_handle.clone(), &log),
                 terrain: services::terrain::TerrainService::register_service(&mut handlers, circuit_data_handle.clone(), &log),
             };
+            let movement_complete = services.agent.movement_complete();
 
-            let (circuit, region_info) = await!(Self::setup_circuit(connect_info.clone(), handlers, handle.remote().clone(), log.clone()))?;
+            let (circuit, region_info) = await!(Self::setup_circuit(connect_info.clone(), handlers, objects, handle.remote().clone(), log.clone(), child))?;
+
+            // Wait until the sim moved the agent into the region, then tell it
+            // where we are looking.
+            if !child {
+                let timeout = util::delay(Duration::from_millis(15_000));
+                let position = match await!(movement_complete.select2(timeout)) {
+                    Ok(Either::A((position, _))) => position,
+                    _ => return Err(ConnectError::Msg("Did not receive AgentMovementComplete".into()).into()),
+                };
+                info!(log.slog_logger(), "Agent arrived at {:?}", position);
+                let agent_state = AgentState::new(position.position);
+                let message = agent_state.to_update_message(connect_info.agent_id.clone(), connect_info.session_id.clone());
+                await!(circuit.send(message, true))?;
+            }
 
             // Update circuit_data_handle.
             circuit_data_handle.set(CircuitData {
@@ -120,6 +238,22 @@ impl Simulator {
                 region_id: region_info.region_id.clone(),
             });
 
+            // Poll the event queue, if the sim provides one.
+            let event_queue_shutdown = Arc::new(AtomicBool::new(false));
+            if let Some(url) = capabilities.get(&Capability::EventQueueGet) {
+                // TODO see: https://github.com/hyperium/hyper/issues/1219
+                let uri: Uri = url.as_str().parse().unwrap();
+                let logger = log.slog_logger();
+                let poll = event_queue::poll(
+                    uri,
+                    circuit.event_dispatcher(),
+                    Arc::clone(&event_queue_shutdown),
+                    handle.clone(),
+                    logger.clone(),
+                );
+                handle.spawn(poll.map_err(move |e| warn!(logger, "Event queue failed: {}", e)));
+            }
+
             // TODO: Move into Services.
             let texture_service = Self::setup_texture_service(&capabilities, log.clone());
             let locator = SimLocator {
@@ -129,21 +263,89 @@ impl Simulator {
 
             Ok(Simulator {
                 // TODO replace with circuit_data (or rename to sim_data)?
-                caps: Mutex::new(capabilities),
+                caps: Arc::new(Mutex::new(capabilities)),
                 circuit: Mutex::new(circuit),
                 region_info: region_info,
                 services: services,
-                texture_service: Mutex::new(texture_service),
+                circuit_data: circuit_data_handle,
+                texture_service: Arc::new(Mutex::new(texture_service)),
                 handle: handle,
                 locator: locator,
+                agent_id: connect_info.agent_id.clone(),
+                session_id: connect_info.session_id.clone(),
+                circuit_code: connect_info.circuit_code,
+                connect_info: connect_info,
+                log: log,
+                // The initial throttle was sent by setup_circuit.
+                throttle_counter: AtomicU32Counter::new(1),
+                event_queue_shutdown: event_queue_shutdown,
             })
         }
     }
 
+    /// Log the agent out and close the circuit to the simulator.
+    ///
+    /// This sends a `LogoutRequest` and waits at most `timeout` for the sim
+    /// to reply with `LogoutReply`, then closes the circuit. If there is no
+    /// reply in time the circuit will be closed anyway. The returned future
+    /// can be waited on without running the reactor.
+    pub fn logout(self, timeout: Duration) -> impl Future<Item = (), Error = Error> {
+        async_block! {
+            self.event_queue_shutdown.store(true, Ordering::SeqCst);
+            let logger = self.log.slog_logger();
+            if let Err(e) = self.services.objects.save_cache() {
+                warn!(logger, "Saving the object cache failed: {}", e);
+            }
+            let circuit = self.circuit.into_inner().map_err(|_| LogoutError::CircuitPoisoned)?;
+
+            // Subscribe before sending the request, so the reply can't be missed.
+            let reply = circuit.subscribe(MessageType::LogoutReply).into_future();
+            let message = LogoutRequest {
+                agent_data: LogoutRequest_AgentData {
+                    agent_id: self.agent_id,
+                    session_id: self.session_id,
+                },
+            };
+            // The LogoutReply confirms the request was received, so there is
+            // no need to wait for the ack.
+            let _ = circuit.send(message, true);
+
+            let timeout = util::delay(timeout);
+            match await!(reply.select2(timeout)) {
+                Ok(Either::A(((Some(_), _), _))) => {
+                    info!(logger, "Logged out from simulator successfully.");
+                }
+                _ => warn!(logger, "No LogoutReply received, closing circuit anyway."),
+            }
+
+            circuit.close().map_err(LogoutError::CloseCircuit)?;
+            Ok(())
+        }
+    }
+
+    /// Close the circuit to the simulator without logging out.
+    ///
+    /// This is how connections to neighbour sims are torn down, for the sim
+    /// of the root agent use `logout`.
+    pub fn disconnect(self) -> Result<(), Error> {
+        self.event_queue_shutdown.store(true, Ordering::SeqCst);
+        let circuit = self
+            .circuit
+            .into_inner()
+            .map_err(|_| LogoutError::CircuitPoisoned)?;
+        circuit.close().map_err(LogoutError::CloseCircuit)?;
+        Ok(())
+    }
+
     pub fn locator(&self) -> SimLocator {
         self.locator.clone()
     }
 
+    /// The information this sim was connected with.
+    pub fn connect_info(&self) -> &ConnectInfo {
+        &self.connect_info
+    }
+
     pub fn services(&self) -> &Services {
         &self.services
     }
@@ -152,6 +354,180 @@ impl Simulator {
         &self.region_info
     }
 
+    /// The last known position of the agent.
+    ///
+    /// This is `None` for child agents, the sim only tells the agent where it
+    /// is after moving it into the region with `CompleteAgentMovement`.
+    pub fn agent_position(&self) -> Option<AgentPosition> {
+        self.services.agent.position()
+    }
+
+    /// Returns the capabilities currently granted by the sim.
+    pub fn capabilities(&self) -> Capabilities {
+        self.caps.lock().unwrap().clone()
+    }
+
+    /// Request the capabilities from the seed capability again and replace
+    /// the current ones.
+    ///
+    /// This is needed when the sim rotated the URLs of its capabilities.
+    pub fn refresh_capabilities(&self) -> impl Future<Item = (), Error = Error> {
+        let caps = Arc::clone(&self.caps);
+        let texture_service = Arc::clone(&self.texture_service);
+        let circuit_data = self.circuit_data.clone();
+        let refresh = self.caps.lock().unwrap().refresh(self.handle.clone());
+        refresh
+            .map(move |new_caps| {
+                texture_service
+                    .lock()
+                    .unwrap()
+                    .update_capabilities(&new_caps);
+                let data = circuit_data.unwrap();
+                circuit_data.set(CircuitData {
+                    capabilities: new_caps.clone(),
+                    region_id: data.region_id.clone(),
+                    message_sender: data.message_sender.clone(),
+                });
+                *caps.lock().unwrap() = new_caps;
+            })
+            .map_err(|e| e.into())
+    }
+
+    /// Move the agent into the region of this sim, making it the root agent.
+    ///
+    /// This is needed when the agent crossed into the region of a neighbour
+    /// sim connected with `connect_child`. Afterwards the capabilities are
+    /// requested again, as the sim grants more of them to root agents, and
+    /// the services are switched over to them.
+    pub fn promote_to_root(&self) -> impl Future<Item = (), Error = Error> {
+        let message = complete_agent_movement(&self.connect_info);
+        let complete = self.send_message(message, true);
+        let refresh = self.refresh_capabilities();
+        complete.map_err(Error::from).and_then(|_| refresh)
+    }
+
+    /// Returns the current statistics of the circuit to the simulator.
+    pub fn circuit_stats(&self) -> CircuitStats {
+        self.circuit.lock().unwrap().stats()
+    }
+
+    /// Tell the sim how much bandwidth we accept for the different categories
+    /// of traffic it sends to us.
+    ///
+    /// Initially `Throttle::default()` is used.
+    pub fn set_throttle(&self, throttle: &Throttle) -> SendMessage {
+        let message = throttle.to_message(
+            self.agent_id.clone(),
+            self.session_id.clone(),
+            self.circuit_code,
+            self.throttle_counter.next(),
+        );
+        self.send_message(message, true)
+    }
+
+    /// Request a teleport to a position in the region with the given handle.
+    ///
+    /// The sim answers with `TeleportStart`, `TeleportProgress` and finally
+    /// `TeleportFinish` or `TeleportFailed`. `ConnectInfo::for_teleport`
+    /// provides the information to connect to the destination,
+    /// `systems::teleport::Teleport` takes care of all of this.
+    pub fn teleport_location(
+        &self,
+        region_handle: &RegionHandle,
+        position: Vector3<f32>,
+        look_at: Vector3<f32>,
+    ) -> SendMessage {
+        let message = TeleportLocationRequest {
+            agent_data: TeleportLocationRequest_AgentData {
+                agent_id: self.agent_id.clone(),
+                session_id: self.session_id.clone(),
+            },
+            info: TeleportLocationRequest_Info {
+                region_handle: region_handle.handle(),
+                position: position,
+                look_at: look_at,
+            },
+        };
+        self.send_message(message, true)
+    }
+
+    /// Request a teleport to a region of a foreign grid.
+    ///
+    /// Sims only teleport to hyperlinks they created themselves, so the sim
+    /// is asked to link the region with a `MapNameRequest` first. It contacts
+    /// the gatekeeper of the foreign grid and replies with a `MapBlockReply`
+    /// containing the local handle of the hyperlink, the teleport to it is
+    /// then requested like with `teleport_location`.
+    ///
+    /// Returns the handle of the hyperlink.
+    pub fn teleport_hypergrid(
+        &self,
+        address: RegionAddress,
+        position: Vector3<f32>,
+        look_at: Vector3<f32>,
+    ) -> impl Future<Item = RegionHandle, Error = Error> {
+        let agent_id = self.agent_id.clone();
+        let session_id = self.session_id.clone();
+        let sender = self.message_sender();
+        let handle = self.handle.clone();
+        // Subscribe before sending the request, so the reply is not missed.
+        let replies = self.subscribe(MessageType::MapBlockReply);
+
+        async_block! {
+            let gatekeeper = match address.gatekeeper.clone() {
+                Some(gatekeeper) => gatekeeper,
+                None => return Err(Error::from(hypergrid::HypergridError::NotHypergrid(address))),
+            };
+            let name = hypergrid::hyperlink_name(&gatekeeper, &address.region);
+            let request = MapNameRequest {
+                agent_data: MapNameRequest_AgentData {
+                    agent_id: agent_id.clone(),
+                    session_id: session_id.clone(),
+                    flags: MAP_LAYER_FLAG,
+                    // Filled in by the sim.
+                    estate_id: 0,
+                    godlike: false,
+                },
+                name_data: MapNameRequest_NameData {
+                    name: name.clone().into_bytes(),
+                },
+            };
+            await!(sender.send(request, true))?;
+
+            let hyperlink = replies
+                .filter_map(move |msg| match msg {
+                    MessageInstance::MapBlockReply(reply) => hypergrid::find_hyperlink(&reply, &name),
+                    _ => None,
+                })
+                .into_future();
+            let timeout = reactor::Timeout::new(Duration::from_millis(HYPERGRID_LINK_TIMEOUT_MS), &handle)?;
+            let region_handle = match await!(hyperlink.select2(timeout)) {
+                Ok(Either::A(((Some(Some(region_handle)), _), _))) => region_handle,
+                _ => return Err(Error::from(hypergrid::HypergridError::NotLinked(address))),
+            };
+
+            let message = TeleportLocationRequest {
+                agent_data: TeleportLocationRequest_AgentData {
+                    agent_id: agent_id,
+                    session_id: session_id,
+                },
+                info: TeleportLocationRequest_Info {
+                    region_handle: region_handle.handle(),
+                    position: position,
+                    look_at: look_at,
+                },
+            };
+            await!(sender.send(message, true))?;
+            Ok(region_handle)
+        }
+    }
+
+    /// Returns a sender for messages to the sim, which can be moved to other
+    /// threads and futures.
+    pub fn message_sender(&self) -> MessageSender {
+        self.circuit.lock().unwrap().message_sender()
+    }
+
     pub fn send_message<M: Into<MessageInstance>>(
         &self,
         message: M,
@@ -160,6 +536,29 @@ impl Simulator {
         self.circuit.lock().unwrap().send(message, reliable)
     }
 
+    /// Subscribe to all messages of a specific type received from the sim.
+    ///
+    /// See `Circuit::subscribe`.
+    pub fn subscribe(&self, m_type: MessageType) -> Subscription {
+        self.circuit.lock().unwrap().subscribe(m_type)
+    }
+
+    /// Subscribe to all messages received from the sim for which the filter
+    /// evaluates to true.
+    pub fn subscribe_filter(
+        &self,
+        filter: Box<Fn(&MessageInstance) -> bool + Send>,
+    ) -> Subscription {
+        self.circuit.lock().unwrap().subscribe_filter(filter)
+    }
+
+    /// Returns the channel on which problems of the circuit are reported.
+    ///
+    /// See `Circuit::events`.
+    pub fn circuit_events(&self) -> crossbeam_channel::Receiver<CircuitEvent> {
+        self.circuit.lock().unwrap().events()
+    }
+
     /// To call this method you need to use `EventLoop::run_with_handle`.
     pub fn get_texture(&self, id: &Uuid, handle: &Handle) -> GetTexture {
         self.texture_service.lock().unwrap().get_texture(id, handle)
@@ -172,13 +571,18 @@ impl Simulator {
         //connect_info: &ConnectInfo,
         connect_info: ConnectInfo,
         handlers: message_handlers::Handlers,
+        objects: ObjectService,
         reactor_remote: reactor::Remote,
         log: Log,
         //log: &Log,
+        child: bool,
     ) -> Result<(Circuit, RegionInfo), Error> {
         let config = CircuitConfig {
             send_timeout: Duration::from_millis(5000),
             send_attempts: 5,
+            ping_interval: Duration::from_millis(5000),
+            receive_timeout: Duration::from_millis(30_000),
+            send_throttle: Some(Throttle::default()),
         };
         let agent_id = connect_info.agent_id.clone();
         let session_id = connect_info.session_id.clone();
@@ -209,29 +613,44 @@ impl Simulator {
             "Connected to simulator successfully, received region_info: {:?}", region_info
         );
 
-        let message = CompleteAgentMovement {
-            agent_data: CompleteAgentMovement_AgentData {
-                agent_id: agent_id.clone(),
-                session_id: session_id.clone(),
-                circuit_code: circuit_code,
+        // The sim only announces objects with ObjectUpdateCached if we tell
+        // it that we have cached some.
+        let cached = match connect_info.object_cache {
+            Some(ref cache) => match cache.load_region(&region_info.cache_id) {
+                Ok(region_cache) => {
+                    let cached = !region_cache.is_empty();
+                    objects.set_cache(region_cache);
+                    cached
+                }
+                Err(e) => {
+                    warn!(
+                        log.slog_logger(),
+                        "Loading the object cache failed, discarding it: {}", e
+                    );
+                    match cache.discard_region(&region_info.cache_id) {
+                        Ok(region_cache) => objects.set_cache(region_cache),
+                        Err(e) => warn!(
+                            log.slog_logger(),
+                            "Discarding the object cache failed: {}", e
+                        ),
+                    }
+                    false
+                }
             },
+            None => false,
         };
-        await!(circuit.send(message, true))?;
+        await!(circuit.send(region_handshake_reply(&connect_info, cached), true))?;
 
-        // let region_x = 256000.;
-        // let region_y = 256000.;
-        let local_x = 10.;
-        let local_y = 10.;
-
-        let z_axis = Vector3::z_axis();
-        let agent_state = AgentState {
-            position: Vector3::new(local_x, local_y, 0.),
-            move_direction: None,
-            modality: Modality::Walking,
-            body_rotation: UnitQuaternion::from_axis_angle(&z_axis, 0.),
-            head_rotation: UnitQuaternion::from_axis_angle(&z_axis, 0.),
-        };
-        let message = agent_state.to_update_message(agent_id, session_id);
+        let throttle = Throttle::default();
+        let message = throttle.to_message(agent_id.clone(), session_id.clone(), circuit_code, 0);
+
+        // Child agents stay where they are.
+        if child {
+            await!(circuit.send(message, true))?;
+            return Ok((circuit, region_info));
+        }
+
+        await!(circuit.send(complete_agent_movement(&connect_info), true))?;
         await!(circuit.send(message, true))?;
 
         Ok((circuit, region_info))
@@ -251,10 +670,52 @@ impl Simulator {
         */
         // TODO see: https://github.com/hyperium/hyper/issues/1219
         let caps_seed_uri: Uri = info.capabilities_seed.into_string().parse().unwrap();
-        await!(Capabilities::setup_capabilities(caps_seed_uri, handle)).map_err(|e| e.into())
+        await!(Capabilities::setup_capabilities(
+            caps_seed_uri,
+            info.requested_capabilities,
+            handle
+        ))
+        .map_err(|e| e.into())
     }
 
     fn setup_texture_service(caps: &Capabilities, log: Log) -> TextureService {
         TextureService::new(caps, log)
     }
 }
+
+/// How long to wait for the sim to link a region of a foreign grid, which
+/// involves contacting the foreign gatekeeper.
+const HYPERGRID_LINK_TIMEOUT_MS: u64 = 30_000;
+
+/// The map layer of region information, as opposed to map items.
+const MAP_LAYER_FLAG: u32 = 2;
+
+/// Asks the sim to send full updates of all cacheable objects right away
+/// instead of `ObjectUpdateCached`, as none of them are cached.
+const REGION_HANDSHAKE_CACHE_EMPTY: u32 = 1;
+
+fn region_handshake_reply(connect_info: &ConnectInfo, cached: bool) -> RegionHandshakeReply {
+    RegionHandshakeReply {
+        agent_data: RegionHandshakeReply_AgentData {
+            agent_id: connect_info.agent_id.clone(),
+            session_id: connect_info.session_id.clone(),
+        },
+        region_info: RegionHandshakeReply_RegionInfo {
+            flags: if cached {
+                0
+            } else {
+                REGION_HANDSHAKE_CACHE_EMPTY
+            },
+        },
+    }
+}
+
+fn complete_agent_movement(connect_info: &ConnectInfo) -> CompleteAgentMovement {
+    CompleteAgentMovement {
+        agent_data: CompleteAgentMovement_AgentData {
+            agent_id: connect_info.agent_id.clone(),
+            session_id: connect_info.session_id.clone(),
+            circuit_code: connect_info.circuit_code,
+        },
+    }
+}
@@ -1,9 +1,21 @@
-// TODO: The idea for this module is to implement some logic which takes care
-// of maintaining a local instance about agent state and having this be
-// automatically sent to the sim at a regular interval.
+//! Maintains the local state of the agent and sends it to the sim.
+//!
+//! The sim needs to receive `AgentUpdate` messages regularly: they control
+//! the movement of the avatar and tell the sim where the camera is, which
+//! decides which objects the sim sends. `AgentUpdater` sends the shared
+//! `AgentState` at a fixed rate while it changes, and otherwise only as a
+//! keep-alive, suppressing duplicate updates the same way the official viewer
+//! does.
 
+use circuit::{MessageSender, SendMessage, SendMessageError};
+use futures::{Async, Future, Poll, Stream};
+use messages::all::{SetAlwaysRun, SetAlwaysRun_AgentData};
 use messages::{AgentUpdate, AgentUpdate_AgentData};
-use types::{UnitQuaternion, Uuid, Vector3};
+use simulator::Simulator;
+use std::io;
+use std::sync::{Arc, Mutex};
+use tokio_core::reactor::{Handle, Interval};
+use types::{Duration, Instant, Quaternion, UnitQuaternion, Uuid, Vector3};
 
 bitflags! {
     /// Agent Updates contain a set of flags which inform the sim about the current status
@@ -35,6 +47,7 @@ bitflags! {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MoveDirection {
     Forward,
     Backward,
@@ -57,11 +70,15 @@ impl MoveDirection {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Modality {
     /// The default.
     Walking,
 
     /// Fast version of walking.
+    ///
+    /// Running is not part of the `AgentUpdate`, `AgentUpdater` tells the sim
+    /// with `SetAlwaysRun` when the agent starts or stops running.
     Running,
 
     /// Flying.
@@ -71,6 +88,36 @@ pub enum Modality {
     Sitting,
 }
 
+/// The default draw distance in meters.
+const DEFAULT_FAR: f32 = 64.;
+
+/// The viewpoint of the agent, the sim sends the objects visible from it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Camera {
+    /// Region local position of the camera.
+    pub center: Vector3<f32>,
+    /// Direction the camera is looking at.
+    pub at_axis: Vector3<f32>,
+    pub left_axis: Vector3<f32>,
+    pub up_axis: Vector3<f32>,
+    /// The draw distance.
+    pub far: f32,
+}
+
+impl Camera {
+    /// A camera at the position of the agent, looking in the direction the
+    /// body is facing.
+    pub fn at_agent(position: Vector3<f32>, body_rotation: &UnitQuaternion<f32>) -> Camera {
+        Camera {
+            center: position,
+            at_axis: body_rotation * Vector3::x(),
+            left_axis: body_rotation * Vector3::y(),
+            up_axis: body_rotation * Vector3::z(),
+            far: DEFAULT_FAR,
+        }
+    }
+}
+
 pub struct AgentState {
     /// The region local coordinates.
     pub position: Vector3<f32>,
@@ -83,9 +130,24 @@ pub struct AgentState {
 
     pub body_rotation: UnitQuaternion<f32>,
     pub head_rotation: UnitQuaternion<f32>,
+
+    pub camera: Camera,
 }
 
 impl AgentState {
+    /// An agent standing at a position, with the camera at the agent.
+    pub fn new(position: Vector3<f32>) -> AgentState {
+        let rotation = UnitQuaternion::identity();
+        AgentState {
+            position: position,
+            move_direction: None,
+            modality: Modality::Walking,
+            body_rotation: rotation,
+            head_rotation: rotation,
+            camera: Camera::at_agent(position, &rotation),
+        }
+    }
+
     pub fn to_control_flags(&self) -> ControlFlags {
         let mut flags = match self.move_direction {
             None => ControlFlags::EMPTY,
@@ -97,11 +159,9 @@ impl AgentState {
             Some(MoveDirection::Down) => ControlFlags::MOVE_UP_NEG,
         };
 
-        // TODO: fast movement / running
-
         match self.modality {
-            Modality::Walking => {}
-            Modality::Running => panic!("running is not yet implemented"), // TODO
+            // Running is enabled with SetAlwaysRun.
+            Modality::Walking | Modality::Running => {}
             Modality::Flying => flags.insert(ControlFlags::FLY),
             Modality::Sitting => {}
         }
@@ -117,14 +177,216 @@ impl AgentState {
                 body_rotation: self.body_rotation.quaternion().clone(),
                 head_rotation: self.head_rotation.quaternion().clone(),
                 state: 0,
-                camera_center: self.position,
-                camera_at_axis: self.position + Vector3::new(1., 0., 0.),
-                camera_left_axis: self.position + Vector3::new(0., 1., 0.),
-                camera_up_axis: self.position + Vector3::new(0., 0., 1.),
-                far: 0.,
+                camera_center: self.camera.center,
+                camera_at_axis: self.camera.at_axis,
+                camera_left_axis: self.camera.left_axis,
+                camera_up_axis: self.camera.up_axis,
+                far: self.camera.far,
                 control_flags: self.to_control_flags().bits(),
                 flags: 0,
             },
         }
     }
 }
+
+/// Configuration of an `AgentUpdater`.
+#[derive(Clone, Debug)]
+pub struct AgentUpdaterConfig {
+    /// Interval of the updates while the state is changing.
+    pub update_interval: Duration,
+    /// Interval of the updates while the state does not change.
+    pub keep_alive_interval: Duration,
+}
+
+impl Default for AgentUpdaterConfig {
+    fn default() -> Self {
+        // The official viewer sends up to 10 updates per second.
+        AgentUpdaterConfig {
+            update_interval: Duration::from_millis(100),
+            keep_alive_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Number of times an update is repeated after a change, before further
+/// duplicates are suppressed. The updates are sent unreliably.
+const DUPLICATE_UPDATES: u32 = 1;
+
+/// Rotations closer than this (as dot product) are considered the same.
+const ROTATION_THRESHOLD: f32 = 0.9999;
+/// Camera movements shorter than this (in meters) are not considered a change.
+const CAMERA_THRESHOLD: f32 = 0.1;
+/// Camera axes closer than this (as dot product) are considered the same.
+const CAMERA_AXIS_THRESHOLD: f32 = 0.999;
+
+/// Sends the state of the agent to the sim periodically.
+///
+/// This is a `Future` which has to be spawned on the reactor, it completes
+/// when the circuit to the sim is closed. The state can be changed through
+/// the handle returned by `state`.
+pub struct AgentUpdater {
+    state: Arc<Mutex<AgentState>>,
+    sender: MessageSender,
+    agent_id: Uuid,
+    session_id: Uuid,
+    config: AgentUpdaterConfig,
+    interval: Interval,
+
+    /// The last update sent and when it was sent.
+    last_update: Option<(AgentUpdate_AgentData, Instant)>,
+    duplicates: u32,
+    always_run: bool,
+    /// Sending the last update, used to notice when the circuit was closed.
+    sending: Option<SendMessage>,
+}
+
+impl AgentUpdater {
+    pub fn new(
+        sim: &Simulator,
+        state: AgentState,
+        config: AgentUpdaterConfig,
+        handle: &Handle,
+    ) -> Result<AgentUpdater, io::Error> {
+        let connect_info = sim.connect_info();
+        Ok(AgentUpdater {
+            state: Arc::new(Mutex::new(state)),
+            sender: sim.message_sender(),
+            agent_id: connect_info.agent_id.clone(),
+            session_id: connect_info.session_id.clone(),
+            interval: Interval::new(config.update_interval, handle)?,
+            config: config,
+            last_update: None,
+            duplicates: 0,
+            always_run: false,
+            sending: None,
+        })
+    }
+
+    /// The shared state of the agent, changes are sent with the next update.
+    pub fn state(&self) -> Arc<Mutex<AgentState>> {
+        Arc::clone(&self.state)
+    }
+
+    fn update(&mut self) {
+        let (update, running) = {
+            let state = self.state.lock().unwrap();
+            let update = state.to_update_message(self.agent_id.clone(), self.session_id.clone());
+            (update, state.modality == Modality::Running)
+        };
+
+        if running != self.always_run {
+            self.always_run = running;
+            let message = SetAlwaysRun {
+                agent_data: SetAlwaysRun_AgentData {
+                    agent_id: self.agent_id.clone(),
+                    session_id: self.session_id.clone(),
+                    always_run: running,
+                },
+            };
+            let _ = self.sender.send(message, true);
+        }
+
+        let now = Instant::now();
+        let send = match self.last_update {
+            None => true,
+            Some((ref last, sent_at)) => {
+                if differs(last, &update.agent_data) {
+                    self.duplicates = 0;
+                    true
+                } else if self.duplicates < DUPLICATE_UPDATES {
+                    self.duplicates += 1;
+                    true
+                } else {
+                    now.duration_since(sent_at) >= self.config.keep_alive_interval
+                }
+            }
+        };
+        if send {
+            self.last_update = Some((update.agent_data.clone(), now));
+            self.sending = Some(self.sender.send(update, false));
+        }
+    }
+}
+
+impl Future for AgentUpdater {
+    type Item = ();
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(), io::Error> {
+        match self.sending.as_mut().map(|sending| sending.poll()) {
+            Some(Err(SendMessageError::CircuitClosed)) => return Ok(Async::Ready(())),
+            Some(Ok(Async::NotReady)) | None => {}
+            Some(_) => self.sending = None,
+        }
+        loop {
+            match self.interval.poll()? {
+                Async::Ready(Some(())) => self.update(),
+                Async::Ready(None) => return Ok(Async::Ready(())),
+                Async::NotReady => return Ok(Async::NotReady),
+            }
+        }
+    }
+}
+
+/// Whether two updates differ enough for the new one to be sent.
+fn differs(a: &AgentUpdate_AgentData, b: &AgentUpdate_AgentData) -> bool {
+    let rotation_differs = |a: &Quaternion<f32>, b: &Quaternion<f32>| {
+        a.coords.dot(&b.coords).abs() < ROTATION_THRESHOLD
+    };
+    let axis_differs = |a: &Vector3<f32>, b: &Vector3<f32>| a.dot(b) < CAMERA_AXIS_THRESHOLD;
+
+    a.control_flags != b.control_flags
+        || a.flags != b.flags
+        || a.state != b.state
+        || a.far != b.far
+        || rotation_differs(&a.body_rotation, &b.body_rotation)
+        || rotation_differs(&a.head_rotation, &b.head_rotation)
+        || (a.camera_center - b.camera_center).norm() > CAMERA_THRESHOLD
+        || axis_differs(&a.camera_at_axis, &b.camera_at_axis)
+        || axis_differs(&a.camera_left_axis, &b.camera_left_axis)
+        || axis_differs(&a.camera_up_axis, &b.camera_up_axis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(state: &AgentState) -> AgentUpdate_AgentData {
+        state.to_update_message(Uuid::nil(), Uuid::nil()).agent_data
+    }
+
+    #[test]
+    fn change_detection() {
+        let mut state = AgentState::new(Vector3::new(128., 128., 20.));
+        let first = update(&state);
+        assert!(!differs(&first, &update(&state)));
+
+        // Small camera movements are ignored.
+        state.camera.center += Vector3::new(0.01, 0., 0.);
+        assert!(!differs(&first, &update(&state)));
+
+        state.camera.center += Vector3::new(1., 0., 0.);
+        assert!(differs(&first, &update(&state)));
+
+        let mut state = AgentState::new(Vector3::new(128., 128., 20.));
+        state.move_direction = Some(MoveDirection::Forward);
+        assert!(differs(&first, &update(&state)));
+
+        let mut state = AgentState::new(Vector3::new(128., 128., 20.));
+        state.body_rotation = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 0.5);
+        assert!(differs(&first, &update(&state)));
+    }
+
+    #[test]
+    fn running_control_flags() {
+        let mut state = AgentState::new(Vector3::new(0., 0., 0.));
+        state.move_direction = Some(MoveDirection::Forward);
+        state.modality = Modality::Running;
+        assert_eq!(state.to_control_flags(), ControlFlags::MOVE_FWD_POS);
+        state.modality = Modality::Flying;
+        assert_eq!(
+            state.to_control_flags(),
+            ControlFlags::MOVE_FWD_POS | ControlFlags::FLY
+        );
+    }
+}
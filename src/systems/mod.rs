@@ -3,6 +3,7 @@
 //! having to deal with the corresponding messages manually.
 
 pub mod agent_update;
+pub mod teleport;
 
 /*
 // TODO: Consider whether for our purposes we want to keep this composable, or just
@@ -0,0 +1,300 @@
+//! Teleporting the agent to another location.
+//!
+//! A teleport is requested from the sim the agent currently is in, which
+//! replies with `TeleportStart`, a number of `TeleportProgress` messages and
+//! finally either `TeleportFinish`, `TeleportLocal` (if the destination is in
+//! the same region) or `TeleportFailed`. After `TeleportFinish` the circuit to
+//! the destination sim is established and the agent is moved into the new
+//! region, just like after login.
+
+use circuit::message_handlers::Handlers;
+use circuit::Subscription;
+use failure::Error;
+use futures::{Async, Future, Poll, Stream};
+use grid_map::region_handle::RegionHandle;
+use logging::Log;
+use messages::all::{
+    TeleportLandmarkRequest, TeleportLandmarkRequest_Info, TeleportLocationRequest,
+    TeleportLocationRequest_AgentData, TeleportLocationRequest_Info, TeleportLureRequest,
+    TeleportLureRequest_Info,
+};
+use messages::MessageInstance;
+use simulator::{ConnectInfo, Simulator};
+use tokio_core::reactor::{Handle, Timeout};
+use types::{Duration, Uuid, Vector3};
+
+/// How long to wait for the sim to finish the teleport.
+const TELEPORT_TIMEOUT_SECS: u64 = 60;
+
+/// Where to teleport to.
+#[derive(Clone, Debug)]
+pub enum TeleportTarget {
+    /// A position in the region with the given handle.
+    Location {
+        region_handle: RegionHandle,
+        position: Vector3<f32>,
+        look_at: Vector3<f32>,
+    },
+    /// The location stored in a landmark of the inventory.
+    Landmark(Uuid),
+    /// The home location of the agent.
+    Home,
+    /// The location of an agent which offered a teleport, the id is the one
+    /// of the offer.
+    Lure(Uuid),
+}
+
+impl TeleportTarget {
+    fn to_message(&self, agent_id: Uuid, session_id: Uuid) -> MessageInstance {
+        match *self {
+            TeleportTarget::Location {
+                ref region_handle,
+                position,
+                look_at,
+            } => TeleportLocationRequest {
+                agent_data: TeleportLocationRequest_AgentData {
+                    agent_id: agent_id,
+                    session_id: session_id,
+                },
+                info: TeleportLocationRequest_Info {
+                    region_handle: region_handle.handle(),
+                    position: position,
+                    look_at: look_at,
+                },
+            }
+            .into(),
+            TeleportTarget::Landmark(ref landmark_id) => TeleportLandmarkRequest {
+                info: TeleportLandmarkRequest_Info {
+                    agent_id: agent_id,
+                    session_id: session_id,
+                    landmark_id: landmark_id.clone(),
+                },
+            }
+            .into(),
+            // The landmark with the nil id is the home location.
+            TeleportTarget::Home => TeleportLandmarkRequest {
+                info: TeleportLandmarkRequest_Info {
+                    agent_id: agent_id,
+                    session_id: session_id,
+                    landmark_id: Uuid::nil(),
+                },
+            }
+            .into(),
+            TeleportTarget::Lure(ref lure_id) => TeleportLureRequest {
+                info: TeleportLureRequest_Info {
+                    agent_id: agent_id,
+                    session_id: session_id,
+                    lure_id: lure_id.clone(),
+                    // TELEPORT_FLAGS_VIA_LURE
+                    teleport_flags: 1 << 2,
+                },
+            }
+            .into(),
+        }
+    }
+}
+
+#[derive(Debug, Fail)]
+pub enum TeleportError {
+    #[fail(display = "The teleport failed: {}", reason)]
+    Failed {
+        /// The reason given by the sim.
+        reason: String,
+        /// Additional messages for the user.
+        alerts: Vec<String>,
+    },
+
+    #[fail(display = "The sim did not finish the teleport in time.")]
+    Timeout,
+
+    #[fail(display = "The circuit was closed during the teleport.")]
+    CircuitClosed,
+
+    #[fail(display = "The sim sent an invalid destination: {}", 0)]
+    InvalidDestination(String),
+
+    #[fail(display = "Connecting to the destination failed: {}", 0)]
+    Connect(Error),
+}
+
+/// The states of a teleport in progress.
+pub enum TeleportProgress {
+    /// The sim started the teleport.
+    Started,
+    /// A status message of the sim.
+    Progress(String),
+    /// The agent was moved within the region, the teleport is done.
+    Local {
+        position: Vector3<f32>,
+        look_at: Vector3<f32>,
+    },
+    /// The agent arrived in the destination region, this is the sim of that
+    /// region.
+    ///
+    /// The previous sim keeps a child agent, it can be disconnected with
+    /// `Simulator::disconnect`.
+    Arrived(Simulator),
+}
+
+enum State {
+    Requested,
+    Connecting(Box<Future<Item = Simulator, Error = Error>>),
+    Done,
+}
+
+/// A teleport in progress, this is a `Stream` of its `TeleportProgress`
+/// which ends after `Local` or `Arrived`.
+pub struct Teleport {
+    messages: Subscription,
+    timeout: Timeout,
+    state: State,
+
+    connect_info: ConnectInfo,
+    handlers: Option<Handlers>,
+    handle: Handle,
+    log: Log,
+}
+
+impl Teleport {
+    /// Request the teleport from the sim the agent is in.
+    ///
+    /// `handlers` are the message handlers for the circuit to the
+    /// destination sim.
+    pub fn start(
+        sim: &Simulator,
+        target: TeleportTarget,
+        handlers: Handlers,
+        handle: Handle,
+        log: Log,
+    ) -> Result<Teleport, Error> {
+        // Subscribe before sending the request, so no reply is missed.
+        let messages = sim.subscribe_filter(Box::new(|msg: &MessageInstance| match *msg {
+            MessageInstance::TeleportStart(_)
+            | MessageInstance::TeleportProgress(_)
+            | MessageInstance::TeleportFinish(_)
+            | MessageInstance::TeleportLocal(_)
+            | MessageInstance::TeleportFailed(_) => true,
+            _ => false,
+        }));
+        let timeout = Timeout::new(Duration::from_secs(TELEPORT_TIMEOUT_SECS), &handle)?;
+
+        let connect_info = sim.connect_info().clone();
+        let message = target.to_message(
+            connect_info.agent_id.clone(),
+            connect_info.session_id.clone(),
+        );
+        // Sending is completed in the background.
+        let _ = sim.send_message(message, true);
+
+        Ok(Teleport {
+            messages: messages,
+            timeout: timeout,
+            state: State::Requested,
+            connect_info: connect_info,
+            handlers: Some(handlers),
+            handle: handle,
+            log: log,
+        })
+    }
+
+    fn handle_message(
+        &mut self,
+        msg: MessageInstance,
+    ) -> Result<Option<TeleportProgress>, TeleportError> {
+        match msg {
+            MessageInstance::TeleportStart(_) => Ok(Some(TeleportProgress::Started)),
+            MessageInstance::TeleportProgress(msg) => {
+                Ok(Some(TeleportProgress::Progress(text(&msg.info.message))))
+            }
+            MessageInstance::TeleportLocal(msg) => {
+                self.state = State::Done;
+                Ok(Some(TeleportProgress::Local {
+                    position: msg.info.position,
+                    look_at: msg.info.look_at,
+                }))
+            }
+            MessageInstance::TeleportFailed(msg) => {
+                self.state = State::Done;
+                Err(TeleportError::Failed {
+                    reason: text(&msg.info.reason),
+                    alerts: msg.alert_info.iter().map(|a| text(&a.message)).collect(),
+                })
+            }
+            MessageInstance::TeleportFinish(msg) => {
+                let connect_info = self
+                    .connect_info
+                    .for_teleport(&msg)
+                    .map_err(|e| TeleportError::InvalidDestination(format!("{}", e)))?;
+                info!(
+                    self.log.slog_logger(),
+                    "Teleport finished, connecting to {}:{}",
+                    connect_info.sim_ip,
+                    connect_info.sim_port
+                );
+                let connect = Simulator::connect(
+                    connect_info,
+                    self.handlers.take().unwrap_or_default(),
+                    self.handle.clone(),
+                    self.log.clone(),
+                );
+                self.state = State::Connecting(Box::new(connect));
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+impl Stream for Teleport {
+    type Item = TeleportProgress;
+    type Error = TeleportError;
+
+    fn poll(&mut self) -> Poll<Option<TeleportProgress>, TeleportError> {
+        loop {
+            let poll = match self.state {
+                State::Done => return Ok(Async::Ready(None)),
+                State::Connecting(ref mut connect) => connect.poll(),
+                State::Requested => {
+                    match self.messages.poll() {
+                        Ok(Async::Ready(Some(msg))) => match self.handle_message(msg)? {
+                            Some(progress) => return Ok(Async::Ready(Some(progress))),
+                            None => continue,
+                        },
+                        Ok(Async::Ready(None)) | Err(()) => {
+                            self.state = State::Done;
+                            return Err(TeleportError::CircuitClosed);
+                        }
+                        Ok(Async::NotReady) => {}
+                    }
+                    return match self.timeout.poll() {
+                        Ok(Async::NotReady) => Ok(Async::NotReady),
+                        _ => {
+                            self.state = State::Done;
+                            Err(TeleportError::Timeout)
+                        }
+                    };
+                }
+            };
+
+            // Connecting to the destination.
+            return match poll {
+                Ok(Async::NotReady) => Ok(Async::NotReady),
+                Ok(Async::Ready(sim)) => {
+                    self.state = State::Done;
+                    Ok(Async::Ready(Some(TeleportProgress::Arrived(sim))))
+                }
+                Err(e) => {
+                    self.state = State::Done;
+                    Err(TeleportError::Connect(e))
+                }
+            };
+        }
+    }
+}
+
+/// Decode a nul terminated string of a message.
+fn text(raw: &[u8]) -> String {
+    String::from_utf8_lossy(raw)
+        .trim_end_matches('\0')
+        .to_string()
+}
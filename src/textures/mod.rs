@@ -1,5 +1,5 @@
 //! Contains the texture manager.
-use capabilities::Capabilities;
+use capabilities::{Capabilities, Capability};
 use futures::{self, Future, Stream};
 use hyper;
 use hyper::header::CONTENT_TYPE;
@@ -57,7 +57,8 @@ impl From<::jpeg2000::error::DecodeError> for TextureServiceError {
 }
 
 pub struct TextureService {
-    get_texture: Url,
+    /// `None` if the sim did not grant the capability.
+    get_texture: Option<Url>,
     caches: Vec<RefCell<TextureCache>>,
     log: Log,
 }
@@ -65,12 +66,17 @@ pub struct TextureService {
 impl TextureService {
     pub fn new(caps: &Capabilities, log: Log) -> Self {
         TextureService {
-            get_texture: caps.urls().get_texture.clone(),
+            get_texture: caps.get(&Capability::GetTexture).cloned(),
             caches: Vec::new(),
             log: log,
         }
     }
 
+    /// Use the capabilities after they were refreshed.
+    pub fn update_capabilities(&mut self, caps: &Capabilities) {
+        self.get_texture = caps.get(&Capability::GetTexture).cloned();
+    }
+
     /// Register a TextureCache as the next layer in the cache hierarchy.
     ///
     /// Caches will be queried on lookup in the order they were inserted here.
@@ -91,16 +97,24 @@ impl TextureService {
         }
 
         // Get the texture from the network instead.
-        let url_res = self
-            .get_texture
-            .join(format!("?texture_id={}", id).as_str());
+        let get_texture = match self.get_texture {
+            Some(ref url) => url,
+            None => {
+                return Box::new(futures::future::result(Err(
+                    TextureServiceError::SimConfigError(
+                        "GetTexture capability not granted".to_string(),
+                    ),
+                )))
+            }
+        };
+        let url_res = get_texture.join(format!("?texture_id={}", id).as_str());
         let url = match url_res {
             Ok(u) => u,
             Err(_) => {
                 return Box::new(futures::future::result(Err(
                     TextureServiceError::SimConfigError(format!(
                         "get_texture url: {}",
-                        self.get_texture
+                        get_texture
                     )),
                 )))
             }
@@ -0,0 +1,17 @@
+//! The HTTP client used to talk to login servers, grids and sims.
+
+use hyper;
+use hyper::client::HttpConnector;
+use hyper_tls::{Error, HttpsConnector};
+
+/// A client for both `http` and `https` URIs.
+pub type Client = hyper::Client<HttpsConnector<HttpConnector>, hyper::Body>;
+
+/// Threads used to resolve host names.
+const DNS_THREADS: usize = 1;
+
+/// Create a client, which fails if the TLS backend can't be initialized.
+pub fn client() -> Result<Client, Error> {
+    let connector = HttpsConnector::new(DNS_THREADS)?;
+    Ok(hyper::Client::builder().build(connector))
+}
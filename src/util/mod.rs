@@ -1,12 +1,15 @@
 #![allow(unused)]
 
+use futures::sync::oneshot;
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, VecDeque};
 use std::sync::atomic::AtomicUsize;
 use std::sync::mpsc;
+use std::thread;
 use std::time::{Duration, Instant};
 
 pub mod bitsreader;
+pub mod http;
 
 #[cfg(test)]
 pub(crate) mod tests;
@@ -47,6 +50,19 @@ impl AtomicU32Counter {
     }
 }
 
+/// Returns a future which completes after `duration`.
+///
+/// Unlike `reactor::Timeout` this also fires if the future is only waited on
+/// and not run on a reactor, as the time is tracked by a thread of its own.
+pub fn delay(duration: Duration) -> oneshot::Receiver<()> {
+    let (sender, receiver) = oneshot::channel();
+    thread::spawn(move || {
+        thread::sleep(duration);
+        let _ = sender.send(());
+    });
+    receiver
+}
+
 pub struct FifoCache<T> {
     capacity: usize,
     data: VecDeque<T>,
@@ -0,0 +1,746 @@
+extern crate futures;
+extern crate opensim_networking;
+extern crate tokio_core;
+
+use opensim_networking::circuit::message_handlers::{self, HandlerContext, Handlers};
+use opensim_networking::circuit::CircuitEvent;
+use opensim_networking::event_queue::Event;
+use opensim_networking::grid_info::get_grid_info;
+use opensim_networking::grid_map::region_handle::RegionHandle;
+use opensim_networking::hypergrid;
+use opensim_networking::logging::{Log, LogLevel};
+use opensim_networking::login::{hash_password, login, LoginRequest, RegionAddress, StartLocation};
+use opensim_networking::messages::all::{
+    CrossedRegion, CrossedRegion_AgentData, CrossedRegion_Info, CrossedRegion_RegionData,
+    DisableSimulator, EnableSimulator, EnableSimulator_SimulatorInfo, ObjectUpdateCompressed,
+    ObjectUpdateCompressed_ObjectData, ObjectUpdateCompressed_RegionData, TeleportFailed,
+    TeleportFailed_AlertInfo, TeleportFailed_Info, TeleportFinish, TeleportFinish_Info,
+};
+use opensim_networking::messages::{MessageInstance, MessageType};
+use opensim_networking::mock_sim::{MockSimConfig, MockSimulator};
+use opensim_networking::neighbours::{NeighbourEvent, Neighbours};
+use opensim_networking::object_update::Primitive;
+use opensim_networking::packet::Packet;
+use opensim_networking::services::object_cache::ObjectCache;
+use opensim_networking::services::terrain::Receivers;
+use opensim_networking::simulator::{ConnectInfo, Simulator};
+use opensim_networking::systems::teleport::{
+    Teleport, TeleportError, TeleportProgress, TeleportTarget,
+};
+use opensim_networking::types::{Duration, Instant, Vector3};
+
+use futures::{Future, Stream};
+use std::cell::Cell;
+use std::env;
+use std::fs;
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use tokio_core::reactor::Core;
+
+fn test_log(name: &str) -> Log {
+    let dir = env::temp_dir().join("opensim_networking_tests").join(name);
+    Log::new_dir(dir, LogLevel::Debug).unwrap()
+}
+
+/// How long to wait for the mock sim or the client.
+fn timeout() -> Duration {
+    Duration::from_secs(5)
+}
+
+/// Connect to a sim, without running the reactor of `core`.
+fn connect(
+    connect_info: ConnectInfo,
+    handlers: Handlers,
+    core: &Core,
+    log_name: &str,
+) -> Simulator {
+    Simulator::connect(connect_info, handlers, core.handle(), test_log(log_name))
+        .wait()
+        .unwrap()
+}
+
+/// Run the reactor until `poll` returns a value, failing the test if there
+/// is none within `timeout`.
+fn turn_until<T, F>(core: &mut Core, timeout: Duration, what: &str, mut poll: F) -> T
+where
+    F: FnMut() -> Option<T>,
+{
+    let deadline = Instant::now() + timeout;
+    loop {
+        core.turn(Some(Duration::from_millis(50)));
+        if let Some(value) = poll() {
+            return value;
+        }
+        assert!(Instant::now() < deadline, "timed out waiting for {}", what);
+    }
+}
+
+/// Pass the events with the given name to the returned receiver.
+fn receive_events(handlers: &mut Handlers, name: &str) -> mpsc::Receiver<Event> {
+    let (events_tx, events_rx) = mpsc::channel();
+    let events_tx = Mutex::new(events_tx);
+    handlers.register_event(
+        name,
+        Box::new(move |event: Event, _context: &HandlerContext| {
+            events_tx.lock().unwrap().send(event).unwrap();
+            Ok(())
+        }),
+    );
+    events_rx
+}
+
+/// Body of an `EstablishAgentCommunication` event for a made up sim.
+const ESTABLISH_AGENT_COMMUNICATION: &str = "<map>\
+     <key>agent-id</key><uuid>10b2de5f-2030-4ac4-ab53-9a8f082af748</uuid>\
+     <key>sim-ip-and-port</key><string>127.0.0.1:9001</string>\
+     <key>seed-capability</key><string>http://127.0.0.1:9000/CAPS/abc/</string>\
+     </map>";
+
+#[test]
+fn login_connect_logout() {
+    let mock = MockSimulator::start(MockSimConfig::default()).unwrap();
+
+    let request = LoginRequest {
+        first_name: "Test".to_string(),
+        last_name: "User".to_string(),
+        password_hash: hash_password("password"),
+        start: StartLocation::Last,
+        options: LoginRequest::default_options(),
+    };
+    let mut core = Core::new().unwrap();
+    let response = core
+        .run(login(request, mock.login_uri().parse().unwrap()))
+        .unwrap();
+    assert_eq!(response.agent_id, mock.config().agent_id);
+    assert_eq!(response.circuit_code, mock.config().circuit_code);
+    assert_eq!(response.first_name, "Test");
+    assert_eq!(response.region_x, 256_000);
+    let sim = connect(
+        ConnectInfo::from(response),
+        Handlers::default(),
+        &core,
+        "login_connect_logout",
+    );
+    assert_eq!(sim.region_info().sim_name, "testland");
+    assert_eq!(
+        sim.region_info().region_id,
+        mock.config().region_handshake.region_info2.region_id
+    );
+    let position = sim.agent_position().unwrap();
+    assert_eq!(position.region_handle, mock.config().region_handle);
+    assert_eq!(position.position, mock.config().agent_position);
+
+    sim.logout(timeout()).wait().unwrap();
+
+    assert!(mock
+        .wait_for(MessageType::CloseCircuit, Duration::from_secs(1))
+        .is_some());
+    let received: Vec<_> = mock
+        .received()
+        .iter()
+        .map(|msg| msg.message_type())
+        .collect();
+    for expected in &[
+        MessageType::UseCircuitCode,
+        MessageType::RegionHandshakeReply,
+        MessageType::CompleteAgentMovement,
+        MessageType::AgentThrottle,
+        MessageType::LogoutRequest,
+    ] {
+        assert!(received.contains(expected), "missing {:?}", expected);
+    }
+}
+
+#[test]
+fn grid_info_points_to_login() {
+    let mock = MockSimulator::start(MockSimConfig::default()).unwrap();
+
+    let mut core = Core::new().unwrap();
+    let info = core
+        .run(get_grid_info(mock.grid_uri().parse().unwrap()))
+        .unwrap();
+    assert_eq!(info.login.as_str(), mock.login_uri());
+    assert_eq!(info.grid_name, Some("Mock Grid".to_string()));
+}
+
+#[test]
+fn hypergrid_links_region() {
+    let mock = MockSimulator::start(MockSimConfig::default()).unwrap();
+
+    let mut core = Core::new().unwrap();
+    let linked = core
+        .run(hypergrid::link_region(
+            mock.gatekeeper(),
+            "testland".to_string(),
+        ))
+        .unwrap();
+    let region_id = mock
+        .config()
+        .region_handshake
+        .region_info2
+        .region_id
+        .clone();
+    assert_eq!(linked.region_id, region_id);
+    assert_eq!(linked.region_handle.xy(), (256_000, 256_000));
+    assert_eq!(linked.external_name, "mock:testland");
+}
+
+#[test]
+fn hypergrid_teleport_uses_hyperlink() {
+    let mock = MockSimulator::start(MockSimConfig::default()).unwrap();
+
+    let mut core = Core::new().unwrap();
+    let sim = connect(
+        mock.connect_info(),
+        Handlers::default(),
+        &core,
+        "hypergrid_teleport_uses_hyperlink",
+    );
+
+    let replies =
+        sim.subscribe_filter(Box::new(|msg: &MessageInstance| match msg.message_type() {
+            MessageType::TeleportStart | MessageType::TeleportFailed => true,
+            _ => false,
+        }));
+    let address: RegionAddress = "grid.example.org:8002:Welcome".parse().unwrap();
+    let handle = core
+        .run(sim.teleport_hypergrid(
+            address,
+            Vector3::new(128., 128., 30.),
+            Vector3::new(1., 0., 0.),
+        ))
+        .unwrap();
+    match mock.wait_for(MessageType::TeleportLocationRequest, timeout()) {
+        Some(MessageInstance::TeleportLocationRequest(request)) => {
+            assert_eq!(request.info.region_handle, handle.handle());
+        }
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+
+    // The sim only teleports to the hyperlinks it created.
+    match core.run(replies.into_future()) {
+        Ok((Some(MessageInstance::TeleportStart(_)), _)) => {}
+        Ok((msg, _)) => panic!("unexpected message: {:?}", msg),
+        Err(_) => panic!("the circuit was closed"),
+    }
+
+    sim.logout(timeout()).wait().unwrap();
+}
+
+#[test]
+fn terrain_service_receives_layer_data() {
+    let layer_data = Packet::read(include_bytes!("data/layer_land.bin"))
+        .unwrap()
+        .message;
+    let mut config = MockSimConfig::default();
+    config.after_movement.push(layer_data);
+    let mock = MockSimulator::start(config).unwrap();
+
+    let mut core = Core::new().unwrap();
+    let sim = connect(
+        mock.connect_info(),
+        Handlers::default(),
+        &core,
+        "terrain_service_receives_layer_data",
+    );
+
+    let region_id = sim.region_info().region_id.clone();
+    let mut receivers = Receivers::new();
+    receivers
+        .register(region_id.clone(), &sim.services().terrain)
+        .unwrap();
+
+    // The patches are decoded in the background, keep the reactor running
+    // until they arrive.
+    let patches = Cell::new(0);
+    turn_until(&mut core, timeout(), "terrain patches", || {
+        receivers.receive_patches(|id, _patch| {
+            assert_eq!(id, &region_id);
+            patches.set(patches.get() + 1);
+        });
+        if patches.get() > 0 {
+            Some(())
+        } else {
+            None
+        }
+    });
+}
+
+/// Connect to a new mock sim using the object cache in `dir`.
+fn connect_with_object_cache(
+    config: MockSimConfig,
+    dir: &::std::path::Path,
+    core: &Core,
+    log_name: &str,
+) -> (MockSimulator, Simulator) {
+    let mock = MockSimulator::start(config).unwrap();
+    let mut connect_info = mock.connect_info();
+    connect_info.object_cache = Some(ObjectCache::new(dir));
+    let sim = connect(connect_info, Handlers::default(), core, log_name);
+    (mock, sim)
+}
+
+fn handshake_reply_flags(mock: &MockSimulator) -> u32 {
+    match mock.wait_for(MessageType::RegionHandshakeReply, Duration::from_secs(1)) {
+        Some(MessageInstance::RegionHandshakeReply(reply)) => reply.region_info.flags,
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+}
+
+/// A full update of a single object.
+fn object_update_compressed(config: &MockSimConfig, data: &[u8]) -> MessageInstance {
+    ObjectUpdateCompressed {
+        region_data: ObjectUpdateCompressed_RegionData {
+            region_handle: config.region_handle.handle(),
+            time_dilation: 0xffff,
+        },
+        object_data: vec![ObjectUpdateCompressed_ObjectData {
+            update_flags: 0,
+            data: data.to_vec(),
+        }],
+    }
+    .into()
+}
+
+/// Wait until the object service of the sim knows the object.
+fn wait_for_object(sim: &Simulator, local_id: u32) -> Primitive {
+    let deadline = Instant::now() + timeout();
+    loop {
+        if let Some(object) = sim.services().objects.get(local_id) {
+            return object;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "object {} not received",
+            local_id
+        );
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+#[test]
+fn object_cache_requests_misses() {
+    let dir = env::temp_dir()
+        .join("opensim_networking_tests")
+        .join("object_cache_requests_misses");
+    let _ = fs::remove_dir_all(&dir);
+    let core = Core::new().unwrap();
+    let box_data = include_bytes!("data/object_update_compressed_box.bin");
+    let tree_data = include_bytes!("data/object_update_compressed_tree.bin");
+
+    // Without a cache all objects are requested, the box is cached.
+    let mut config = MockSimConfig::default();
+    let update = object_update_compressed(&config, box_data);
+    config.objects.push(update);
+    let (mock, sim) = connect_with_object_cache(config, &dir, &core, "object_cache_first_visit");
+    assert_eq!(handshake_reply_flags(&mock), 1);
+    wait_for_object(&sim, 1_234_567);
+    sim.logout(timeout()).wait().unwrap();
+    assert!(mock
+        .received()
+        .iter()
+        .all(|msg| msg.message_type() != MessageType::RequestMultipleObjects));
+
+    // Afterwards the sim only sends the CRCs, only the tree which was not
+    // cached yet is requested.
+    let mut config = MockSimConfig::default();
+    let updates = vec![
+        object_update_compressed(&config, box_data),
+        object_update_compressed(&config, tree_data),
+    ];
+    config.objects.extend(updates);
+    let (mock, sim) = connect_with_object_cache(config, &dir, &core, "object_cache_second_visit");
+    assert_eq!(handshake_reply_flags(&mock), 0);
+    match mock.wait_for(MessageType::RequestMultipleObjects, timeout()) {
+        Some(MessageInstance::RequestMultipleObjects(request)) => {
+            let ids: Vec<_> = request.object_data.iter().map(|object| object.id).collect();
+            assert_eq!(ids, vec![7_654_321]);
+        }
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+    let object = wait_for_object(&sim, 1_234_567);
+    assert_eq!(object.text.unwrap().text, "Hello");
+    wait_for_object(&sim, 7_654_321);
+    sim.logout(timeout()).wait().unwrap();
+}
+
+#[test]
+fn logout_times_out_without_reply() {
+    let mut config = MockSimConfig::default();
+    config.logout_reply = false;
+    let mock = MockSimulator::start(config).unwrap();
+    let core = Core::new().unwrap();
+    let sim = connect(
+        mock.connect_info(),
+        Handlers::default(),
+        &core,
+        "logout_times_out_without_reply",
+    );
+
+    // The reactor is not turned, the timeout has to fire anyway.
+    let start = Instant::now();
+    sim.logout(Duration::from_millis(500)).wait().unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(500));
+    assert!(mock
+        .wait_for(MessageType::CloseCircuit, Duration::from_secs(1))
+        .is_some());
+}
+
+#[test]
+fn dropping_simulator_closes_circuit() {
+    let mock = MockSimulator::start(MockSimConfig::default()).unwrap();
+    let core = Core::new().unwrap();
+    let sim = connect(
+        mock.connect_info(),
+        Handlers::default(),
+        &core,
+        "dropping_simulator_closes_circuit",
+    );
+
+    drop(sim);
+    assert!(mock
+        .wait_for(MessageType::CloseCircuit, Duration::from_secs(1))
+        .is_some());
+}
+
+#[test]
+fn event_queue_dispatches_events() {
+    let mock = MockSimulator::start(MockSimConfig::default()).unwrap();
+    mock.send_event("EstablishAgentCommunication", ESTABLISH_AGENT_COMMUNICATION);
+    let mut handlers = Handlers::default();
+    let events = receive_events(&mut handlers, "EstablishAgentCommunication");
+
+    let mut core = Core::new().unwrap();
+    let sim = connect(
+        mock.connect_info(),
+        handlers,
+        &core,
+        "event_queue_dispatches_events",
+    );
+
+    // The poller runs on the reactor.
+    match turn_until(&mut core, timeout(), "event", || events.try_recv().ok()) {
+        Event::EstablishAgentCommunication(e) => {
+            assert_eq!(e.sim_address, "127.0.0.1:9001".parse().unwrap());
+        }
+        e => panic!("wrong event: {:?}", e),
+    }
+
+    sim.logout(timeout()).wait().unwrap();
+
+    // The poller releases the queue once its pending request returns.
+    turn_until(&mut core, timeout(), "event queue release", || {
+        if mock.event_queue_released() {
+            Some(())
+        } else {
+            None
+        }
+    });
+}
+
+#[test]
+fn event_queue_retries_failed_requests() {
+    let mut config = MockSimConfig::default();
+    config.event_queue_failures = 2;
+    let mock = MockSimulator::start(config).unwrap();
+    mock.send_event("EstablishAgentCommunication", ESTABLISH_AGENT_COMMUNICATION);
+    let mut handlers = Handlers::default();
+    let events = receive_events(&mut handlers, "EstablishAgentCommunication");
+
+    let mut core = Core::new().unwrap();
+    let sim = connect(
+        mock.connect_info(),
+        handlers,
+        &core,
+        "event_queue_retries_failed_requests",
+    );
+
+    // Both failures are retried after a delay.
+    turn_until(&mut core, timeout() * 2, "event", || events.try_recv().ok());
+
+    sim.logout(timeout()).wait().unwrap();
+}
+
+#[test]
+fn handler_errors_are_reported() {
+    let mock = MockSimulator::start(MockSimConfig::default()).unwrap();
+    let mut handlers = Handlers::default();
+    handlers.register_type(
+        MessageType::DisableSimulator,
+        Box::new(|msg: MessageInstance, _context: &HandlerContext| {
+            Err(message_handlers::Error {
+                msg: msg,
+                kind: message_handlers::ErrorKind::WrongHandler,
+            })
+        }),
+    );
+
+    let core = Core::new().unwrap();
+    let sim = connect(
+        mock.connect_info(),
+        handlers,
+        &core,
+        "handler_errors_are_reported",
+    );
+    let events = sim.circuit_events();
+
+    mock.send(DisableSimulator {});
+    loop {
+        match events.recv_timeout(timeout()) {
+            Ok(CircuitEvent::HandlerError(err)) => {
+                assert_eq!(err.msg.message_type(), MessageType::DisableSimulator);
+                break;
+            }
+            Ok(_) => continue,
+            Err(_) => panic!("handler error was not reported"),
+        }
+    }
+
+    sim.logout(timeout()).wait().unwrap();
+}
+
+#[test]
+fn neighbours_connect_and_disconnect() {
+    let root = MockSimulator::start(MockSimConfig::default()).unwrap();
+    let neighbour = MockSimulator::start(MockSimConfig::default()).unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mut handlers = Handlers::default();
+    let neighbours = Neighbours::register(
+        &mut handlers,
+        RegionHandle::from_xy(256_000, 256_000),
+        root.connect_info(),
+        Box::new(|_: &RegionHandle| Handlers::default()),
+        core.handle(),
+        test_log("neighbours_child"),
+    );
+    let sim = connect(root.connect_info(), handlers, &core, "neighbours_root");
+
+    let (events_tx, events_rx) = mpsc::channel();
+    core.handle().spawn(neighbours.for_each(move |event| {
+        events_tx.send(event).unwrap();
+        Ok(())
+    }));
+    let next_event = |core: &mut Core| {
+        turn_until(core, timeout(), "neighbour event", || {
+            events_rx.try_recv().ok()
+        })
+    };
+
+    // Announce the neighbour like a real sim would.
+    let info = neighbour.connect_info();
+    let region = RegionHandle::from_xy(256_256, 256_000);
+    root.send(EnableSimulator {
+        simulator_info: EnableSimulator_SimulatorInfo {
+            handle: region.handle(),
+            ip: info.sim_ip,
+            port: info.sim_port,
+        },
+    });
+    root.send_event(
+        "EstablishAgentCommunication",
+        &format!(
+            "<map>\
+             <key>agent-id</key><uuid>{}</uuid>\
+             <key>sim-ip-and-port</key><string>{}:{}</string>\
+             <key>seed-capability</key><string>{}</string>\
+             </map>",
+            info.agent_id, info.sim_ip, info.sim_port, info.capabilities_seed
+        ),
+    );
+    match next_event(&mut core) {
+        NeighbourEvent::Connected(r) => assert_eq!(r, region),
+        e => panic!("unexpected event: {:?}", e),
+    }
+    // Child agents must not be moved into the region.
+    assert!(neighbour
+        .wait_for(MessageType::UseCircuitCode, Duration::from_secs(1))
+        .is_some());
+    assert!(!neighbour
+        .received()
+        .iter()
+        .any(|msg| msg.message_type() == MessageType::CompleteAgentMovement));
+
+    neighbour.send(DisableSimulator {});
+    match next_event(&mut core) {
+        NeighbourEvent::Disconnected(r) => assert_eq!(r, region),
+        e => panic!("unexpected event: {:?}", e),
+    }
+
+    sim.logout(timeout()).wait().unwrap();
+}
+
+#[test]
+fn teleport_to_other_sim() {
+    let region = RegionHandle::from_xy(256_256, 256_000);
+    let mut config = MockSimConfig::default();
+    config.teleport_regions.push(region.clone());
+    let origin = MockSimulator::start(config).unwrap();
+    let destination = MockSimulator::start(MockSimConfig::default()).unwrap();
+
+    let mut core = Core::new().unwrap();
+    let sim = connect(
+        origin.connect_info(),
+        Handlers::default(),
+        &core,
+        "teleport_origin",
+    );
+
+    let teleport = Teleport::start(
+        &sim,
+        TeleportTarget::Location {
+            region_handle: region.clone(),
+            position: Vector3::new(128., 128., 30.),
+            look_at: Vector3::new(1., 0., 0.),
+        },
+        Handlers::default(),
+        core.handle(),
+        test_log("teleport_destination"),
+    )
+    .unwrap();
+    assert!(origin
+        .wait_for(MessageType::TeleportLocationRequest, timeout())
+        .is_some());
+
+    // The origin replied with TeleportStart.
+    let info = destination.connect_info();
+    origin.send(TeleportFinish {
+        info: TeleportFinish_Info {
+            agent_id: info.agent_id.clone(),
+            location_id: 0,
+            sim_ip: info.sim_ip,
+            sim_port: info.sim_port,
+            region_handle: region.handle(),
+            seed_capability: format!("{}\0", info.capabilities_seed).into_bytes(),
+            sim_access: 13,
+            teleport_flags: 0,
+            region_size_x: 256,
+            region_size_y: 256,
+        },
+    });
+
+    let progress = core.run(teleport.collect()).unwrap();
+    assert!(match progress[0] {
+        TeleportProgress::Started => true,
+        _ => false,
+    });
+    let arrived = match progress.into_iter().last() {
+        Some(TeleportProgress::Arrived(arrived)) => arrived,
+        _ => panic!("teleport did not arrive"),
+    };
+    assert_eq!(arrived.connect_info().sim_port, info.sim_port);
+    assert!(destination
+        .wait_for(MessageType::CompleteAgentMovement, Duration::from_secs(1))
+        .is_some());
+
+    sim.disconnect().unwrap();
+    arrived.logout(timeout()).wait().unwrap();
+}
+
+#[test]
+fn teleport_failed() {
+    let origin = MockSimulator::start(MockSimConfig::default()).unwrap();
+
+    let mut core = Core::new().unwrap();
+    let sim = connect(
+        origin.connect_info(),
+        Handlers::default(),
+        &core,
+        "teleport_failed",
+    );
+
+    let teleport = Teleport::start(
+        &sim,
+        TeleportTarget::Home,
+        Handlers::default(),
+        core.handle(),
+        test_log("teleport_failed"),
+    )
+    .unwrap();
+    assert!(origin
+        .wait_for(MessageType::TeleportLandmarkRequest, timeout())
+        .is_some());
+    origin.send(TeleportFailed {
+        info: TeleportFailed_Info {
+            agent_id: origin.connect_info().agent_id,
+            reason: b"no home\0".to_vec(),
+        },
+        alert_info: vec![TeleportFailed_AlertInfo {
+            message: b"Region down".to_vec(),
+            extra_params: Vec::new(),
+        }],
+    });
+
+    match core.run(teleport.collect()) {
+        Err(TeleportError::Failed { reason, alerts }) => {
+            assert_eq!(reason, "no home");
+            assert_eq!(alerts, vec!["Region down".to_string()]);
+        }
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("teleport did not fail"),
+    }
+
+    sim.logout(timeout()).wait().unwrap();
+}
+
+#[test]
+fn neighbours_cross_region() {
+    let root = MockSimulator::start(MockSimConfig::default()).unwrap();
+    let neighbour = MockSimulator::start(MockSimConfig::default()).unwrap();
+
+    let mut core = Core::new().unwrap();
+    let mut handlers = Handlers::default();
+    let from = RegionHandle::from_xy(256_000, 256_000);
+    let neighbours = Neighbours::register(
+        &mut handlers,
+        from.clone(),
+        root.connect_info(),
+        Box::new(|_: &RegionHandle| Handlers::default()),
+        core.handle(),
+        test_log("cross_region_child"),
+    );
+    let sim = connect(root.connect_info(), handlers, &core, "cross_region_root");
+
+    let (events_tx, events_rx) = mpsc::channel();
+    core.handle().spawn(neighbours.for_each(move |event| {
+        events_tx.send(event).unwrap();
+        Ok(())
+    }));
+
+    // Cross before the neighbour was announced, so it is connected directly.
+    let info = neighbour.connect_info();
+    let to = RegionHandle::from_xy(256_256, 256_000);
+    root.send(CrossedRegion {
+        agent_data: CrossedRegion_AgentData {
+            agent_id: info.agent_id.clone(),
+            session_id: info.session_id.clone(),
+        },
+        region_data: CrossedRegion_RegionData {
+            sim_ip: info.sim_ip,
+            sim_port: info.sim_port,
+            region_handle: to.handle(),
+            seed_capability: info.capabilities_seed.to_string().into_bytes(),
+        },
+        info: CrossedRegion_Info {
+            position: Vector3::new(2., 128., 25.),
+            look_at: Vector3::new(1., 0., 0.),
+        },
+    });
+
+    let event = turn_until(&mut core, timeout(), "crossing", || {
+        events_rx.try_recv().ok()
+    });
+    let crossing = match event {
+        NeighbourEvent::Crossed(crossing) => crossing,
+        e => panic!("unexpected event: {:?}", e),
+    };
+    assert_eq!(crossing.from, from);
+    assert_eq!(crossing.to, to);
+    assert_eq!(crossing.position, Vector3::new(2., 128., 25.));
+    assert!(neighbour
+        .wait_for(MessageType::CompleteAgentMovement, Duration::from_secs(1))
+        .is_some());
+
+    sim.disconnect().unwrap();
+    crossing.sim.logout(timeout()).wait().unwrap();
+}
@@ -1,9 +1,85 @@
 extern crate opensim_networking;
 
+use opensim_networking::messages::all::ObjectUpdateCompressed_ObjectData;
+use opensim_networking::object_update::{PCode, PathCurve, Primitive, ProfileCurve};
 use opensim_networking::packet::Packet;
+use opensim_networking::types::{Uuid, Vector3, Vector4};
 
 #[test]
 fn packet_appended_acks() {
     let data = include_bytes!("data/appended_acks.bin");
     Packet::read(data).unwrap();
 }
+
+// The object_update_compressed_* fixtures are laid out in the field order
+// written by OpenSim's `CreateCompressedUpdateBlock`, where the profile
+// curve follows the path parameters.
+#[test]
+fn object_update_compressed_box() {
+    let data = ObjectUpdateCompressed_ObjectData {
+        update_flags: 0,
+        data: include_bytes!("data/object_update_compressed_box.bin").to_vec(),
+    };
+    let prim = Primitive::from_compressed(&data).unwrap();
+    assert_eq!(
+        prim.full_id,
+        Uuid::parse_str("0c2ef5b7-3b28-4c0b-b4f7-29d0ccb1b3d1").unwrap()
+    );
+    assert_eq!(prim.local_id, 1_234_567);
+    assert_eq!(prim.parent_id, 1_234_566);
+    assert_eq!(prim.pcode, PCode::Primitive);
+    assert_eq!(prim.crc, 42);
+    assert_eq!(prim.scale, Vector3::new(0.5, 0.5, 0.5));
+    assert_eq!(prim.motion.position, Vector3::new(0., 0., 0.75));
+    assert!((prim.motion.rotation.coords.w - 0.8).abs() < 1e-6);
+
+    let text = prim.text.unwrap();
+    assert_eq!(text.text, "Hello");
+    assert_eq!(text.color, Vector4::new(255, 0, 0, 255));
+    let light = prim.extra_params.light.unwrap();
+    assert_eq!(light.radius, 10.);
+    let sound = prim.sound.unwrap();
+    assert_eq!(
+        sound.sound_id,
+        Uuid::parse_str("ed124764-705d-d497-167a-182cd9fa2e6c").unwrap()
+    );
+    assert_eq!(sound.radius, 20.);
+
+    assert_eq!(prim.shape.path.curve, PathCurve::Line);
+    assert_eq!(prim.shape.path.begin, 0.);
+    assert_eq!(prim.shape.path.scale_x, 1.);
+    assert_eq!(prim.shape.path.scale_y, 1.);
+    assert_eq!(prim.shape.profile.curve, ProfileCurve::Square);
+    assert_eq!(prim.shape.profile.hollow, 0.);
+    assert_eq!(
+        prim.texture_entry.unwrap().default.texture_id,
+        Uuid::parse_str("89556747-24cb-43ed-920b-47caed15465f").unwrap()
+    );
+    assert!(prim.particle_system.is_none());
+    assert!(prim.name_values.is_empty());
+}
+
+#[test]
+fn object_update_compressed_tree() {
+    let data = ObjectUpdateCompressed_ObjectData {
+        update_flags: 0,
+        data: include_bytes!("data/object_update_compressed_tree.bin").to_vec(),
+    };
+    let prim = Primitive::from_compressed(&data).unwrap();
+    assert_eq!(prim.pcode, PCode::Tree);
+    assert_eq!(prim.parent_id, 0);
+    assert_eq!(prim.data, vec![5]);
+    assert_eq!(prim.motion.position, Vector3::new(50., 60., 22.));
+    assert!(prim.text.is_none());
+    assert!(prim.texture_entry.is_none());
+}
+
+#[test]
+fn object_update_compressed_truncated() {
+    let data = include_bytes!("data/object_update_compressed_box.bin");
+    let data = ObjectUpdateCompressed_ObjectData {
+        update_flags: 0,
+        data: data[..150].to_vec(),
+    };
+    assert!(Primitive::from_compressed(&data).is_err());
+}